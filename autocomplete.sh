@@ -2,14 +2,20 @@
 # https://github.com/benbrunton/kanben
 
 _kanben_completion() {
-    COMMANDS="add clear-done complete delete edit help now reindex start tag tasks top view"
+    COMMANDS="add boards bottom clear-done column complete delete down edit fsck gc help history init migrate-store move move-board now overview plan redo reindex rename restore start tag tasks top trash undo up view workflow"
     COMMANDS_WITH_TASK_PARAM=(
+        "bottom"
         "complete"
         "delete" 
+        "down"
         "edit"
+        "move"
+        "move-board"
+        "rename"
         "start"
         "tag"
         "top"
+        "up"
         "view"
     )
 
@@ -25,7 +31,7 @@ _kanben_completion() {
         for i in "${COMMANDS_WITH_TASK_PARAM[@]}"
         do
             if [ "$i" == "${COMP_WORDS[1]}" ]; then
-                COMPREPLY=($(compgen -W "$(kanben tasks)" "${COMP_WORDS[2]}"))
+                COMPREPLY=($(compgen -W "$(kanben tasks | sed 's/ (.*)$//')" "${COMP_WORDS[2]}"))
             fi
         done
     fi
@@ -6,9 +6,13 @@ use zip::ZipWriter;
 use fs_extra::dir::{copy, CopyOptions};
 use log::info;
 use walkdir::WalkDir;
+use crate::error::KanbenError;
 
 pub trait Archive {
-    fn write(&self) -> File;
+    fn write(&self) -> Result<File, KanbenError>;
+    // copies the data folder alongside itself and returns
+    // where the copy was put
+    fn snapshot(&self, name: &str) -> Result<String, KanbenError>;
 }
 
 pub struct ZipArchive {
@@ -20,14 +24,18 @@ impl ZipArchive {
         ZipArchive{ path: path.to_owned() }
     }
 
-    fn get_new_file(&self, filepath: &str) -> File {
+    fn get_new_file(&self, filepath: &str) -> Result<File, KanbenError> {
         info!("create file: [{}]", &filepath);
-        File::create(&filepath).unwrap()
+        File::create(&filepath).map_err(archive_error)
     }
 }
 
+fn archive_error<E: std::fmt::Display>(e: E) -> KanbenError {
+    KanbenError::Archive(e.to_string())
+}
+
 impl Archive for ZipArchive {
-    fn write(&self) -> File {
+    fn write(&self) -> Result<File, KanbenError> {
         let tmp_archive_dir = ".kanben-archive";
         let target_dir = Path::new("/tmp");
         let inside_target_dir = target_dir.join(&tmp_archive_dir);
@@ -39,18 +47,19 @@ impl Archive for ZipArchive {
         options.copy_inside = true;
 
         copy(&self.path, &inside_target_dir, &options)
-            .expect("unable to copy config directory");
+            .map_err(archive_error)?;
 
         info!("directory copied to /tmp");
         info!("creating archive file");
 
         let timestamp = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH).unwrap()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(archive_error)?
             .as_millis();
         let filepath = format!("/tmp/kb-{}.archive", timestamp);
 
-        let file = self.get_new_file(&filepath);
-        let zip_file = file.try_clone().unwrap();
+        let file = self.get_new_file(&filepath)?;
+        let zip_file = file.try_clone().map_err(archive_error)?;
         let mut zip = ZipWriter::new(zip_file);
         let options = zip::write::FileOptions::default()
             .compression_method(zip::CompressionMethod::Stored);
@@ -67,13 +76,13 @@ impl Archive for ZipArchive {
             info!("starting on {}", &path.display());
             let name = path.strip_prefix(
                 Path::new(prefix)
-            ).expect("unable to strip prefix");
+            ).map_err(archive_error)?;
 
             if path.is_file() {
                 info!("adding file {:?} as {:?} ...", path, name);
                 let _ = zip.start_file_from_path(name, options);
                 let mut f = File::open(path)
-                    .expect("unable to open path");
+                    .map_err(archive_error)?;
 
                 let _ = f.read_to_end(&mut buffer);
                 let _ = zip.write_all(&*buffer);
@@ -85,16 +94,33 @@ impl Archive for ZipArchive {
             info!("{}", entry.path().display());
         }
 
-        let _ = zip.finish();
+        zip.finish().map_err(archive_error)?;
 
         info!("zip complete. Begin cleanup");
 
         remove_dir_all(&inside_target_dir)
-            .expect("unable to clean up");
+            .map_err(archive_error)?;
 
 
         info!("cleanup complete!");
 
-        File::open(&filepath).unwrap()
+        File::open(&filepath).map_err(archive_error)
+    }
+
+    fn snapshot(&self, name: &str) -> Result<String, KanbenError> {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(archive_error)?
+            .as_secs();
+        let target = format!("{}-{}-{}", self.path, name, timestamp);
+
+        info!("copying data dir to {}", &target);
+        let mut options = CopyOptions::new();
+        options.copy_inside = true;
+
+        copy(&self.path, &target, &options)
+            .map_err(archive_error)?;
+
+        Ok(target)
     }
 }
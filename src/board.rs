@@ -1,43 +1,367 @@
-use crate::store::Store;
-use crate::opts::{Task, Column};
+use crate::store::{Store, Transaction};
+use crate::error::KanbenError;
+use crate::opts::{Task, Times};
+use crate::clock;
+use log::error;
+
+mod config;
+mod fsck;
+mod trash;
+mod journal;
+mod history;
+mod pending;
+pub use config::{BoardConfig, Transition, CONFIG_KEY, column_label};
+pub use fsck::Problem;
+pub use trash::Trashed;
+pub use journal::{Entry, Change};
+pub use history::{Event, EventKind, user};
+pub use pending::Pending;
 
 pub trait BoardAccess {
     fn get_all_tasks(&self) -> Vec<Task>;
     fn get_column(&self, col: &str, tag: Option<String>) -> Vec<Task>;
-    fn create_task(&mut self, key: &str, tag: Option<String>);
-    fn update(&mut self, key: &str, task: Task);
+    fn create_task(
+        &mut self, key: &str, tag: Option<String>
+    ) -> Result<(), KanbenError>;
+    fn update(&mut self, key: &str, task: Task) -> Result<(), KanbenError>;
+    fn force_update(
+        &mut self, key: &str, task: Task
+    ) -> Result<(), KanbenError>;
     fn get(&self, key: &str) -> Option<Task>;
-    fn remove(&mut self, key: &str);
-    fn reindex_columns(&mut self) -> Result<usize, ()>;
-    fn top_priority(&mut self, key: &str);
+    fn find_by_id(&self, id: u64) -> Option<Task>;
+    fn remove(&mut self, key: &str) -> Result<(), KanbenError>;
+    fn delete(&mut self, key: &str) -> Result<(), KanbenError>;
+    fn trashed(&self) -> Vec<Trashed>;
+    fn restore(&mut self, key: &str) -> Result<Task, KanbenError>;
+    fn empty_trash(&mut self, keys: &[String]) -> Result<(), KanbenError>;
+    fn journal(&self) -> Vec<Entry>;
+    fn undo(&mut self) -> Result<Option<Entry>, KanbenError>;
+    fn redo(&mut self) -> Result<Option<Entry>, KanbenError>;
+    fn history(&self, key: &str) -> Vec<Event>;
+    fn all_history(&self) -> Vec<Event>;
+    fn described(&mut self, key: &str) -> Result<(), KanbenError>;
+    fn set_description(
+        &mut self, key: &str, path: &str
+    ) -> Result<(), KanbenError>;
+    // columns left over their limits by changes since this was
+    // last asked, so they can be pointed out
+    fn take_over_limit(&mut self) -> Vec<OverLimit>;
+    // makes several changes as one, saved and undone together.
+    // if any of them fails none of them are made
+    fn together<R, F>(
+        &mut self, name: &str, change: F
+    ) -> Result<R, KanbenError>
+    where F: FnOnce(&mut Self) -> Result<R, KanbenError>;
+    // returns the column the task was put in
+    fn insert_task(
+        &mut self, task: Task, top: bool
+    ) -> Result<String, KanbenError>;
+    fn rename(&mut self, key: &str, new_key: &str) -> Result<(), KanbenError>;
+    fn reindex_columns(&mut self) -> Result<usize, KanbenError>;
+    fn check(&self) -> Vec<Problem>;
+    fn repair(&mut self) -> Result<usize, KanbenError>;
+    fn top_priority(&mut self, key: &str) -> Result<(), KanbenError>;
+    fn move_within(
+        &mut self, key: &str, position: Position
+    ) -> Result<(), KanbenError>;
+    fn get_config(&self) -> BoardConfig;
+    fn save_config(&mut self, config: BoardConfig) -> Result<(), KanbenError>;
+    fn add_column(&mut self, name: &str) -> Result<(), KanbenError>;
+    fn remove_column(&mut self, name: &str) -> Result<(), KanbenError>;
+    fn rename_column(
+        &mut self, name: &str, new_name: &str
+    ) -> Result<(), KanbenError>;
+    fn reorder_column(
+        &mut self, name: &str, position: usize
+    ) -> Result<(), KanbenError>;
 }
 
-pub struct Board<'a, S: Store<Task>, C: Store<Vec<String>>> {
+// a soft limit lets a task into a full column, but it's worth
+// pointing out that the column is now over
+#[derive(Debug, PartialEq, Clone)]
+pub struct OverLimit {
+    pub column: String,
+    pub count: usize,
+    pub limit: usize
+}
+
+// where a task can go within its own column
+#[derive(Debug, PartialEq, Clone)]
+pub enum Position {
+    Top,
+    Bottom,
+    Up(usize),
+    Down(usize),
+    Before(String),
+    After(String),
+    At(usize),
+}
+
+fn move_name(key: &str, position: &Position) -> String {
+    match position {
+        Position::Top => format!("move '{}' to the top", key),
+        Position::Bottom => format!("move '{}' to the bottom", key),
+        Position::Up(n) => format!("move '{}' up {}", key, n),
+        Position::Down(n) => format!("move '{}' down {}", key, n),
+        Position::At(n) => format!("move '{}' to position {}", key, n + 1),
+        Position::Before(other) => format!("move '{}' above '{}'", key, other),
+        Position::After(other) => format!("move '{}' below '{}'", key, other)
+    }
+}
+
+pub struct Board<
+    'a,
+    S: Store<Task>,
+    C: Store<Vec<String>>,
+    K: Store<BoardConfig>
+> {
     store: &'a mut S,
     column_store: &'a mut C,
     tag_store: &'a mut C,
+    config_store: &'a mut K,
+    trash: Option<&'a mut dyn Store<Trashed>>,
+    journal: Option<&'a mut dyn Store<Entry>>,
+    history: Option<&'a mut dyn Store<Event>>,
+    pending: Option<&'a mut dyn Store<Pending>>,
+    transaction: Option<&'a dyn Transaction>,
+    over_limit: Vec<OverLimit>,
+    // set while a change is being made, so the changes it's made
+    // up of are saved with it rather than on their own
+    changing: bool,
+    // the number the next history event goes under, once it's
+    // been worked out
+    next_event: Option<u64>,
+    clock: fn() -> u64,
+    // who's making the changes, see `user`
+    actor: Option<String>,
 }
 
 impl <
-    'a, S: Store<Task>, C: Store<Vec<String>>
-> Board<'a, S, C> {
+    'a, S: Store<Task>, C: Store<Vec<String>>, K: Store<BoardConfig>
+> Board<'a, S, C, K> {
     pub fn new(
         store: &'a mut S,
         column_store: &'a mut C,
-        tag_store: &'a mut C
-    ) -> Board<'a, S, C> {
+        tag_store: &'a mut C,
+        config_store: &'a mut K
+    ) -> Board<'a, S, C, K> {
         Board{
             store,
             column_store,
-            tag_store
+            tag_store,
+            config_store,
+            trash: None,
+            journal: None,
+            history: None,
+            pending: None,
+            transaction: None,
+            over_limit: vec!(),
+            changing: false,
+            next_event: None,
+            clock: clock::now,
+            actor: None
+        }
+    }
+
+    // deleted tasks are kept in the trash until it's emptied.
+    // a board without one deletes them for good
+    pub fn with_trash(
+        mut self,
+        trash: &'a mut dyn Store<Trashed>
+    ) -> Board<'a, S, C, K> {
+        self.trash = Some(trash);
+        self
+    }
+
+    // every change is recorded here so it can be undone. a board
+    // without a journal can't undo anything
+    pub fn with_journal(
+        mut self,
+        journal: &'a mut dyn Store<Entry>
+    ) -> Board<'a, S, C, K> {
+        self.journal = Some(journal);
+        self
+    }
+
+    // what's happened to each task, kept for good
+    pub fn with_history(
+        mut self,
+        history: &'a mut dyn Store<Event>
+    ) -> Board<'a, S, C, K> {
+        self.history = Some(history);
+        self
+    }
+
+    // where each change is saved whole before it's committed, see
+    // `Pending`. without one a change that fails part way through
+    // committing can be left half made
+    pub fn with_pending(
+        mut self,
+        pending: &'a mut dyn Store<Pending>
+    ) -> Board<'a, S, C, K> {
+        self.pending = Some(pending);
+        self
+    }
+
+    // stores that share a database commit each change in a single
+    // transaction of its own instead
+    #[cfg(feature = "sqlite")]
+    pub fn with_transaction(
+        mut self,
+        transaction: &'a dyn Transaction
+    ) -> Board<'a, S, C, K> {
+        self.transaction = Some(transaction);
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_clock(mut self, clock: fn() -> u64) -> Board<'a, S, C, K> {
+        self.clock = clock;
+        self
+    }
+
+    // each history event is put down to them. a board without one
+    // records events by nobody in particular
+    pub fn with_actor(
+        mut self,
+        actor: Option<String>
+    ) -> Board<'a, S, C, K> {
+        self.actor = actor;
+        self
+    }
+
+    // every change to the board goes through here. nothing is
+    // written unless the whole change succeeds. each store is
+    // committed on its own - kv can't batch across buckets - so
+    // unless they share a transaction the whole change is saved
+    // to the pending store first. if committing stops part way,
+    // by a crash or a failed write, the rest is written from
+    // there the next time the board is opened, see `recover`
+    fn atomically<R, F>(&mut self, change: F) -> Result<R, KanbenError>
+    where F: FnOnce(&mut Self) -> Result<R, KanbenError> {
+        if self.changing {
+            return change(self);
+        }
+        if let Some(transaction) = self.transaction {
+            transaction.begin()?;
+        }
+        self.config_store.begin();
+        if let Some(trash) = self.trash.as_deref_mut() {
+            trash.begin();
+        }
+        self.store.begin();
+        self.column_store.begin();
+        self.tag_store.begin();
+        if let Some(history) = self.history.as_deref_mut() {
+            history.begin();
+        }
+        if let Some(journal) = self.journal.as_deref_mut() {
+            journal.begin();
+        }
+
+        let warned = self.over_limit.len();
+        self.changing = true;
+        let result = change(self);
+        self.changing = false;
+        match result.and_then(|result| {
+            self.commit()?;
+            Ok(result)
+        }) {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                self.rollback();
+                self.over_limit.truncate(warned);
+                Err(e)
+            }
+        }
+    }
+
+    // a record saved before committing is only cleared once
+    // everything is, so a failure leaves it for `recover`
+    fn commit(&mut self) -> Result<(), KanbenError> {
+        let result = self.save_pending()
+            .and_then(|saved| self.commit_stores().map(|_| saved));
+
+        // anything left uncommitted after a failure is dropped
+        self.rollback();
+        if result? {
+            self.clear_pending();
+        }
+        Ok(())
+    }
+
+    fn rollback(&mut self) {
+        self.config_store.rollback();
+        if let Some(trash) = self.trash.as_deref_mut() {
+            trash.rollback();
+        }
+        self.store.rollback();
+        self.column_store.rollback();
+        self.tag_store.rollback();
+        if let Some(history) = self.history.as_deref_mut() {
+            history.rollback();
+        }
+        if let Some(journal) = self.journal.as_deref_mut() {
+            journal.rollback();
+        }
+        if let Some(transaction) = self.transaction {
+            transaction.rollback();
+        }
+    }
+
+    fn update_name(&self, key: &str, task: &Task) -> String {
+        match self.store.get(key) {
+            Some(old) if old.column != task.column => {
+                format!("move '{}' to {}", key, task.column)
+            },
+            _ => format!("update '{}'", key)
+        }
+    }
+
+    // a change that can be undone, going by `name` in the journal,
+    // and that goes in the history of each task it touches. only
+    // the records it writes are read either side of it. it's
+    // recorded once it's been committed, so what's recorded is
+    // what the stores read back. if recording fails the change
+    // still stands, it just can't be undone
+    fn journaled<R, F>(
+        &mut self,
+        name: String,
+        change: F
+    ) -> Result<R, KanbenError>
+    where F: FnOnce(&mut Self) -> Result<R, KanbenError> {
+        let unrecorded = self.journal.is_none() && self.history.is_none();
+        if unrecorded || self.changing {
+            return self.atomically(change);
+        }
+
+        let mut touched = None;
+        let result = self.atomically(|board| {
+            let result = change(board)?;
+            let keys = board.touched();
+            touched = Some((board.state(&keys, true), keys));
+            Ok(result)
+        })?;
+
+        if let Some((before, keys)) = touched {
+            let recorded = self.atomically(|board| {
+                board.record(name.clone(), &keys, before)
+            });
+            if let Err(e) = recorded {
+                error!("unable to record '{}': {}", name, e);
+            }
         }
+        Ok(result)
     }
 
-    fn add_to_column(&mut self, key: &str, column: Column) {
-        let label = self.get_column_label(column);
-        let mut list = self.get_column_list(&label);
+    fn add_to_column(
+        &mut self,
+        key: &str,
+        label: &str
+    ) -> Result<(), KanbenError> {
+        let mut list = self.get_column_list(label);
         list.push(key.to_owned());
-        self.column_store.set(&label, list);
+        self.column_store.set(label, list)
     }
 
     fn get_column_list(&self, label: &str) -> Vec<String> {
@@ -47,10 +371,17 @@ impl <
         }
     }
 
-    fn get_column_label(&self, column: Column) -> String {
-        let mut label = format!("{:?}", column);
-        label.make_ascii_lowercase();
-        label
+    fn find_column(
+        &self,
+        config: &BoardConfig,
+        name: &str
+    ) -> Result<String, KanbenError> {
+        let label = column_label(name);
+        if config.has_column(&label) {
+            Ok(label)
+        } else {
+            Err(KanbenError::ColumnNotFound(label))
+        }
     }
 
     fn find_in_list(
@@ -61,18 +392,135 @@ impl <
         list.iter().position(|x| x == key)
     }
 
-    fn get_new_task(&self, key: &str, tag: Option<String>) -> Task {
+    fn get_new_task(
+        &mut self,
+        key: &str,
+        tag: Option<String>
+    ) -> Result<Task, KanbenError> {
         let tags = match tag {
             None => None,
             Some(t) => Some(vec!(t.clone()))
         };
 
-        Task{
+        Ok(Task{
             name: key.to_owned(),
-            column: Column::Todo,
+            column: self.get_config().backlog(),
             description: None,
-            tags
+            tags,
+            id: Some(self.next_id()?),
+            times: Times{
+                created: Some((self.clock)()),
+                ..Times::default()
+            }
+        })
+    }
+
+    fn next_id(&mut self) -> Result<u64, KanbenError> {
+        let mut config = self.get_config();
+        config.last_id += 1;
+        let id = config.last_id;
+        self.config_store.set(CONFIG_KEY, config)?;
+        Ok(id)
+    }
+
+    fn apply_update(
+        &mut self,
+        key: &str,
+        task: Task,
+        force: bool
+    ) -> Result<(), KanbenError> {
+        let old_task = self.get(key)
+            .ok_or(KanbenError::TaskNotFound(key.to_owned()))?;
+
+        // ids are fixed once a task has been given one, and its
+        // times are kept by the board rather than whoever's updating
+        let mut task = task;
+        task.id = old_task.id.or(task.id);
+        task.times = old_task.times.clone();
+
+        if old_task.column != task.column {
+            let config = self.get_config();
+            if !config.has_column(&task.column) {
+                return Err(KanbenError::ColumnNotFound(task.column));
+            }
+
+            if !force && !config.allows(
+                &old_task.column, &task.column
+            ) {
+                return Err(KanbenError::IllegalMove{
+                    from: old_task.column,
+                    to: task.column
+                });
+            }
+
+            self.enter_column(&config, &task.column, force)?;
+            self.stamp(&config, &mut task);
+        }
+
+        let old_tags = old_task.tags;
+
+        let (add_tags, rm_tags) = self.get_tag_diff(
+            old_tags,
+            task.tags.clone()
+        );
+
+        self.index_tags(add_tags, key)?;
+        self.rm_tag_index(rm_tags, key)?;
+
+        if old_task.column != task.column {
+            self.remove_from_column(key, &old_task.column)?;
+            self.add_to_column(key, &task.column)?;
+        }
+
+        self.store.set(key, task)
+    }
+
+    // a task's times follow it as it changes column. it's started
+    // the first time it's in progress, and only completed for as
+    // long as it stays in the last column
+    fn stamp(&self, config: &BoardConfig, task: &mut Task) {
+        let now = (self.clock)();
+        task.times.moved = Some(now);
+        if config.in_progress().contains(&task.column) {
+            task.times.started = task.times.started.or(Some(now));
+        }
+        task.times.completed = if task.column == config.done() {
+            Some(now)
+        } else {
+            None
+        };
+    }
+
+    // every way onto a column goes through here. a full column
+    // with a strict limit refuses the task unless it's forced,
+    // otherwise it's let in and noted in `over_limit`
+    fn enter_column(
+        &mut self,
+        config: &BoardConfig,
+        label: &str,
+        force: bool
+    ) -> Result<(), KanbenError> {
+        let limit = match config.limit(label) {
+            Some(limit) => limit,
+            None => return Ok(())
+        };
+        let count = self.get_column_list(label).len() + 1;
+        if count <= limit {
+            return Ok(());
         }
+
+        if config.strict_limits && !force {
+            return Err(KanbenError::WipLimitReached{
+                column: label.to_owned(),
+                limit
+            });
+        }
+        self.over_limit.push(OverLimit{
+            column: label.to_owned(),
+            count,
+            limit
+        });
+        Ok(())
     }
 
     fn get_tag_diff(
@@ -104,37 +552,73 @@ impl <
         (add_tags, rm_tags)
     }
 
-    fn index_tags(&mut self, tags: Vec<String>, key: &str) {
+    fn index_tags(
+        &mut self,
+        tags: Vec<String>,
+        key: &str
+    ) -> Result<(), KanbenError> {
         for tag in tags {
             let mut tag_list = self.tag_store.get(&tag)
                 .unwrap_or(vec!());
             tag_list.push(key.to_owned());
             tag_list.dedup();
-            self.tag_store.set(&tag, tag_list);
+            self.tag_store.set(&tag, tag_list)?;
         }
+        Ok(())
     }
 
-    fn rm_tag_index(&mut self, tags: Vec<String>, key: &str) {
+    fn rm_tag_index(
+        &mut self,
+        tags: Vec<String>,
+        key: &str
+    ) -> Result<(), KanbenError> {
         for tag in tags {
-            let mut tag_list = self.tag_store.get(&tag)
-                .unwrap_or(vec!());
-            let index = self.find_in_list(key, &tag_list)
-                .expect(
-                    &format!(
-                        "can't find '{}' in list '{:?}'",
-                        tag, tag_list
-                    )
-                );
-            tag_list.remove(index);
-            self.tag_store.set(&tag, tag_list);
+            let tag_list = self.tag_store.get(&tag)
+                .unwrap_or(vec!())
+                .into_iter()
+                .filter(|k| k != key)
+                .collect::<Vec<String>>();
+            self.tag_store.set(&tag, tag_list)?;
+        }
+        Ok(())
+    }
+
+    fn not_in_column(&self, key: &str, label: &str) -> KanbenError {
+        match self.store.get(key) {
+            Some(_) => KanbenError::NotInColumn{
+                task: key.to_owned(),
+                column: label.to_owned()
+            },
+            None => KanbenError::TaskNotFound(key.to_owned())
+        }
+    }
+
+    // a key missing from its column list is left alone rather
+    // than treated as a failure - reindex will put it back
+    fn remove_task(&mut self, key: &str, task: Task) -> Result<(), KanbenError> {
+        self.remove_from_column(key, &task.column)?;
+        self.rm_tag_index(task.tags.unwrap_or(vec!()), key)?;
+        self.store.rm(key)
+    }
+
+    fn remove_from_column(
+        &mut self,
+        key: &str,
+        label: &str
+    ) -> Result<(), KanbenError> {
+        let mut col = self.get_column_list(label);
+        if let Some(index) = self.find_in_list(key, &col) {
+            col.remove(index);
+            self.column_store.set(label, col)?;
         }
+        Ok(())
     }
 
 }
 
 impl <
-    'a, S: Store<Task>, C: Store<Vec<String>>
-> BoardAccess for Board<'a, S, C> {
+    'a, S: Store<Task>, C: Store<Vec<String>>, K: Store<BoardConfig>
+> BoardAccess for Board<'a, S, C, K> {
     fn get_all_tasks(&self) -> Vec<Task> {
         self.store.get_all()
     }
@@ -150,130 +634,523 @@ impl <
             None => None
         };
 
-        list.iter().filter(|&key|{
+        let keys: Vec<String> = list.into_iter().filter(|key|{
             match &tag_result {
                 Some(v) => v.iter().any(|x| x == key),
                 None => tag.is_none()
             }
-        }).filter_map(|key| {
-            self.store.get(&key)
-        }).collect()
+        }).collect();
+        self.store.get_many(&keys)
     }
 
-    fn create_task(&mut self, key: &str, tag: Option<String>){
-        let task = self.get_new_task(key, tag.clone());
-        self.store.set(key, task);
-        self.add_to_column(key, Column::Todo);
+    fn create_task(
+        &mut self,
+        key: &str,
+        tag: Option<String>
+    ) -> Result<(), KanbenError> {
+        self.journaled(format!("add '{}'", key), |board| {
+            if board.store.get(key).is_some() {
+                return Err(KanbenError::TaskExists(key.to_owned()));
+            }
+
+            let task = board.get_new_task(key, tag.clone())?;
+            let column = task.column.clone();
+            let config = board.get_config();
+            board.enter_column(&config, &column, false)?;
+            board.store.set(key, task)?;
+            board.add_to_column(key, &column)?;
 
-        if tag.is_some() {
-            let tags = vec!(tag.unwrap().clone());
-            self.index_tags(tags, key);
-        }
+            if tag.is_some() {
+                let tags = vec!(tag.unwrap().clone());
+                board.index_tags(tags, key)?;
+            }
+            Ok(())
+        })
     }
 
     fn get(&self, key: &str) -> Option<Task>{
         self.store.get(key)
     }
 
-    fn update(&mut self, key: &str, task: Task) {
-        let old_task_result = self.get(key); 
+    fn find_by_id(&self, id: u64) -> Option<Task> {
+        self.store.get_all().into_iter().find(|t| t.id == Some(id))
+    }
 
-        if old_task_result.is_none() {
-            return;
-        }
+    fn update(&mut self, key: &str, task: Task) -> Result<(), KanbenError> {
+        self.journaled(self.update_name(key, &task), |board| {
+            board.apply_update(key, task, false)
+        })
+    }
 
-        let old_task = old_task_result.unwrap();
-        let old_tags = old_task.tags;
+    fn force_update(
+        &mut self,
+        key: &str,
+        task: Task
+    ) -> Result<(), KanbenError> {
+        self.journaled(self.update_name(key, &task), |board| {
+            board.apply_update(key, task, true)
+        })
+    }
 
-        let (add_tags, rm_tags) = self.get_tag_diff(
-            old_tags,
-            task.tags.clone()
-        );
+    fn remove(&mut self, key: &str) -> Result<(), KanbenError> {
+        self.journaled(format!("remove '{}'", key), |board| {
+            let task = board.store.get(key)
+                .ok_or(KanbenError::TaskNotFound(key.to_owned()))?;
+            board.remove_task(key, task)
+        })
+    }
 
-        self.index_tags(add_tags, key);
-        self.rm_tag_index(rm_tags, key);
+    fn delete(&mut self, key: &str) -> Result<(), KanbenError> {
+        self.journaled(format!("delete '{}'", key), |board| {
+            let task = board.store.get(key)
+                .ok_or(KanbenError::TaskNotFound(key.to_owned()))?;
+            let position = board.find_in_list(
+                key, &board.get_column_list(&task.column)
+            ).unwrap_or(0);
+            let trashed = Trashed{
+                task: task.clone(),
+                position,
+                deleted: (board.clock)()
+            };
+
+            if let Some(trash) = board.trash.as_deref_mut() {
+                trash.set(&trashed.key(), trashed)?;
+            }
+            board.remove_task(key, task)
+        })
+    }
 
-        if old_task.column != task.column.clone() {
-            self.remove(key);
-            self.add_to_column(key, task.column.clone());
-        }
+    // oldest first
+    fn trashed(&self) -> Vec<Trashed> {
+        let mut trashed = match self.trash.as_deref() {
+            Some(trash) => trash.get_all(),
+            None => vec!()
+        };
+        trashed.sort_by_key(|t| t.deleted);
+        trashed
+    }
+
+    // the task goes back where it was. if its column has gone
+    // since, it goes in the backlog as `reindex` would put it.
+    // it stays in the trash until it's safely back on the board
+    fn restore(&mut self, key: &str) -> Result<Task, KanbenError> {
+        let trashed = self.trash.as_deref()
+            .and_then(|trash| trash.get(key))
+            .ok_or(KanbenError::NotInTrash(key.to_owned()))?;
+        let name = trashed.task.name.clone();
+
+        self.journaled(format!("restore '{}'", name), |board| {
+            let mut task = trashed.task;
+            if board.store.get(&task.name).is_some() {
+                return Err(KanbenError::TaskExists(task.name));
+            }
+
+            let config = board.get_config();
+            if !config.has_column(&task.column) {
+                task.column = config.backlog();
+            }
+            board.enter_column(&config, &task.column, false)?;
+
+            let mut col = board.get_column_list(&task.column);
+            col.insert(trashed.position.min(col.len()), task.name.clone());
+            board.column_store.set(&task.column, col)?;
+            board.index_tags(task.tags.clone().unwrap_or(vec!()), &task.name)?;
+            board.store.set(&task.name, task.clone())?;
+            match board.trash.as_deref_mut() {
+                Some(trash) => trash.rm(key),
+                None => Ok(())
+            }
+        })?;
+        self.store.get(&name).ok_or(KanbenError::TaskNotFound(name))
+    }
+
+    fn empty_trash(&mut self, keys: &[String]) -> Result<(), KanbenError> {
+        self.journaled("empty the trash".to_owned(), |board| {
+            if let Some(trash) = board.trash.as_deref_mut() {
+                for key in keys {
+                    trash.rm(key)?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    // oldest first
+    fn journal(&self) -> Vec<Entry> {
+        self.journal_entries().into_iter().map(|(_, e)| e).collect()
+    }
+
+    fn undo(&mut self) -> Result<Option<Entry>, KanbenError> {
+        self.step(true)
+    }
 
-        self.store.set(key, task.clone());
+    fn redo(&mut self) -> Result<Option<Entry>, KanbenError> {
+        self.step(false)
     }
 
-    fn remove(&mut self, key: &str){
+    // oldest first
+    fn history(&self, key: &str) -> Vec<Event> {
         match self.store.get(key) {
-            Some(task) => {
-                let col_label = self.get_column_label(task.column);
-                let mut col = self.get_column_list(&col_label);
-                let index = self.find_in_list(key, &col)
-                    .expect(
-                        &format!(
-                            "can't find '{}' in column '{}'",
-                            key, col_label
-                        )
-                    );
-                col.remove(index);
-                self.column_store.set(&col_label, col);
-                self.store.rm(key)
-            },
-            None => ()
+            Some(task) => self.task_history(&task),
+            None => vec!()
         }
     }
 
-    fn reindex_columns(&mut self) -> Result<usize, ()>{
-        self.column_store.set("todo", vec!());
-        self.column_store.set("doing", vec!());
-        self.column_store.set("done", vec!());
-        let tasks = self.store.get_all();
-        for task in tasks.iter() {
-            self.add_to_column(&task.name, task.column.clone());
-        }
-        Ok(tasks.len())
-    }
-
-    fn top_priority(&mut self, key: &str) {
-        let task_result = self.store.get(key);
-        match task_result {
-            Some(task) => {
-                let label = self.get_column_label(task.column);
-                let mut col = self.get_column_list(&label);
-                let index = self.find_in_list(key, &col)
-                    .expect(
-                        &format!(
-                            "can't find '{}' in column '{}'",
-                            key, label
-                        )
-                    );
-                col.remove(index);
-                col.insert(0, task.name.clone());
-                self.column_store.set(&label, col);
-            },
-            _ => ()
+    fn all_history(&self) -> Vec<Event> {
+        self.board_history()
+    }
+
+    fn take_over_limit(&mut self) -> Vec<OverLimit> {
+        std::mem::take(&mut self.over_limit)
+    }
+
+    fn together<R, F>(
+        &mut self,
+        name: &str,
+        change: F
+    ) -> Result<R, KanbenError>
+    where F: FnOnce(&mut Self) -> Result<R, KanbenError> {
+        self.journaled(name.to_owned(), change)
+    }
+
+    // a description edited where it is doesn't change the task,
+    // so it's only noted in the history
+    fn described(&mut self, key: &str) -> Result<(), KanbenError> {
+        self.atomically(|board| {
+            let task = board.store.get(key)
+                .ok_or(KanbenError::TaskNotFound(key.to_owned()))?;
+            board.log(&task, EventKind::Described)
+        })
+    }
+
+    // points a task at where its description file has been moved
+    // to. that's housekeeping rather than a change to the task, so
+    // it's neither journaled nor in the history
+    fn set_description(
+        &mut self,
+        key: &str,
+        path: &str
+    ) -> Result<(), KanbenError> {
+        self.atomically(|board| {
+            let mut task = board.store.get(key)
+                .ok_or(KanbenError::TaskNotFound(key.to_owned()))?;
+            task.description = Some(path.to_owned());
+            board.store.set(key, task)
+        })
+    }
+
+    // takes in a task from another board, keeping its column,
+    // tags and description. ids are only unique within a board,
+    // so it's given a new one. a column this board doesn't have,
+    // or that its workflow doesn't let a new task go straight
+    // into, leaves the task in the backlog instead
+    fn insert_task(
+        &mut self,
+        task: Task,
+        top: bool
+    ) -> Result<String, KanbenError> {
+        let name = format!("add '{}' from another board", task.name);
+        self.journaled(name, |board| {
+            let key = task.name.clone();
+            if board.store.get(&key).is_some() {
+                return Err(KanbenError::TaskExists(key));
+            }
+
+            let config = board.get_config();
+            let backlog = config.backlog();
+            let label = match board.find_column(&config, &task.column) {
+                Ok(l) if config.allows(&backlog, &l) => l,
+                _ => backlog
+            };
+            board.enter_column(&config, &label, false)?;
+
+            let mut task = task.clone();
+            task.column = label.clone();
+            task.id = Some(board.next_id()?);
+            let tags = task.tags.clone().unwrap_or(vec!());
+            board.store.set(&key, task)?;
+
+            let mut col = board.get_column_list(&label);
+            if top {
+                col.insert(0, key.clone());
+            } else {
+                col.push(key.clone());
+            }
+            board.column_store.set(&label, col)?;
+            board.index_tags(tags, &key)?;
+            Ok(label)
+        })
+    }
+
+    fn rename(
+        &mut self,
+        key: &str,
+        new_key: &str
+    ) -> Result<(), KanbenError> {
+        let name = format!("rename '{}' to '{}'", key, new_key);
+        self.journaled(name, |board| {
+            let mut task = board.store.get(key)
+                .ok_or(KanbenError::TaskNotFound(key.to_owned()))?;
+            if board.store.get(new_key).is_some() {
+                return Err(KanbenError::TaskExists(new_key.to_owned()));
+            }
+
+            // the new key takes the old one's place in its column
+            let mut col = board.get_column_list(&task.column);
+            match board.find_in_list(key, &col) {
+                Some(index) => col[index] = new_key.to_owned(),
+                None => col.push(new_key.to_owned())
+            }
+            board.column_store.set(&task.column, col)?;
+
+            for tag in task.tags.clone().unwrap_or(vec!()) {
+                let tag_list = board.tag_store.get(&tag)
+                    .unwrap_or(vec!())
+                    .into_iter()
+                    .filter(|k| k != key)
+                    .chain(vec!(new_key.to_owned()))
+                    .collect::<Vec<String>>();
+                board.tag_store.set(&tag, tag_list)?;
+            }
+
+            task.name = new_key.to_owned();
+            board.store.set(new_key, task)?;
+            board.store.rm(key)
+        })
+    }
+
+    fn reindex_columns(&mut self) -> Result<usize, KanbenError> {
+        self.journaled("reindex".to_owned(), |board| {
+            let config = board.get_config();
+            for column in config.columns.iter() {
+                board.column_store.set(column, vec!())?;
+            }
+            let tasks = board.store.get_all();
+            for task in tasks.iter() {
+                let mut task = task.clone();
+                // tasks left behind in a column that no longer
+                // exists are moved back into the backlog
+                if !config.has_column(&task.column) {
+                    task.column = config.backlog();
+                    board.store.set(&task.name, task.clone())?;
+                }
+                board.add_to_column(&task.name, &task.column)?;
+            }
+            Ok(tasks.len())
+        })
+    }
+
+    fn check(&self) -> Vec<Problem> {
+        self.find_problems()
+    }
+
+    fn repair(&mut self) -> Result<usize, KanbenError> {
+        self.journaled("repair the board".to_owned(), |board| {
+            let problems = board.find_problems();
+            let mut count = 0;
+            for problem in problems.iter().filter(|p| p.repairable()) {
+                board.fix_problem(problem)?;
+                count += 1;
+            }
+            Ok(count)
+        })
+    }
+
+    fn top_priority(&mut self, key: &str) -> Result<(), KanbenError> {
+        self.move_within(key, Position::Top)
+    }
+
+    // positions past either end of the column stop at that end
+    fn move_within(
+        &mut self,
+        key: &str,
+        position: Position
+    ) -> Result<(), KanbenError> {
+        self.journaled(move_name(key, &position), |board| {
+            let task = board.store.get(key)
+                .ok_or(KanbenError::TaskNotFound(key.to_owned()))?;
+            let label = task.column.clone();
+            let mut col = board.get_column_list(&label);
+            let current = board.find_in_list(key, &col)
+                .unwrap_or(col.len());
+            col.retain(|k| k != key);
+
+            let index = match &position {
+                Position::Top => 0,
+                Position::Bottom => col.len(),
+                Position::Up(n) => current.saturating_sub(*n),
+                Position::Down(n) => current.saturating_add(*n),
+                Position::At(n) => *n,
+                Position::Before(other) | Position::After(other) => {
+                    if other == key {
+                        return Ok(());
+                    }
+                    let found = board.find_in_list(other, &col)
+                        .ok_or_else(|| board.not_in_column(other, &label))?;
+                    match &position {
+                        Position::After(_) => found + 1,
+                        _ => found
+                    }
+                }
+            };
+
+            let index = index.min(col.len());
+            col.insert(index, task.name.clone());
+            board.column_store.set(&label, col)?;
+            if index != current {
+                board.log(&task, EventKind::Prioritised{
+                    column: label,
+                    from: current + 1,
+                    to: index + 1
+                })?;
+            }
+            Ok(())
+        })
+    }
+
+    fn get_config(&self) -> BoardConfig {
+        match self.config_store.get(CONFIG_KEY) {
+            Some(c) if !c.columns.is_empty() => c,
+            _ => BoardConfig::default()
         }
     }
+
+    fn save_config(
+        &mut self,
+        config: BoardConfig
+    ) -> Result<(), KanbenError> {
+        self.journaled("change the board settings".to_owned(), |board| {
+            for label in config.limits.keys() {
+                if !config.has_column(label) {
+                    return Err(KanbenError::ColumnNotFound(label.to_owned()));
+                }
+            }
+
+            if let Some(workflow) = &config.workflow {
+                for t in workflow.iter() {
+                    for label in [&t.from, &t.to].iter() {
+                        if !config.has_column(label) {
+                            return Err(
+                                KanbenError::ColumnNotFound(label.to_string())
+                            );
+                        }
+                    }
+                }
+            }
+
+            board.config_store.set(CONFIG_KEY, config)
+        })
+    }
+
+    fn add_column(&mut self, name: &str) -> Result<(), KanbenError> {
+        self.journaled(format!("add column '{}'", name), |board| {
+            let mut config = board.get_config();
+            let label = column_label(name);
+            if config.has_column(&label) {
+                return Err(KanbenError::ColumnExists(label));
+            }
+
+            // the last column is where finished tasks go, so new
+            // columns are added in front of it
+            let at = match config.columns.len() {
+                0 | 1 => config.columns.len(),
+                n => n - 1
+            };
+            config.columns.insert(at, label.clone());
+            board.column_store.set(&label, vec!())?;
+            board.config_store.set(CONFIG_KEY, config)
+        })
+    }
+
+    fn remove_column(&mut self, name: &str) -> Result<(), KanbenError> {
+        self.journaled(format!("remove column '{}'", name), |board| {
+            let mut config = board.get_config();
+            let label = board.find_column(&config, name)?;
+            let in_use = !board.get_column_list(&label).is_empty()
+                || board.store.get_all().iter().any(|t| t.column == label);
+
+            if in_use {
+                return Err(KanbenError::ColumnNotEmpty(label));
+            }
+
+            if config.columns.len() <= 2 {
+                return Err(KanbenError::TooFewColumns);
+            }
+
+            config.remove_column(&label);
+            board.column_store.rm(&label)?;
+            board.config_store.set(CONFIG_KEY, config)
+        })
+    }
+
+    fn rename_column(
+        &mut self,
+        name: &str,
+        new_name: &str
+    ) -> Result<(), KanbenError> {
+        let change = format!("rename column '{}' to '{}'", name, new_name);
+        self.journaled(change, |board| {
+            let mut config = board.get_config();
+            let label = board.find_column(&config, name)?;
+            let new_label = column_label(new_name);
+            if config.has_column(&new_label) {
+                return Err(KanbenError::ColumnExists(new_label));
+            }
+
+            for task in board.store.get_all() {
+                if task.column == label {
+                    let mut task = task.clone();
+                    task.column = new_label.clone();
+                    board.store.set(&task.name.clone(), task)?;
+                }
+            }
+
+            let list = board.get_column_list(&label);
+            board.column_store.set(&new_label, list)?;
+            board.column_store.rm(&label)?;
+
+            config.rename_column(&label, &new_label);
+            board.config_store.set(CONFIG_KEY, config)
+        })
+    }
+
+    fn reorder_column(
+        &mut self,
+        name: &str,
+        position: usize
+    ) -> Result<(), KanbenError> {
+        self.journaled(format!("move column '{}'", name), |board| {
+            let mut config = board.get_config();
+            let label = board.find_column(&config, name)?;
+            config.columns.retain(|c| c != &label);
+            let position = position.min(config.columns.len());
+            config.columns.insert(position, label);
+            board.config_store.set(CONFIG_KEY, config)
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::opts::Column;
-    use crate::test::StoreMock;
+    use crate::test::{StoreMock, Stores};
 
     #[test]
     fn it_can_get_all_tasks() {
         let mut store = StoreMock::new();
 
         store.bulk_insert(vec!(
-            ("task1", get_task("task1", Column::Doing)),
-            ("task2", get_task("task2", Column::Todo)),
+            ("task1", get_task("task1", "doing")),
+            ("task2", get_task("task2", "todo")),
         ));
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
         let tasks = board.get_all_tasks();
@@ -286,14 +1163,18 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
-        );
+            &mut tag_store,
+            &mut config_store
+        ).with_clock(|| 500);
 
-        board.create_task("test", None);
-        let task = get_task("test", Column::Todo);
+        board.create_task("test", None).unwrap();
+        let mut task = get_task("test", "todo");
+        task.id = Some(1);
+        task.times.created = Some(500);
 
         assert!(store.set_called_with("test", &task));
     }
@@ -303,14 +1184,16 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
 
-        board.create_task("test", None);
+        board.create_task("test", None).unwrap();
 
         assert_eq!(
             col_store.get("todo").unwrap(),
@@ -323,13 +1206,15 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
-        board.create_task("test1", None);
-        board.create_task("test2", None);
+        board.create_task("test1", None).unwrap();
+        board.create_task("test2", None).unwrap();
 
         assert_eq!(
             col_store.get("todo").unwrap(),
@@ -338,18 +1223,67 @@ mod tests {
 
     }
 
+    #[test]
+    fn it_refuses_to_create_a_task_that_already_exists() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("task", None).unwrap();
+
+        assert_eq!(
+            board.create_task("task", None),
+            Err(KanbenError::TaskExists("task".to_owned()))
+        );
+        assert_eq!(col_store.get("todo").unwrap(), vec!("task"));
+    }
+
+    #[test]
+    fn a_failed_change_leaves_the_board_untouched() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        tag_store.fail_writes();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        let result = board.create_task("task", Some("tag".to_owned()));
+
+        assert_eq!(
+            result,
+            Err(KanbenError::Store("write failed".to_owned()))
+        );
+        assert_eq!(board.get("task"), None);
+        assert_eq!(board.get_config().last_id, 0);
+        assert_eq!(col_store.get("todo"), None);
+    }
+
     #[test]
     fn it_can_get_a_task_with_a_key() {
-        let task = get_task("test", Column::Todo);
+        let task = get_task("test", "todo");
 
         let mut store = StoreMock::new();
-        store.set("test", task.clone());
+        store.set("test", task.clone()).unwrap();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
         let returned_task = board.get("test");
@@ -362,13 +1296,15 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
-        board.create_task("test", None);
-        board.remove("test");
+        board.create_task("test", None).unwrap();
+        board.remove("test").unwrap();
 
         assert!(store.rm_called_with("test"));
     }
@@ -378,53 +1314,267 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        board.create_task("test", None);
-        board.remove("test");
+        board.create_task("test", None).unwrap();
+        board.remove("test").unwrap();
 
         assert_eq!(col_store.get("todo").unwrap().len(), 0);
     }
 
     #[test]
-    fn it_can_update() {
-        let task = get_task("test", Column::Todo);
-
+    fn removing_a_task_removes_it_from_its_tags() {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
-        board.create_task("test", None);
-        board.update("test", task.clone());
 
-        assert!(store.set_called_with("test", &task));
+        board.create_task("test", Some("tag".to_owned())).unwrap();
+        board.remove("test").unwrap();
+
+        assert_eq!(tag_store.get("tag").unwrap().len(), 0);
     }
 
     #[test]
-    fn update_moves_items_between_columns() {
-        let task = get_task("test", Column::Doing);
+    fn deleting_a_task_keeps_it_in_the_trash() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut trash = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        ).with_trash(&mut trash).with_clock(|| 500);
+
+        board.create_task("a", None).unwrap();
+        board.create_task("b", Some("tag".to_owned())).unwrap();
+        board.delete("b").unwrap();
+
+        assert_eq!(board.get("b"), None);
+        assert_eq!(board.trashed(), vec!(Trashed{
+            task: Task{
+                name: "b".to_owned(),
+                column: "todo".to_owned(),
+                description: None,
+                tags: Some(vec!("tag".to_owned())),
+                id: Some(2),
+                times: Times{ created: Some(500), ..Times::default() }
+            },
+            position: 1,
+            deleted: 500
+        }));
+    }
 
+    #[test]
+    fn a_board_without_a_trash_deletes_for_good() {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        board.create_task("test", None);
-        board.update("test", task.clone());
+        board.create_task("a", None).unwrap();
+        board.delete("a").unwrap();
 
-        assert_eq!(col_store.get("doing").unwrap().len(), 1);
+        assert_eq!(board.get("a"), None);
+        assert!(board.trashed().is_empty());
+    }
+
+    #[test]
+    fn a_task_whose_column_has_gone_is_restored_to_the_backlog() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut trash = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        ).with_trash(&mut trash);
+        board.add_column("review").unwrap();
+        board.create_task("a", None).unwrap();
+        board.update("a", get_task("a", "review")).unwrap();
+        board.delete("a").unwrap();
+        board.remove_column("review").unwrap();
+
+        let task = board.restore("1").unwrap();
+
+        assert_eq!(task.column, "todo");
+        assert_eq!(board.get_column("todo", None), vec!(task));
+        assert!(board.trashed().is_empty());
+    }
+
+    #[test]
+    fn restoring_needs_the_title_to_be_free() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut trash = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        ).with_trash(&mut trash);
+        board.create_task("a", None).unwrap();
+        board.delete("a").unwrap();
+        board.create_task("a", None).unwrap();
+
+        assert_eq!(
+            board.restore("1"),
+            Err(KanbenError::TaskExists("a".to_owned()))
+        );
+        assert_eq!(board.trashed().len(), 1);
+    }
+
+    #[test]
+    fn it_can_insert_a_task_from_another_board() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+        board.create_task("first", None).unwrap();
+        board.create_task("second", None).unwrap();
+        board.update("second", Task{
+            name: "second".to_owned(),
+            column: "doing".to_owned(),
+            description: None,
+            tags: None,
+            id: None,
+            times: Times::default()
+        }).unwrap();
+        let task = Task{
+            name: "moved".to_owned(),
+            column: "doing".to_owned(),
+            description: Some("/a/file".to_owned()),
+            tags: Some(vec!("tag".to_owned())),
+            id: Some(40),
+            times: Times::default()
+        };
+
+        board.insert_task(task, true).unwrap();
+
+        let inserted = board.get("moved").unwrap();
+        assert_eq!(inserted.id, Some(3));
+        assert_eq!(inserted.description, Some("/a/file".to_owned()));
+        assert_eq!(col_store.get("doing").unwrap(), vec!("moved", "second"));
+        assert_eq!(tag_store.get("tag").unwrap(), vec!("moved"));
+    }
+
+    #[test]
+    fn a_task_from_a_column_this_board_lacks_goes_in_the_backlog() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+        let task = Task{
+            name: "moved".to_owned(),
+            column: "review".to_owned(),
+            description: None,
+            tags: None,
+            id: Some(1),
+            times: Times::default()
+        };
+
+        let result = board.insert_task(task, false);
+
+        assert_eq!(result, Ok("todo".to_owned()));
+        assert_eq!(board.get("moved").unwrap().column, "todo");
+        assert_eq!(board.get_column("todo", None).len(), 1);
+    }
+
+    #[test]
+    fn a_task_the_workflow_wont_let_in_goes_in_the_backlog() {
+        let mut stores = Stores::new();
+        let mut config = BoardConfig::default();
+        config.workflow = Some(config.linear_workflow());
+        stores.config.set(CONFIG_KEY, config).unwrap();
+        let mut board = stores.board();
+
+        let result = board.insert_task(get_task("moved", "done"), false);
+
+        assert_eq!(result, Ok("todo".to_owned()));
+        assert_eq!(board.get("moved").unwrap().column, "todo");
+        assert!(board.get_column("done", None).is_empty());
+    }
+
+    #[test]
+    fn it_can_update() {
+        let task = get_task("test", "todo");
+
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        ).with_clock(|| 500);
+        board.create_task("test", None).unwrap();
+        board.update("test", task.clone()).unwrap();
+
+        let mut expected = task.clone();
+        expected.id = Some(1);
+        expected.times.created = Some(500);
+        assert!(store.set_called_with("test", &expected));
+    }
+
+    #[test]
+    fn update_moves_items_between_columns() {
+        let task = get_task("test", "doing");
+
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("test", None).unwrap();
+        board.update("test", task.clone()).unwrap();
+
+        assert_eq!(col_store.get("doing").unwrap().len(), 1);
     }
 
     #[test]
@@ -432,9 +1582,9 @@ mod tests {
         let mut store = StoreMock::new();
 
         store.bulk_insert(vec!(
-            ("task1", get_task("task1", Column::Doing)),
-            ("task2", get_task("task2", Column::Doing)),
-            ("task4", get_task("task4", Column::Todo)),
+            ("task1", get_task("task1", "doing")),
+            ("task2", get_task("task2", "doing")),
+            ("task4", get_task("task4", "todo")),
         ));
 
         let mut col_store = StoreMock::new();
@@ -447,10 +1597,13 @@ mod tests {
         ));
 
         let mut tag_store = StoreMock::new();
+
+        let mut config_store = StoreMock::new();
         let board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
         let tasks = board.get_column("doing", None);
@@ -459,16 +1612,18 @@ mod tests {
 
     #[test]
     fn it_can_reindex_columns() {
-        let task = get_task("test", Column::Doing);
+        let task = get_task("test", "doing");
 
         let mut store = StoreMock::new();
-        store.set("test", task.clone());
+        store.set("test", task.clone()).unwrap();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
         let _ = board.reindex_columns();
@@ -482,9 +1637,9 @@ mod tests {
         let mut store = StoreMock::new();
 
         store.bulk_insert(vec!(
-            ("task1", get_task("task1", Column::Doing)),
-            ("task2", get_task("task2", Column::Doing)),
-            ("task4", get_task("task2", Column::Todo)),
+            ("task1", get_task("task1", "doing")),
+            ("task2", get_task("task2", "doing")),
+            ("task4", get_task("task2", "todo")),
         ));
 
         let mut col_store = StoreMock::new();
@@ -497,26 +1652,155 @@ mod tests {
         ));
 
         let mut tag_store = StoreMock::new();
+
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        board.top_priority("task1");
+        board.top_priority("task1").unwrap();
         let col = board.get_column("doing", None);
         let task1 = col.get(0).unwrap();
         assert_eq!(&task1.name, "task1");
     }
 
+    fn ordered_column() -> (StoreMock<Task>, StoreMock<Vec<String>>) {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let names = vec!("a", "b", "c", "d");
+        for name in names.iter() {
+            store.bulk_insert(vec!((*name, get_task(name, "todo"))));
+        }
+        store.bulk_insert(vec!(("e", get_task("e", "done"))));
+        col_store.bulk_insert(vec!(
+            ("todo", names.iter().map(|n| n.to_string()).collect()),
+            ("done", vec!("e".to_owned()))
+        ));
+        (store, col_store)
+    }
+
+    fn order<B: BoardAccess>(board: &B) -> Vec<String> {
+        board.get_column("todo", None).into_iter().map(|t| t.name).collect()
+    }
+
+    #[test]
+    fn it_can_move_an_item_to_the_bottom() {
+        let (mut store, mut col_store) = ordered_column();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.move_within("b", Position::Bottom).unwrap();
+
+        assert_eq!(order(&board), vec!("a", "c", "d", "b"));
+    }
+
+    #[test]
+    fn it_can_move_an_item_up_and_down() {
+        let (mut store, mut col_store) = ordered_column();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.move_within("c", Position::Up(1)).unwrap();
+        assert_eq!(order(&board), vec!("a", "c", "b", "d"));
+
+        board.move_within("a", Position::Down(2)).unwrap();
+        assert_eq!(order(&board), vec!("c", "b", "a", "d"));
+
+        // too far stops at the end of the column
+        board.move_within("b", Position::Down(10)).unwrap();
+        assert_eq!(order(&board), vec!("c", "a", "d", "b"));
+        board.move_within("d", Position::Up(10)).unwrap();
+        assert_eq!(order(&board), vec!("d", "c", "a", "b"));
+    }
+
+    #[test]
+    fn it_can_move_an_item_before_or_after_another() {
+        let (mut store, mut col_store) = ordered_column();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.move_within("d", Position::Before("b".to_owned())).unwrap();
+        assert_eq!(order(&board), vec!("a", "d", "b", "c"));
+
+        board.move_within("a", Position::After("c".to_owned())).unwrap();
+        assert_eq!(order(&board), vec!("d", "b", "c", "a"));
+    }
+
+    #[test]
+    fn it_can_move_an_item_to_a_position() {
+        let (mut store, mut col_store) = ordered_column();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.move_within("a", Position::At(2)).unwrap();
+        assert_eq!(order(&board), vec!("b", "c", "a", "d"));
+
+        board.move_within("b", Position::At(9)).unwrap();
+        assert_eq!(order(&board), vec!("c", "a", "d", "b"));
+    }
+
+    #[test]
+    fn it_only_moves_an_item_next_to_one_in_the_same_column() {
+        let (mut store, mut col_store) = ordered_column();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        let other_column = board.move_within(
+            "a", Position::After("e".to_owned())
+        );
+        let missing = board.move_within(
+            "a", Position::Before("z".to_owned())
+        );
+
+        assert_eq!(other_column, Err(KanbenError::NotInColumn{
+            task: "e".to_owned(),
+            column: "todo".to_owned()
+        }));
+        assert_eq!(missing, Err(KanbenError::TaskNotFound("z".to_owned())));
+        assert_eq!(order(&board), vec!("a", "b", "c", "d"));
+    }
+
     #[test]
     fn it_can_move_an_item_after_making_it_priority() {
         let mut store = StoreMock::new();
 
         store.bulk_insert(vec!(
-            ("task1", get_task("task1", Column::Doing)),
-            ("task2", get_task("task2", Column::Doing)),
-            ("task4", get_task("task4", Column::Todo))
+            ("task1", get_task("task1", "doing")),
+            ("task2", get_task("task2", "doing")),
+            ("task4", get_task("task4", "todo"))
         ));
 
         let mut col_store = StoreMock::new();
@@ -529,14 +1813,17 @@ mod tests {
         ));
 
         let mut tag_store = StoreMock::new();
+
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        board.top_priority("task1");
-        board.update("task1", get_task("task1", Column::Done));
+        board.top_priority("task1").unwrap();
+        board.update("task1", get_task("task1", "done")).unwrap();
         assert_eq!(board.get_column("done", None).len(), 1);
     }
 
@@ -545,9 +1832,9 @@ mod tests {
         let mut store = StoreMock::new();
 
         store.bulk_insert(vec!(
-            ("task1", get_task("task1", Column::Doing)),
-            ("task2", get_task("task2", Column::Doing)),
-            ("task4", get_task("task4", Column::Todo))
+            ("task1", get_task("task1", "doing")),
+            ("task2", get_task("task2", "doing")),
+            ("task4", get_task("task4", "todo"))
         ));
 
         let mut col_store = StoreMock::new();
@@ -559,16 +1846,18 @@ mod tests {
             ),
         ));
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
-        board.top_priority("task1");
-        board.top_priority("task2");
+        board.top_priority("task1").unwrap();
+        board.top_priority("task2").unwrap();
         assert_eq!(
             board.get_column("doing", None).get(0).unwrap(),
-            &get_task("task2", Column::Doing)
+            &get_task("task2", "doing")
         );
     }
 
@@ -577,15 +1866,17 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
-        board.create_task("task", None);
-        let mut new_task = get_task("task", Column::Todo);
+        board.create_task("task", None).unwrap();
+        let mut new_task = get_task("task", "todo");
         new_task.tags = Some(vec!("tag".to_owned()));
-        board.update("task", new_task);
+        board.update("task", new_task).unwrap();
 
         let tag_index = tag_store.get("tag").unwrap();
 
@@ -600,16 +1891,18 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
-        tag_store.set("tag", vec!("task2".to_owned()));
+        let mut config_store = StoreMock::new();
+        tag_store.set("tag", vec!("task2".to_owned())).unwrap();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
-        board.create_task("task", None);
-        let mut new_task = get_task("task", Column::Todo);
+        board.create_task("task", None).unwrap();
+        let mut new_task = get_task("task", "todo");
         new_task.tags = Some(vec!("tag".to_owned()));
-        board.update("task", new_task);
+        board.update("task", new_task).unwrap();
 
         let tag_index = tag_store.get("tag").unwrap();
 
@@ -624,16 +1917,18 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
-        tag_store.set("tag", vec!("task".to_owned()));
+        let mut config_store = StoreMock::new();
+        tag_store.set("tag", vec!("task".to_owned())).unwrap();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
-        board.create_task("task", None);
-        let mut new_task = get_task("task", Column::Todo);
+        board.create_task("task", None).unwrap();
+        let mut new_task = get_task("task", "todo");
         new_task.tags = Some(vec!("tag".to_owned()));
-        board.update("task", new_task);
+        board.update("task", new_task).unwrap();
 
         let tag_index = tag_store.get("tag").unwrap();
 
@@ -648,20 +1943,22 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
-        tag_store.set("tag", vec!("task".to_owned()));
-        col_store.set("todo", vec!("task".to_owned()));
-        let mut task = get_task("task", Column::Todo);
+        let mut config_store = StoreMock::new();
+        tag_store.set("tag", vec!("task".to_owned())).unwrap();
+        col_store.set("todo", vec!("task".to_owned())).unwrap();
+        let mut task = get_task("task", "todo");
         task.tags = Some(vec!("tag".to_owned()));
-        store.set("task", task);
+        store.set("task", task).unwrap();
 
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        let new_task = get_task("task", Column::Todo);
-        board.update("task", new_task);
+        let new_task = get_task("task", "todo");
+        board.update("task", new_task).unwrap();
 
         let tag_index = tag_store.get("tag").unwrap();
         let expected_list: Vec<String> = vec!();
@@ -677,15 +1974,19 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
-        );
+            &mut tag_store,
+            &mut config_store
+        ).with_clock(|| 500);
 
-        board.create_task("test", Some("tag".to_owned()));
-        let mut task = get_task("test", Column::Todo);
+        board.create_task("test", Some("tag".to_owned())).unwrap();
+        let mut task = get_task("test", "todo");
         task.tags = Some(vec!("tag".to_owned()));
+        task.id = Some(1);
+        task.times.created = Some(500);
 
         assert!(store.set_called_with("test", &task));
     }
@@ -695,13 +1996,15 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        board.create_task("test", Some("tag".to_owned()));
+        board.create_task("test", Some("tag".to_owned())).unwrap();
 
         let tag_index = tag_store.get("tag").unwrap();
 
@@ -713,16 +2016,16 @@ mod tests {
 
     #[test]
     fn it_can_return_tasks_by_column_filtered_by_tag() {
-        let mut task1 = get_task("task1", Column::Doing);
+        let mut task1 = get_task("task1", "doing");
         task1.tags = Some(vec!("tag".to_owned()));
-        let mut task2 = get_task("task2", Column::Doing);
+        let mut task2 = get_task("task2", "doing");
         task2.tags = Some(vec!("tag".to_owned()));
         let mut store = StoreMock::new();
 
         store.bulk_insert(vec!(
             ("task1", task1.clone()),
             ("task2", task2.clone()),
-            ("task3", get_task("task3", Column::Doing)),
+            ("task3", get_task("task3", "doing")),
         ));
 
         let mut col_store = StoreMock::new();
@@ -736,14 +2039,17 @@ mod tests {
         ));
 
         let mut tag_store = StoreMock::new();
+
+        let mut config_store = StoreMock::new();
         tag_store.set(
             "tag", vec!("task1".to_owned(), "task2".to_owned())
-        );
+        ).unwrap();
 
         let board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
         let tasks = board.get_column(
@@ -753,12 +2059,701 @@ mod tests {
         assert_eq!(tasks, vec!(task1.clone(), task2.clone()));
     }
 
-    fn get_task(key: &str, column: Column) -> Task {
+    #[test]
+    fn it_adds_new_tasks_to_the_first_configured_column() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        config_store.set(CONFIG_KEY, BoardConfig{
+            columns: vec!("backlog".to_owned(), "done".to_owned()),
+            ..BoardConfig::default()
+        }).unwrap();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        ).with_clock(|| 500);
+
+        board.create_task("test", None).unwrap();
+        let mut task = get_task("test", "backlog");
+        task.id = Some(1);
+        task.times.created = Some(500);
+
+        assert!(store.set_called_with("test", &task));
+        assert_eq!(
+            col_store.get("backlog").unwrap(),
+            vec!("test".to_owned())
+        );
+    }
+
+    #[test]
+    fn it_can_add_a_column() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.add_column("Review").unwrap();
+
+        assert_eq!(
+            board.get_config().columns,
+            vec!("todo", "doing", "review", "done")
+        );
+    }
+
+    #[test]
+    fn it_wont_add_a_column_twice() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        assert_eq!(
+            board.add_column("Doing"),
+            Err(KanbenError::ColumnExists("doing".to_owned()))
+        );
+    }
+
+    #[test]
+    fn it_can_remove_an_empty_column() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.remove_column("doing").unwrap();
+
+        assert_eq!(board.get_config().columns, vec!("todo", "done"));
+    }
+
+    #[test]
+    fn it_wont_remove_a_column_with_tasks_in_it() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("test", None).unwrap();
+
+        assert_eq!(
+            board.remove_column("todo"),
+            Err(KanbenError::ColumnNotEmpty("todo".to_owned()))
+        );
+    }
+
+    #[test]
+    fn it_keeps_at_least_two_columns() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.remove_column("doing").unwrap();
+
+        assert_eq!(
+            board.remove_column("done"),
+            Err(KanbenError::TooFewColumns)
+        );
+    }
+
+    #[test]
+    fn renaming_a_column_moves_its_tasks() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        board.rename_column("todo", "backlog").unwrap();
+
+        assert_eq!(
+            board.get_config().columns,
+            vec!("backlog", "doing", "done")
+        );
+        assert_eq!(
+            board.get_column("backlog", None).iter()
+                .map(|t| t.column.clone())
+                .collect::<Vec<String>>(),
+            vec!("backlog", "backlog")
+        );
+    }
+
+    #[test]
+    fn it_can_reorder_columns() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.add_column("review").unwrap();
+        board.reorder_column("review", 2).unwrap();
+
+        assert_eq!(
+            board.get_config().columns,
+            vec!("todo", "doing", "review", "done")
+        );
+    }
+
+    #[test]
+    fn reindex_moves_tasks_from_unknown_columns_to_the_backlog() {
+        let mut store = StoreMock::new();
+        store.set("test", get_task("test", "blocked")).unwrap();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        let _ = board.reindex_columns();
+
+        assert_eq!(board.get_column("todo", None).len(), 1);
+        assert!(store.set_called_with("test", &get_task("test", "todo")));
+    }
+
+    #[test]
+    fn update_rejects_moves_the_workflow_doesnt_allow() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut config = BoardConfig::default();
+        config.workflow = Some(config.linear_workflow());
+        config_store.set(CONFIG_KEY, config).unwrap();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("test", None).unwrap();
+        let result = board.update("test", get_task("test", "done"));
+
+        assert_eq!(
+            result,
+            Err(KanbenError::IllegalMove{
+                from: "todo".to_owned(),
+                to: "done".to_owned()
+            })
+        );
+        assert_eq!(board.get("test").unwrap().column, "todo");
+        assert_eq!(board.get_column("done", None).len(), 0);
+    }
+
+    #[test]
+    fn force_update_ignores_the_workflow() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut config = BoardConfig::default();
+        config.workflow = Some(config.linear_workflow());
+        config_store.set(CONFIG_KEY, config).unwrap();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("test", None).unwrap();
+        board.force_update("test", get_task("test", "done")).unwrap();
+
+        assert_eq!(board.get_column("done", None).len(), 1);
+    }
+
+    #[test]
+    fn update_fails_for_a_missing_task() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        assert_eq!(
+            board.update("test", get_task("test", "doing")),
+            Err(KanbenError::TaskNotFound("test".to_owned()))
+        );
+    }
+
+    #[test]
+    fn update_rejects_columns_that_dont_exist() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("test", None).unwrap();
+
+        assert_eq!(
+            board.update("test", get_task("test", "blocked")),
+            Err(KanbenError::ColumnNotFound("blocked".to_owned()))
+        );
+    }
+
+    #[test]
+    fn it_wont_save_a_workflow_with_unknown_columns() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        let mut config = BoardConfig::default();
+        config.workflow = Some(vec!(Transition::new("todo", "review")));
+
+        assert_eq!(
+            board.save_config(config),
+            Err(KanbenError::ColumnNotFound("review".to_owned()))
+        );
+    }
+
+    #[test]
+    fn removing_a_column_drops_it_from_the_workflow() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut config = BoardConfig::default();
+        config.columns.push("archived".to_owned());
+        config.workflow = Some(config.linear_workflow());
+        config_store.set(CONFIG_KEY, config).unwrap();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.remove_column("archived").unwrap();
+
+        assert_eq!(
+            board.get_config().workflow,
+            Some(vec!(
+                Transition::new("todo", "doing"),
+                Transition::new("doing", "done")
+            ))
+        );
+    }
+
+    #[test]
+    fn strict_limits_refuse_moves_into_a_full_column() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut config = BoardConfig::default();
+        config.limits.insert("doing".to_owned(), 1);
+        config.strict_limits = true;
+        config_store.set(CONFIG_KEY, config).unwrap();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        board.update("task1", get_task("task1", "doing")).unwrap();
+        let result = board.update("task2", get_task("task2", "doing"));
+
+        assert_eq!(
+            result,
+            Err(KanbenError::WipLimitReached{
+                column: "doing".to_owned(),
+                limit: 1
+            })
+        );
+        assert_eq!(board.get_column("doing", None).len(), 1);
+    }
+
+    #[test]
+    fn soft_limits_let_the_move_happen() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut config = BoardConfig::default();
+        config.limits.insert("doing".to_owned(), 1);
+        config_store.set(CONFIG_KEY, config).unwrap();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        board.update("task1", get_task("task1", "doing")).unwrap();
+        board.update("task2", get_task("task2", "doing")).unwrap();
+
+        assert_eq!(board.get_column("doing", None).len(), 2);
+        assert_eq!(board.take_over_limit(), vec!(OverLimit{
+            column: "doing".to_owned(),
+            count: 2,
+            limit: 1
+        }));
+        assert!(board.take_over_limit().is_empty());
+    }
+
+    fn limited(column: &str, strict: bool) -> Stores {
+        let mut stores = Stores::new();
+        let mut config = BoardConfig::default();
+        config.limits.insert(column.to_owned(), 1);
+        config.strict_limits = strict;
+        stores.config.set(CONFIG_KEY, config).unwrap();
+        stores
+    }
+
+    #[test]
+    fn strict_limits_apply_to_new_tasks() {
+        let mut stores = limited("todo", true);
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+
+        let result = board.create_task("b", None);
+
+        assert_eq!(result, Err(KanbenError::WipLimitReached{
+            column: "todo".to_owned(),
+            limit: 1
+        }));
+        assert!(board.get("b").is_none());
+    }
+
+    #[test]
+    fn soft_limits_note_new_tasks_over_the_limit() {
+        let mut stores = limited("todo", false);
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+        board.create_task("b", None).unwrap();
+
+        assert_eq!(board.take_over_limit(), vec!(OverLimit{
+            column: "todo".to_owned(),
+            count: 2,
+            limit: 1
+        }));
+    }
+
+    #[test]
+    fn strict_limits_apply_to_tasks_from_other_boards() {
+        let mut stores = limited("doing", true);
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+        board.update("a", get_task("a", "doing")).unwrap();
+
+        let result = board.insert_task(get_task("b", "doing"), false);
+
+        assert_eq!(result, Err(KanbenError::WipLimitReached{
+            column: "doing".to_owned(),
+            limit: 1
+        }));
+        assert!(board.get("b").is_none());
+    }
+
+    #[test]
+    fn changes_that_fail_arent_noted_as_over_the_limit() {
+        let mut stores = limited("doing", false);
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+        board.create_task("b", None).unwrap();
+        board.update("a", get_task("a", "doing")).unwrap();
+        drop(board);
+        stores.tasks.fail_writes();
+        let mut board = stores.board();
+
+        let result = board.update("b", get_task("b", "doing"));
+
+        assert!(result.is_err());
+        assert!(board.take_over_limit().is_empty());
+    }
+
+    #[test]
+    fn force_update_ignores_strict_limits() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut config = BoardConfig::default();
+        config.limits.insert("doing".to_owned(), 0);
+        config.strict_limits = true;
+        config_store.set(CONFIG_KEY, config).unwrap();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("task1", None).unwrap();
+        board.force_update("task1", get_task("task1", "doing")).unwrap();
+
+        assert_eq!(board.get_column("doing", None).len(), 1);
+    }
+
+    #[test]
+    fn new_tasks_are_given_increasing_ids() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+
+        assert_eq!(board.get("task1").unwrap().id, Some(1));
+        assert_eq!(board.get("task2").unwrap().id, Some(2));
+        assert_eq!(board.find_by_id(2).unwrap().name, "task2");
+    }
+
+    #[test]
+    fn ids_are_not_reused_after_a_delete() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("task1", None).unwrap();
+        board.remove("task1").unwrap();
+        board.create_task("task2", None).unwrap();
+
+        assert_eq!(board.get("task2").unwrap().id, Some(2));
+    }
+
+    #[test]
+    fn update_keeps_the_existing_id() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("test", None).unwrap();
+        let mut task = get_task("test", "doing");
+        task.id = Some(99);
+        board.update("test", task).unwrap();
+
+        assert_eq!(board.get("test").unwrap().id, Some(1));
+    }
+
+    fn move_task(stores: &mut Stores, at: fn() -> u64, column: &str) {
+        let mut board = stores.board().with_clock(at);
+        let mut task = board.get("test").unwrap();
+        task.column = column.to_owned();
+        board.update("test", task).unwrap();
+    }
+
+    #[test]
+    fn tasks_keep_the_times_they_moved() {
+        let mut stores = Stores::new();
+        stores.board().with_clock(|| 100).create_task("test", None).unwrap();
+        move_task(&mut stores, || 200, "doing");
+        move_task(&mut stores, || 300, "done");
+
+        assert_eq!(stores.board().get("test").unwrap().times, Times{
+            created: Some(100),
+            started: Some(200),
+            completed: Some(300),
+            moved: Some(300)
+        });
+    }
+
+    #[test]
+    fn a_task_stays_started_but_not_completed_when_it_goes_back() {
+        let mut stores = Stores::new();
+        stores.board().with_clock(|| 100).create_task("test", None).unwrap();
+        move_task(&mut stores, || 200, "doing");
+        move_task(&mut stores, || 300, "done");
+        move_task(&mut stores, || 400, "doing");
+
+        assert_eq!(stores.board().get("test").unwrap().times, Times{
+            created: Some(100),
+            started: Some(200),
+            completed: None,
+            moved: Some(400)
+        });
+    }
+
+    #[test]
+    fn updates_cant_change_a_tasks_times() {
+        let mut stores = Stores::new();
+        let mut board = stores.board().with_clock(|| 100);
+        board.create_task("test", None).unwrap();
+        let mut task = board.get("test").unwrap();
+        task.tags = Some(vec!("tag".to_owned()));
+        task.times = Times::default();
+        board.update("test", task).unwrap();
+
+        let times = board.get("test").unwrap().times;
+        assert_eq!(times, Times{ created: Some(100), ..Times::default() });
+    }
+
+    #[test]
+    fn renaming_a_task_keeps_its_place_in_the_column() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        board.create_task("task3", None).unwrap();
+        board.rename("task2", "renamed").unwrap();
+
+        assert_eq!(
+            col_store.get("todo").unwrap(),
+            vec!("task1", "renamed", "task3")
+        );
+        assert!(store.rm_called_with("task2"));
+    }
+
+    #[test]
+    fn renaming_a_task_keeps_its_id_and_tags() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        tag_store.set("tag", vec!("other".to_owned())).unwrap();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("task", Some("tag".to_owned())).unwrap();
+        board.rename("task", "renamed").unwrap();
+
+        let task = board.get("renamed").unwrap();
+        assert_eq!(task.name, "renamed");
+        assert_eq!(task.id, Some(1));
+        assert_eq!(task.tags, Some(vec!("tag".to_owned())));
+        assert_eq!(
+            tag_store.get("tag").unwrap(),
+            vec!("other", "renamed")
+        );
+    }
+
+    #[test]
+    fn it_wont_rename_over_an_existing_task() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+
+        assert_eq!(
+            board.rename("task1", "task2"),
+            Err(KanbenError::TaskExists("task2".to_owned()))
+        );
+        assert_eq!(
+            col_store.get("todo").unwrap(),
+            vec!("task1", "task2")
+        );
+    }
+
+    fn get_task(key: &str, column: &str) -> Task {
         Task {
             name: key.to_owned(),
-            column,
-            description: None,
-            tags: None
+            column: column.to_owned(),
+            ..Task::default()
         }
     }
 }
@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+
+// settings that belong to a single board. these are kept in
+// the `board` bucket, next to the column lists themselves
+
+pub const CONFIG_KEY: &str = "board";
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct BoardConfig {
+    pub columns: Vec<String>,
+    // when there is no workflow every move is allowed
+    #[serde(default)]
+    pub workflow: Option<Vec<Transition>>,
+    // the most tasks each column should hold at once
+    #[serde(default)]
+    pub limits: BTreeMap<String, usize>,
+    // strict limits refuse moves into a full column, otherwise
+    // the move goes ahead with a warning
+    #[serde(default)]
+    pub strict_limits: bool,
+    // the id handed to the most recently created task
+    #[serde(default)]
+    pub last_id: u64
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Transition {
+    pub from: String,
+    pub to: String
+}
+
+impl Transition {
+    pub fn new(from: &str, to: &str) -> Transition {
+        Transition{ from: from.to_owned(), to: to.to_owned() }
+    }
+}
+
+impl Default for BoardConfig {
+    fn default() -> BoardConfig {
+        BoardConfig{
+            columns: vec!(
+                "todo".to_owned(),
+                "doing".to_owned(),
+                "done".to_owned()
+            ),
+            workflow: None,
+            limits: BTreeMap::new(),
+            strict_limits: false,
+            last_id: 0
+        }
+    }
+}
+
+impl BoardConfig {
+    pub fn has_column(&self, label: &str) -> bool {
+        self.position(label).is_some()
+    }
+
+    pub fn position(&self, label: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == label)
+    }
+
+    // new tasks land in the first column
+    pub fn backlog(&self) -> String {
+        self.columns.first().cloned().unwrap_or_default()
+    }
+
+    // finished tasks live in the last column
+    pub fn done(&self) -> String {
+        self.columns.last().cloned().unwrap_or_default()
+    }
+
+    // everything between the first and last columns is
+    // considered to be in progress
+    pub fn in_progress(&self) -> Vec<String> {
+        if self.columns.len() < 3 {
+            return vec!();
+        }
+
+        self.columns[1..self.columns.len() - 1].to_vec()
+    }
+
+    // `start` moves a task into the first in-progress column
+    pub fn start_column(&self) -> String {
+        match self.in_progress().first() {
+            Some(c) => c.clone(),
+            None => self.done()
+        }
+    }
+
+    pub fn limit(&self, label: &str) -> Option<usize> {
+        self.limits.get(label).cloned()
+    }
+
+    pub fn allows(&self, from: &str, to: &str) -> bool {
+        if from == to {
+            return true;
+        }
+
+        match &self.workflow {
+            None => true,
+            Some(w) => w.iter().any(|t| t.from == from && t.to == to)
+        }
+    }
+
+    // every move between neighbouring columns, left to right
+    pub fn linear_workflow(&self) -> Vec<Transition> {
+        self.columns.windows(2)
+            .map(|pair| Transition::new(&pair[0], &pair[1]))
+            .collect()
+    }
+
+    // spells out the implicit "anything goes" workflow so that
+    // single moves can be taken away from it
+    pub fn workflow_or_all(&self) -> Vec<Transition> {
+        match &self.workflow {
+            Some(w) => w.clone(),
+            None => self.columns.iter().flat_map(|from| {
+                self.columns.iter()
+                    .filter(move |to| *to != from)
+                    .map(move |to| Transition::new(from, to))
+            }).collect()
+        }
+    }
+
+    pub fn rename_column(&mut self, label: &str, new_label: &str) {
+        if let Some(i) = self.position(label) {
+            self.columns[i] = new_label.to_owned();
+        }
+
+        if let Some(limit) = self.limits.remove(label) {
+            self.limits.insert(new_label.to_owned(), limit);
+        }
+
+        if let Some(w) = self.workflow.as_mut() {
+            for t in w.iter_mut() {
+                if t.from == label {
+                    t.from = new_label.to_owned();
+                }
+                if t.to == label {
+                    t.to = new_label.to_owned();
+                }
+            }
+        }
+    }
+
+    pub fn remove_column(&mut self, label: &str) {
+        self.columns.retain(|c| c != label);
+        self.limits.remove(label);
+        if let Some(w) = self.workflow.as_mut() {
+            w.retain(|t| t.from != label && t.to != label);
+        }
+    }
+}
+
+pub fn column_label(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_defaults_to_todo_doing_done() {
+        let config = BoardConfig::default();
+        assert_eq!(config.backlog(), "todo");
+        assert_eq!(config.in_progress(), vec!("doing".to_owned()));
+        assert_eq!(config.done(), "done");
+    }
+
+    #[test]
+    fn columns_between_first_and_last_are_in_progress() {
+        let config = BoardConfig{
+            columns: vec!(
+                "todo".to_owned(),
+                "doing".to_owned(),
+                "review".to_owned(),
+                "done".to_owned()
+            ),
+            ..BoardConfig::default()
+        };
+
+        assert_eq!(
+            config.in_progress(),
+            vec!("doing".to_owned(), "review".to_owned())
+        );
+        assert_eq!(config.start_column(), "doing");
+    }
+
+    #[test]
+    fn start_goes_to_done_when_there_is_no_middle_column() {
+        let config = BoardConfig{
+            columns: vec!("todo".to_owned(), "done".to_owned()),
+            ..BoardConfig::default()
+        };
+
+        assert_eq!(config.start_column(), "done");
+    }
+
+    #[test]
+    fn every_move_is_allowed_without_a_workflow() {
+        let config = BoardConfig::default();
+        assert!(config.allows("todo", "done"));
+    }
+
+    #[test]
+    fn a_linear_workflow_only_allows_the_next_column() {
+        let mut config = BoardConfig::default();
+        config.workflow = Some(config.linear_workflow());
+
+        assert!(config.allows("todo", "doing"));
+        assert!(config.allows("doing", "done"));
+        assert!(!config.allows("todo", "done"));
+        assert!(!config.allows("done", "doing"));
+    }
+
+    #[test]
+    fn renaming_a_column_updates_the_workflow() {
+        let mut config = BoardConfig::default();
+        config.workflow = Some(config.linear_workflow());
+
+        config.rename_column("doing", "wip");
+
+        assert!(config.allows("todo", "wip"));
+        assert!(config.allows("wip", "done"));
+    }
+
+    #[test]
+    fn renaming_a_column_keeps_its_limit() {
+        let mut config = BoardConfig::default();
+        config.limits.insert("doing".to_owned(), 3);
+
+        config.rename_column("doing", "wip");
+
+        assert_eq!(config.limit("wip"), Some(3));
+        assert_eq!(config.limit("doing"), None);
+    }
+
+    #[test]
+    fn column_labels_are_lowercase_and_trimmed() {
+        assert_eq!(column_label(" Review "), "review");
+    }
+}
@@ -0,0 +1,444 @@
+use std::fmt;
+use std::collections::{BTreeMap, BTreeSet};
+use crate::store::Store;
+use crate::error::KanbenError;
+use crate::opts::Task;
+use super::{Board, BoardAccess, BoardConfig};
+
+// the ways the buckets can disagree with each other. tasks are
+// the source of truth - column lists and tag indexes are fixed
+// up to match them
+#[derive(Debug, PartialEq, Clone)]
+pub enum Problem {
+    Undecodable{ bucket: String, key: String },
+    MissingTask{ column: String, key: String },
+    DuplicateEntry{ column: String, key: String },
+    UnknownColumn{ key: String, column: String },
+    Unlisted{ key: String, column: String },
+    Misplaced{ key: String, listed: Vec<String>, column: String },
+    StaleTag{ tag: String, key: String },
+    DuplicateTag{ tag: String, key: String },
+    MissingTag{ tag: String, key: String },
+}
+
+impl Problem {
+    // a record that can't be read is left for someone to look
+    // at, everything else can be rebuilt from the tasks
+    pub fn repairable(&self) -> bool {
+        !matches!(self, Problem::Undecodable{ .. })
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::Undecodable{ bucket, key } => write!(
+                f, "Record '{}' in '{}' can't be read.", key, bucket
+            ),
+            Problem::MissingTask{ column, key } => write!(
+                f, "Column '{}' lists '{}', which doesn't exist.", column, key
+            ),
+            Problem::DuplicateEntry{ column, key } => write!(
+                f, "Column '{}' lists '{}' more than once.", column, key
+            ),
+            Problem::UnknownColumn{ key, column } => write!(
+                f,
+                "Task '{}' is in column '{}', which isn't on the board.",
+                key, column
+            ),
+            Problem::Unlisted{ key, column } => write!(
+                f,
+                "Task '{}' belongs in '{}' but isn't listed in any column.",
+                key, column
+            ),
+            Problem::Misplaced{ key, listed, column } => write!(
+                f,
+                "Task '{}' is listed in '{}' but belongs in '{}'.",
+                key, listed.join("', '"), column
+            ),
+            Problem::StaleTag{ tag, key } => write!(
+                f, "Tag '{}' lists '{}', which doesn't have that tag.", tag, key
+            ),
+            Problem::DuplicateTag{ tag, key } => write!(
+                f, "Tag '{}' lists '{}' more than once.", tag, key
+            ),
+            Problem::MissingTag{ tag, key } => write!(
+                f, "Task '{}' is tagged '{}' but missing from its index.", key, tag
+            ),
+        }
+    }
+}
+
+impl <
+    'a, S: Store<Task>, C: Store<Vec<String>>, K: Store<BoardConfig>
+> Board<'a, S, C, K> {
+    pub(super) fn find_problems(&self) -> Vec<Problem> {
+        let config = self.get_config();
+        let mut problems = vec!();
+
+        let buckets = [
+            ("tasks", self.store.corrupt_keys()),
+            ("columns", self.column_store.corrupt_keys()),
+            ("tags", self.tag_store.corrupt_keys()),
+            ("board", self.config_store.corrupt_keys()),
+        ];
+        for (bucket, keys) in buckets.iter() {
+            for key in keys {
+                problems.push(Problem::Undecodable{
+                    bucket: bucket.to_string(),
+                    key: key.clone()
+                });
+            }
+        }
+        // entries for tasks that can't be read aren't reported
+        // as missing - the task is still there
+        let unreadable = &buckets[0].1;
+
+        let tasks: BTreeMap<String, Task> = self.store.keys().into_iter()
+            .filter_map(|k| self.store.get(&k).map(|t| (k, t)))
+            .collect();
+
+        let mut listed: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for column in self.column_lists(&config) {
+            let mut seen = BTreeSet::new();
+            for key in self.get_column_list(&column) {
+                if !seen.insert(key.clone()) {
+                    problems.push(Problem::DuplicateEntry{
+                        column: column.clone(),
+                        key
+                    });
+                } else if !tasks.contains_key(&key) {
+                    if !unreadable.contains(&key) {
+                        problems.push(Problem::MissingTask{
+                            column: column.clone(),
+                            key
+                        });
+                    }
+                } else {
+                    listed.entry(key).or_insert(vec!()).push(column.clone());
+                }
+            }
+        }
+
+        for (key, task) in tasks.iter() {
+            if !config.has_column(&task.column) {
+                problems.push(Problem::UnknownColumn{
+                    key: key.clone(),
+                    column: task.column.clone()
+                });
+                continue;
+            }
+
+            match listed.get(key) {
+                None => problems.push(Problem::Unlisted{
+                    key: key.clone(),
+                    column: task.column.clone()
+                }),
+                Some(l) if l != &vec!(task.column.clone()) => {
+                    problems.push(Problem::Misplaced{
+                        key: key.clone(),
+                        listed: l.clone(),
+                        column: task.column.clone()
+                    })
+                },
+                _ => ()
+            }
+        }
+
+        for tag in self.tag_store.keys() {
+            let mut seen = BTreeSet::new();
+            for key in self.tag_store.get(&tag).unwrap_or(vec!()) {
+                let tagged = tasks.get(&key)
+                    .and_then(|t| t.tags.as_ref())
+                    .map_or(false, |tags| tags.contains(&tag));
+                if !seen.insert(key.clone()) {
+                    problems.push(Problem::DuplicateTag{
+                        tag: tag.clone(),
+                        key
+                    });
+                } else if !tagged && !unreadable.contains(&key) {
+                    problems.push(Problem::StaleTag{
+                        tag: tag.clone(),
+                        key
+                    });
+                }
+            }
+        }
+
+        for (key, task) in tasks.iter() {
+            for tag in task.tags.clone().unwrap_or(vec!()) {
+                let indexed = self.tag_store.get(&tag)
+                    .map_or(false, |l| l.contains(key));
+                if !indexed {
+                    problems.push(Problem::MissingTag{
+                        tag,
+                        key: key.clone()
+                    });
+                }
+            }
+        }
+
+        problems
+    }
+
+    pub(super) fn fix_problem(
+        &mut self,
+        problem: &Problem
+    ) -> Result<(), KanbenError> {
+        match problem {
+            Problem::Undecodable{ .. } => Ok(()),
+            Problem::MissingTask{ column, key } => {
+                let mut list = self.get_column_list(column);
+                list.retain(|k| k != key);
+                self.column_store.set(column, list)
+            },
+            Problem::DuplicateEntry{ column, key } => {
+                let list = keep_first(self.get_column_list(column), key);
+                self.column_store.set(column, list)
+            },
+            Problem::UnknownColumn{ key, .. } => {
+                let backlog = self.get_config().backlog();
+                if let Some(mut task) = self.store.get(key) {
+                    task.column = backlog.clone();
+                    self.store.set(key, task)?;
+                }
+                self.place(key, &backlog)
+            },
+            Problem::Unlisted{ key, column }
+                | Problem::Misplaced{ key, column, .. } => {
+                self.place(key, column)
+            },
+            Problem::StaleTag{ tag, key } => {
+                let mut list = self.tag_store.get(tag).unwrap_or(vec!());
+                list.retain(|k| k != key);
+                self.tag_store.set(tag, list)
+            },
+            Problem::DuplicateTag{ tag, key } => {
+                let list = self.tag_store.get(tag).unwrap_or(vec!());
+                self.tag_store.set(tag, keep_first(list, key))
+            },
+            Problem::MissingTag{ tag, key } => {
+                let mut list = self.tag_store.get(tag).unwrap_or(vec!());
+                if !list.contains(key) {
+                    list.push(key.clone());
+                }
+                self.tag_store.set(tag, list)
+            },
+        }
+    }
+
+    // lists kept for columns that are no longer on the board
+    // are checked as well as the board's own columns
+    fn column_lists(&self, config: &BoardConfig) -> Vec<String> {
+        let mut columns = config.columns.clone();
+        for key in self.column_store.keys() {
+            if !columns.contains(&key) {
+                columns.push(key);
+            }
+        }
+        columns
+    }
+
+    // leaves a task listed once, in the column it belongs to
+    fn place(&mut self, key: &str, column: &str) -> Result<(), KanbenError> {
+        let config = self.get_config();
+        for label in self.column_lists(&config) {
+            let mut list = self.get_column_list(&label);
+            if label == column {
+                if !list.iter().any(|k| k == key) {
+                    list.push(key.to_owned());
+                    self.column_store.set(&label, list)?;
+                }
+            } else if list.iter().any(|k| k == key) {
+                list.retain(|k| k != key);
+                self.column_store.set(&label, list)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn keep_first(list: Vec<String>, key: &str) -> Vec<String> {
+    let mut found = false;
+    list.into_iter().filter(|k| {
+        if k != key {
+            return true;
+        }
+        let first = !found;
+        found = true;
+        first
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::StoreMock;
+
+    #[test]
+    fn a_healthy_board_has_no_problems() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+        board.create_task("task1", Some("tag".to_owned())).unwrap();
+        board.create_task("task2", None).unwrap();
+
+        assert_eq!(board.check(), vec!());
+    }
+
+    #[test]
+    fn it_finds_problems_with_the_column_lists() {
+        let mut store = StoreMock::new();
+        store.bulk_insert(vec!(
+            ("task1", get_task("task1", "todo")),
+            ("task2", get_task("task2", "doing")),
+            ("task3", get_task("task3", "done")),
+            ("task4", get_task("task4", "review")),
+        ));
+        let mut col_store = StoreMock::new();
+        col_store.bulk_insert(vec!(
+            ("todo", vec!("task1".to_owned(), "task1".to_owned())),
+            ("doing", vec!("gone".to_owned(), "task2".to_owned())),
+            ("done", vec!("task2".to_owned())),
+        ));
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        assert_eq!(board.check(), vec!(
+            Problem::DuplicateEntry{
+                column: "todo".to_owned(),
+                key: "task1".to_owned()
+            },
+            Problem::MissingTask{
+                column: "doing".to_owned(),
+                key: "gone".to_owned()
+            },
+            Problem::Misplaced{
+                key: "task2".to_owned(),
+                listed: vec!("doing".to_owned(), "done".to_owned()),
+                column: "doing".to_owned()
+            },
+            Problem::Unlisted{
+                key: "task3".to_owned(),
+                column: "done".to_owned()
+            },
+            Problem::UnknownColumn{
+                key: "task4".to_owned(),
+                column: "review".to_owned()
+            },
+        ));
+    }
+
+    #[test]
+    fn it_finds_problems_with_the_tag_index() {
+        let mut store = StoreMock::new();
+        let mut task1 = get_task("task1", "todo");
+        task1.tags = Some(vec!("a".to_owned()));
+        store.bulk_insert(vec!(
+            ("task1", task1),
+            ("task2", get_task("task2", "todo")),
+        ));
+        let mut col_store = StoreMock::new();
+        col_store.bulk_insert(vec!(
+            ("todo", vec!("task1".to_owned(), "task2".to_owned())),
+        ));
+        let mut tag_store = StoreMock::new();
+        tag_store.bulk_insert(vec!(
+            ("b", vec!("task2".to_owned(), "task2".to_owned())),
+        ));
+        let mut config_store = StoreMock::new();
+        let board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        assert_eq!(board.check(), vec!(
+            Problem::StaleTag{ tag: "b".to_owned(), key: "task2".to_owned() },
+            Problem::DuplicateTag{ tag: "b".to_owned(), key: "task2".to_owned() },
+            Problem::MissingTag{ tag: "a".to_owned(), key: "task1".to_owned() },
+        ));
+    }
+
+    #[test]
+    fn it_reports_records_that_cant_be_read() {
+        let mut store = StoreMock::new();
+        store.set_corrupt_keys(vec!("broken"));
+        let mut col_store = StoreMock::new();
+        col_store.bulk_insert(vec!(("todo", vec!("broken".to_owned()))));
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        let problems = board.check();
+        assert_eq!(problems, vec!(Problem::Undecodable{
+            bucket: "tasks".to_owned(),
+            key: "broken".to_owned()
+        }));
+        assert!(!problems[0].repairable());
+    }
+
+    #[test]
+    fn it_repairs_what_it_finds() {
+        let mut store = StoreMock::new();
+        let mut task1 = get_task("task1", "todo");
+        task1.tags = Some(vec!("a".to_owned()));
+        store.bulk_insert(vec!(
+            ("task1", task1),
+            ("task2", get_task("task2", "doing")),
+            ("task3", get_task("task3", "review")),
+        ));
+        let mut col_store = StoreMock::new();
+        col_store.bulk_insert(vec!(
+            ("todo", vec!("gone".to_owned(), "task2".to_owned())),
+            ("doing", vec!("task2".to_owned(), "task2".to_owned())),
+        ));
+        let mut tag_store = StoreMock::new();
+        tag_store.bulk_insert(vec!(("b", vec!("task1".to_owned()))));
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        assert_eq!(board.repair(), Ok(7));
+        assert_eq!(board.check(), vec!());
+        assert_eq!(
+            col_store.get("todo").unwrap(),
+            vec!("task1", "task3")
+        );
+        assert_eq!(col_store.get("doing").unwrap(), vec!("task2"));
+        assert_eq!(tag_store.get("a").unwrap(), vec!("task1"));
+        assert_eq!(tag_store.get("b").unwrap(), Vec::<String>::new());
+        assert_eq!(store.get("task3").unwrap().column, "todo");
+    }
+
+    fn get_task(key: &str, column: &str) -> Task {
+        Task {
+            name: key.to_owned(),
+            column: column.to_owned(),
+            ..Task::default()
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use std::{collections::BTreeSet, env, process::Command};
+use serde::{Serialize, Deserialize};
+use crate::store::Store;
+use crate::error::KanbenError;
+use crate::opts::Task;
+use super::{Board, BoardConfig, Entry};
+
+// something that happened to a task
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EventKind {
+    Created { column: String },
+    Moved { from: String, to: String },
+    Tagged { tag: String },
+    Untagged { tag: String },
+    // positions in the column, starting from 1
+    Prioritised { column: String, from: usize, to: usize },
+    Described,
+    Renamed { from: String },
+    Deleted,
+    Restored,
+    Removed
+}
+
+// events are kept in the order they happened, one record each,
+// under a number that counts up
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Event {
+    // seconds since the epoch
+    pub at: u64,
+    // which task it happened to, see `history_key`
+    pub key: String,
+    // the task's title at the time
+    pub task: String,
+    pub kind: EventKind,
+    // who made the change, if that could be told. events recorded
+    // before this was kept have none
+    #[serde(default)]
+    pub actor: Option<String>
+}
+
+// who's running kanben: their login name, or failing that the
+// name they commit to git under. it can start git, so it's worked
+// out once rather than for every event
+pub fn user() -> Option<String> {
+    let login = ["USER", "USERNAME"].iter()
+        .filter_map(|v| env::var(v).ok())
+        .find(|u| !u.trim().is_empty());
+    if login.is_some() {
+        return login;
+    }
+
+    let output = Command::new("git")
+        .args(["config", "user.name"])
+        .output()
+        .ok()?;
+    let name = String::from_utf8(output.stdout).ok()?;
+    let name = name.trim();
+    if name.is_empty() { None } else { Some(name.to_owned()) }
+}
+
+// a task's history goes by its id, so it stays with it through
+// renames and after it's deleted
+fn history_key(task: &Task) -> String {
+    match task.id {
+        Some(id) => id.to_string(),
+        None => task.name.clone()
+    }
+}
+
+// what a journal entry did to each task, going forwards or, when
+// it's being undone, backwards
+fn events(entry: &Entry, undo: bool) -> Vec<(Task, EventKind)> {
+    let added = entry.tasks.iter()
+        .filter_map(|c| match c.sides(undo) {
+            (None, Some(task)) => Some(task),
+            _ => None
+        })
+        .collect::<Vec<&Task>>();
+    let gone = entry.tasks.iter()
+        .filter_map(|c| match c.sides(undo) {
+            (Some(task), None) => Some(task),
+            _ => None
+        })
+        .collect::<Vec<&Task>>();
+    let trashed = entry.trash.iter()
+        .filter_map(|c| c.sides(undo).1.as_ref().map(|t| t.key()))
+        .collect::<BTreeSet<String>>();
+    let untrashed = entry.trash.iter()
+        .filter(|c| c.sides(undo).1.is_none())
+        .filter_map(|c| c.sides(undo).0.as_ref().map(|t| t.key()))
+        .collect::<BTreeSet<String>>();
+    let same_task = |a: &Task, b: &Task| a.id.is_some() && a.id == b.id;
+
+    let mut found = vec!();
+    for task in added.iter() {
+        let kind = match gone.iter().find(|old| same_task(old, task)) {
+            Some(old) => EventKind::Renamed{ from: old.name.clone() },
+            None if untrashed.contains(&history_key(task)) => {
+                EventKind::Restored
+            },
+            None => EventKind::Created{ column: task.column.clone() }
+        };
+        found.push(((*task).clone(), kind));
+    }
+    for task in gone.iter() {
+        if added.iter().any(|new| same_task(task, new)) {
+            continue;
+        }
+        let kind = if trashed.contains(&history_key(task)) {
+            EventKind::Deleted
+        } else {
+            EventKind::Removed
+        };
+        found.push(((*task).clone(), kind));
+    }
+
+    for change in entry.tasks.iter() {
+        let (old, new) = match change.sides(undo) {
+            (Some(old), Some(new)) => (old, new),
+            _ => continue
+        };
+        if old.column != new.column {
+            found.push((new.clone(), EventKind::Moved{
+                from: old.column.clone(),
+                to: new.column.clone()
+            }));
+        }
+        let old_tags = old.tags.clone().unwrap_or_default();
+        let new_tags = new.tags.clone().unwrap_or_default();
+        for tag in new_tags.iter().filter(|t| !old_tags.contains(t)) {
+            found.push((new.clone(), EventKind::Tagged{ tag: tag.clone() }));
+        }
+        for tag in old_tags.iter().filter(|t| !new_tags.contains(t)) {
+            found.push((new.clone(), EventKind::Untagged{ tag: tag.clone() }));
+        }
+        if old.description != new.description {
+            found.push((new.clone(), EventKind::Described));
+        }
+    }
+    found
+}
+
+impl <
+    'a, S: Store<Task>, C: Store<Vec<String>>, K: Store<BoardConfig>
+> Board<'a, S, C, K> {
+    pub(super) fn log(
+        &mut self,
+        task: &Task,
+        kind: EventKind
+    ) -> Result<(), KanbenError> {
+        let at = (self.clock)();
+        let history = match self.history.as_deref_mut() {
+            Some(h) => h,
+            None => return Ok(())
+        };
+
+        // only the first event has to look for the last number. one
+        // skipped when a change is rolled back leaves a gap, which
+        // doesn't change the order
+        let next = match self.next_event {
+            Some(n) => n,
+            None => history.keys().iter()
+                .filter_map(|k| k.parse::<u64>().ok())
+                .max()
+                .map_or(1, |n| n + 1)
+        };
+        self.next_event = Some(next + 1);
+        history.set(&format!("{:010}", next), Event{
+            at,
+            key: history_key(task),
+            task: task.name.clone(),
+            kind,
+            actor: self.actor.clone()
+        })
+    }
+
+    pub(super) fn log_entry(
+        &mut self,
+        entry: &Entry,
+        undo: bool
+    ) -> Result<(), KanbenError> {
+        for (task, kind) in events(entry, undo) {
+            self.log(&task, kind)?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn task_history(&self, task: &Task) -> Vec<Event> {
+        let key = history_key(task);
+        self.board_history().into_iter().filter(|e| e.key == key).collect()
+    }
+
+    // everything, oldest first
+    pub(super) fn board_history(&self) -> Vec<Event> {
+        let history = match self.history.as_deref() {
+            Some(h) => h,
+            None => return vec!()
+        };
+        let mut keys = history.keys();
+        keys.sort();
+        history.get_many(&keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardAccess, Position};
+    use crate::test::Stores;
+
+    fn kinds(events: Vec<Event>) -> Vec<EventKind> {
+        events.into_iter().map(|e| e.kind).collect()
+    }
+
+    fn todo() -> String {
+        "todo".to_owned()
+    }
+
+    #[test]
+    fn a_task_keeps_a_history_of_its_changes() {
+        let mut stores = Stores::new();
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+        board.create_task("b", None).unwrap();
+        let mut task = board.get("b").unwrap();
+        task.column = "doing".to_owned();
+        task.tags = Some(vec!("x".to_owned()));
+        task.description = Some("files/2/description.md".to_owned());
+        board.update("b", task).unwrap();
+        board.described("b").unwrap();
+
+        assert_eq!(kinds(board.history("b")), vec!(
+            EventKind::Created{ column: todo() },
+            EventKind::Moved{ from: todo(), to: "doing".to_owned() },
+            EventKind::Tagged{ tag: "x".to_owned() },
+            EventKind::Described,
+            EventKind::Described
+        ));
+        assert_eq!(board.history("a").len(), 1);
+    }
+
+    #[test]
+    fn priority_moves_are_recorded() {
+        let mut stores = Stores::new();
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+        board.create_task("b", None).unwrap();
+        board.top_priority("b").unwrap();
+        board.move_within("b", Position::Top).unwrap();
+
+        assert_eq!(kinds(board.history("b")), vec!(
+            EventKind::Created{ column: todo() },
+            EventKind::Prioritised{ column: todo(), from: 2, to: 1 }
+        ));
+        assert_eq!(board.history("a").len(), 1);
+    }
+
+    #[test]
+    fn history_follows_a_task_through_a_rename() {
+        let mut stores = Stores::new();
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+        board.rename("a", "b").unwrap();
+
+        let history = board.history("b");
+
+        assert_eq!(kinds(history.clone()), vec!(
+            EventKind::Created{ column: todo() },
+            EventKind::Renamed{ from: "a".to_owned() }
+        ));
+        assert_eq!(history[0].task, "a");
+        assert_eq!(history[1].task, "b");
+    }
+
+    #[test]
+    fn deleting_restoring_and_undoing_are_recorded() {
+        let mut stores = Stores::new();
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+        board.delete("a").unwrap();
+        board.restore("1").unwrap();
+        board.undo().unwrap();
+        board.undo().unwrap();
+
+        assert_eq!(kinds(board.history("a")), vec!(
+            EventKind::Created{ column: todo() },
+            EventKind::Deleted,
+            EventKind::Restored,
+            EventKind::Deleted,
+            EventKind::Restored
+        ));
+    }
+
+    #[test]
+    fn each_event_says_who_made_it() {
+        let mut stores = Stores::new();
+        let mut board = stores.board().with_actor(Some("ben".to_owned()));
+        board.create_task("a", None).unwrap();
+
+        assert_eq!(board.history("a")[0].actor, Some("ben".to_owned()));
+    }
+
+    #[test]
+    fn events_are_by_nobody_unless_the_board_is_told_who() {
+        let mut stores = Stores::new();
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+
+        assert_eq!(board.history("a")[0].actor, None);
+    }
+
+    #[test]
+    fn events_carry_on_from_the_last_one_recorded() {
+        let mut stores = Stores::new();
+        stores.history.set("0000000007", Event{
+            at: 0,
+            key: "9".to_owned(),
+            task: "old".to_owned(),
+            kind: EventKind::Removed,
+            actor: None
+        }).unwrap();
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+        board.create_task("b", None).unwrap();
+
+        let mut keys = stores.history.keys();
+        keys.sort();
+        assert_eq!(keys, vec!("0000000007", "0000000008", "0000000009"));
+    }
+
+    #[test]
+    fn the_whole_board_is_in_the_order_things_happened() {
+        let mut stores = Stores::new();
+        let mut board = stores.board().with_clock(|| 10);
+        board.create_task("b", None).unwrap();
+        board.create_task("a", None).unwrap();
+        board.remove("b").unwrap();
+
+        let events = board.all_history().into_iter()
+            .map(|e| (e.task, e.kind))
+            .collect::<Vec<(String, EventKind)>>();
+
+        assert_eq!(events, vec!(
+            ("b".to_owned(), EventKind::Created{ column: todo() }),
+            ("a".to_owned(), EventKind::Created{ column: todo() }),
+            ("b".to_owned(), EventKind::Removed)
+        ));
+    }
+}
@@ -0,0 +1,576 @@
+use std::collections::{BTreeMap, BTreeSet};
+use serde::{Serialize, Deserialize};
+use crate::store::Store;
+use crate::error::KanbenError;
+use crate::opts::Task;
+use super::{Board, BoardAccess, BoardConfig, Trashed, CONFIG_KEY};
+
+// how many changes are kept to undo
+const JOURNAL_LENGTH: usize = 100;
+
+// a record either side of a change. None is a record that
+// didn't exist
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Change<T> {
+    pub key: String,
+    pub before: Option<T>,
+    pub after: Option<T>
+}
+
+impl<T> Change<T> {
+    // the side undoing or redoing moves away from, then the
+    // side it moves to
+    pub fn sides(&self, undo: bool) -> (&Option<T>, &Option<T>) {
+        if undo {
+            (&self.after, &self.before)
+        } else {
+            (&self.before, &self.after)
+        }
+    }
+}
+
+// one change to the board, holding every record it touched so
+// it can be undone and redone exactly
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Entry {
+    pub name: String,
+    // seconds since the epoch
+    pub at: u64,
+    #[serde(default)]
+    pub undone: bool,
+    pub tasks: Vec<Change<Task>>,
+    pub columns: Vec<Change<Vec<String>>>,
+    pub tags: Vec<Change<Vec<String>>>,
+    pub config: Vec<Change<BoardConfig>>,
+    pub trash: Vec<Change<Trashed>>
+}
+
+impl Entry {
+    fn between(name: String, at: u64, before: &State, after: &State) -> Entry {
+        Entry{
+            name,
+            at,
+            undone: false,
+            tasks: diff(&before.tasks, &after.tasks),
+            columns: diff(&before.columns, &after.columns),
+            tags: diff(&before.tags, &after.tags),
+            config: diff(&before.config, &after.config),
+            trash: diff(&before.trash, &after.trash)
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+            && self.columns.is_empty()
+            && self.tags.is_empty()
+            && self.config.is_empty()
+            && self.trash.is_empty()
+    }
+
+    // whether the board is still as this change left it, or
+    // as it found it when redoing
+    fn matches(&self, state: &State, undo: bool) -> bool {
+        same_tasks(&self.tasks, &state.tasks, undo)
+            && same(&self.columns, &state.columns, undo)
+            && same(&self.tags, &state.tags, undo)
+            && same(&self.config, &state.config, undo)
+            && same(&self.trash, &state.trash, undo)
+    }
+}
+
+// the records a change touched in each store
+pub(super) struct Touched {
+    tasks: Vec<String>,
+    columns: Vec<String>,
+    tags: Vec<String>,
+    config: Vec<String>,
+    trash: Vec<String>
+}
+
+impl Touched {
+    fn by(entry: &Entry) -> Touched {
+        Touched{
+            tasks: entry.tasks.iter().map(|c| c.key.clone()).collect(),
+            columns: entry.columns.iter().map(|c| c.key.clone()).collect(),
+            tags: entry.tags.iter().map(|c| c.key.clone()).collect(),
+            config: entry.config.iter().map(|c| c.key.clone()).collect(),
+            trash: entry.trash.iter().map(|c| c.key.clone()).collect()
+        }
+    }
+}
+
+fn staged_keys<T, St: Store<T> + ?Sized>(store: &St) -> Vec<String> {
+    store.staged().into_iter().map(|(key, _)| key).collect()
+}
+
+// the touched records either side of a change. a board that's
+// never saved its config has the default one, and the last id
+// handed out is left out, undoing a change never hands an id
+// out again
+pub(super) struct State {
+    tasks: BTreeMap<String, Task>,
+    columns: BTreeMap<String, Vec<String>>,
+    tags: BTreeMap<String, Vec<String>>,
+    config: BTreeMap<String, BoardConfig>,
+    trash: BTreeMap<String, Trashed>
+}
+
+fn read_all<T, St: Store<T> + ?Sized>(store: &St) -> BTreeMap<String, T> {
+    store.keys().into_iter()
+        .filter_map(|k| store.get(&k).map(|v| (k, v)))
+        .collect()
+}
+
+// the records as they are now, or as they were last committed
+fn read<T, St: Store<T> + ?Sized>(
+    store: &St,
+    keys: &[String],
+    stored: bool
+) -> BTreeMap<String, T> {
+    keys.iter()
+        .filter_map(|k| {
+            let value = if stored { store.stored(k) } else { store.get(k) };
+            value.map(|v| (k.clone(), v))
+        })
+        .collect()
+}
+
+// an empty column or tag is the same as one that isn't stored,
+// and some stores don't keep them
+fn lists(
+    lists: BTreeMap<String, Vec<String>>
+) -> BTreeMap<String, Vec<String>> {
+    lists.into_iter().filter(|(_, l)| !l.is_empty()).collect()
+}
+
+fn diff<T: PartialEq + Clone>(
+    before: &BTreeMap<String, T>,
+    after: &BTreeMap<String, T>
+) -> Vec<Change<T>> {
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    keys.into_iter()
+        .filter(|k| before.get(*k) != after.get(*k))
+        .map(|k| Change{
+            key: k.clone(),
+            before: before.get(k).cloned(),
+            after: after.get(k).cloned()
+        })
+        .collect()
+}
+
+fn same<T: PartialEq>(
+    changes: &[Change<T>],
+    state: &BTreeMap<String, T>,
+    undo: bool
+) -> bool {
+    changes.iter().all(|c| state.get(&c.key) == c.sides(undo).0.as_ref())
+}
+
+// where a task's description is kept can be moved along without
+// being journaled, see `set_description`, so that's left out
+fn same_tasks(
+    changes: &[Change<Task>],
+    state: &BTreeMap<String, Task>,
+    undo: bool
+) -> bool {
+    let undescribed = |task: Option<&Task>| task.map(|t| Task{
+        description: None,
+        ..t.clone()
+    });
+    changes.iter().all(|c| {
+        undescribed(state.get(&c.key)) == undescribed(c.sides(undo).0.as_ref())
+    })
+}
+
+fn put<T: Clone, St: Store<T> + ?Sized>(
+    store: &mut St,
+    changes: &[Change<T>],
+    undo: bool
+) -> Result<(), KanbenError> {
+    for change in changes {
+        match change.sides(undo).1 {
+            Some(value) => store.set(&change.key, value.clone())?,
+            None => store.rm(&change.key)?
+        }
+    }
+    Ok(())
+}
+
+// a task going into the trash takes its description from the
+// task as it is now. the copy the trash had before may well have
+// been cleared out when the task last came back
+fn take_description(
+    change: Change<Trashed>,
+    tasks: &[Change<Task>],
+    undo: bool
+) -> Change<Trashed> {
+    let mut change = change;
+    let name = match change.sides(undo).1 {
+        Some(trashed) => trashed.task.name.clone(),
+        None => return change
+    };
+    let description = tasks.iter()
+        .filter_map(|c| match c.sides(undo) {
+            (Some(task), None) if task.name == name => Some(task),
+            _ => None
+        })
+        .next()
+        .map(|task| task.description.clone());
+
+    let trashed = if undo { &mut change.before } else { &mut change.after };
+    if let (Some(trashed), Some(description)) = (trashed, description) {
+        trashed.task.description = description;
+    }
+    change
+}
+
+impl <
+    'a, S: Store<Task>, C: Store<Vec<String>>, K: Store<BoardConfig>
+> Board<'a, S, C, K> {
+    // the keys written since the stores' `begin`
+    pub(super) fn touched(&self) -> Touched {
+        Touched{
+            tasks: staged_keys(&*self.store),
+            columns: staged_keys(&*self.column_store),
+            tags: staged_keys(&*self.tag_store),
+            config: staged_keys(&*self.config_store),
+            trash: self.trash.as_deref().map_or(vec!(), staged_keys)
+        }
+    }
+
+    // the touched records as they are, or as they were before
+    // the change being made when `stored` is set
+    pub(super) fn state(&self, touched: &Touched, stored: bool) -> State {
+        let mut config = read(&*self.config_store, &touched.config, stored);
+        if touched.config.iter().any(|k| k == CONFIG_KEY) {
+            config.entry(CONFIG_KEY.to_owned())
+                .or_insert_with(BoardConfig::default);
+        }
+        for c in config.values_mut() {
+            c.last_id = 0;
+        }
+        State{
+            tasks: read(&*self.store, &touched.tasks, stored),
+            columns: lists(read(&*self.column_store, &touched.columns, stored)),
+            tags: lists(read(&*self.tag_store, &touched.tags, stored)),
+            config,
+            trash: self.trash.as_deref()
+                .map(|t| read(t, &touched.trash, stored))
+                .unwrap_or_default()
+        }
+    }
+
+    // adds what's changed since `before` to the journal and the
+    // history. once something new has been done, whatever was
+    // undone can't be redone any more
+    pub(super) fn record(
+        &mut self,
+        name: String,
+        touched: &Touched,
+        before: State
+    ) -> Result<(), KanbenError> {
+        let after = self.state(touched, false);
+        let entry = Entry::between(name, (self.clock)(), &before, &after);
+        self.log_entry(&entry, false)?;
+        let journal = match self.journal.as_deref_mut() {
+            Some(j) if !entry.is_empty() => j,
+            _ => return Ok(())
+        };
+
+        let mut keys = journal.keys();
+        keys.sort();
+        for key in keys.iter() {
+            if journal.get(key).filter(|e| e.undone).is_some() {
+                journal.rm(key)?;
+            }
+        }
+        let next = keys.last()
+            .and_then(|k| k.parse::<u64>().ok())
+            .map_or(1, |n| n + 1);
+        journal.set(&format!("{:08}", next), entry)?;
+
+        let mut keys = journal.keys();
+        keys.sort();
+        let extra = keys.len().saturating_sub(JOURNAL_LENGTH);
+        for key in keys.iter().take(extra) {
+            journal.rm(key)?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn journal_entries(&self) -> Vec<(String, Entry)> {
+        let mut entries = match self.journal.as_deref() {
+            Some(j) => read_all(j).into_iter().collect(),
+            None => vec!()
+        };
+        entries.sort_by(|a: &(String, Entry), b| a.0.cmp(&b.0));
+        entries
+    }
+
+    // undoes the latest change still standing, or redoes the
+    // earliest one undone. tasks are committed before the trash,
+    // so a markdown task coming back can still copy its
+    // description in from there
+    pub(super) fn step(
+        &mut self,
+        undo: bool
+    ) -> Result<Option<Entry>, KanbenError> {
+        let entries = self.journal_entries();
+        let found = if undo {
+            entries.into_iter().rev().find(|(_, e)| !e.undone)
+        } else {
+            entries.into_iter().find(|(_, e)| e.undone)
+        };
+        let (key, mut entry) = match found {
+            Some(f) => f,
+            None => return Ok(None)
+        };
+
+        let (removed, kept): (Vec<Change<Trashed>>, Vec<Change<Trashed>>) =
+            entry.trash.iter().cloned()
+                .partition(|c| c.sides(undo).1.is_none());
+        let kept = kept.into_iter()
+            .map(|c| take_description(c, &entry.tasks, undo))
+            .collect::<Vec<Change<Trashed>>>();
+
+        entry.undone = undo;
+        self.atomically(|board| {
+            let now = board.state(&Touched::by(&entry), false);
+            if !entry.matches(&now, undo) {
+                return Err(KanbenError::ChangedSince(entry.name.clone()));
+            }
+
+            for change in entry.tasks.iter() {
+                let mut change = change.clone();
+                board.bring_description(&mut change, &removed, undo);
+                board.keep_description(&mut change, undo);
+                put(&mut *board.store, &[change], undo)?;
+            }
+            put(&mut *board.column_store, &entry.columns, undo)?;
+            put(&mut *board.tag_store, &entry.tags, undo)?;
+            if let Some(trash) = board.trash.as_deref_mut() {
+                put(trash, &kept, undo)?;
+                put(trash, &removed, undo)?;
+            }
+
+            let last_id = board.get_config().last_id;
+            for change in entry.config.iter() {
+                let mut change = change.clone();
+                let sides = change.before.iter_mut()
+                    .chain(change.after.iter_mut());
+                for config in sides {
+                    config.last_id = last_id;
+                }
+                put(&mut *board.config_store, &[change], undo)?;
+            }
+
+            board.log_entry(&entry, undo)?;
+            match board.journal.as_deref_mut() {
+                Some(journal) => journal.set(&key, entry.clone()),
+                None => Ok(())
+            }
+        })?;
+        Ok(Some(entry))
+    }
+
+    // a task that's staying on the board keeps its description
+    // where it is now, the file may have been moved since
+    fn keep_description(&self, change: &mut Change<Task>, undo: bool) {
+        let current = match self.store.get(&change.key) {
+            Some(task) => task.description,
+            None => return
+        };
+        let task = if undo { &mut change.before } else { &mut change.after };
+        if let Some(task) = task {
+            task.description = current;
+        }
+    }
+
+    // a task coming back out of the trash takes its description
+    // from there. on most boards that's the same file anyway
+    fn bring_description(
+        &self,
+        change: &mut Change<Task>,
+        removed: &[Change<Trashed>],
+        undo: bool
+    ) {
+        let name = match change.sides(undo) {
+            (None, Some(task)) => task.name.clone(),
+            _ => return
+        };
+        let from_trash = removed.iter()
+            .filter_map(|c| c.sides(undo).0.as_ref())
+            .find(|t| t.task.name == name)
+            .and_then(|t| t.task.description.clone());
+
+        let task = if undo { &mut change.before } else { &mut change.after };
+        if let (Some(task), Some(description)) = (task, from_trash) {
+            task.description = Some(description);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::Stores;
+    use crate::error::KanbenError;
+
+    fn names(board: &impl BoardAccess, column: &str) -> Vec<String> {
+        board.get_column(column, None).into_iter().map(|t| t.name).collect()
+    }
+
+    #[test]
+    fn every_change_is_recorded() {
+        let mut stores = Stores::new();
+        let mut board = stores.board();
+
+        board.create_task("a", None).unwrap();
+        board.create_task("b", None).unwrap();
+        board.top_priority("b").unwrap();
+
+        let journal = board.journal().into_iter()
+            .map(|e| e.name)
+            .collect::<Vec<String>>();
+        assert_eq!(journal, vec!("add 'a'", "add 'b'", "move 'b' to the top"));
+    }
+
+    #[test]
+    fn undoing_puts_tasks_columns_and_tags_back() {
+        let mut stores = Stores::new();
+        let mut board = stores.board();
+        board.create_task("a", Some("tag".to_owned())).unwrap();
+        board.create_task("b", None).unwrap();
+        let mut done = board.get("a").unwrap();
+        done.column = "done".to_owned();
+        board.update("a", done).unwrap();
+
+        let entry = board.undo().unwrap().unwrap();
+
+        assert_eq!(entry.name, "move 'a' to done");
+        assert_eq!(names(&board, "todo"), vec!("a", "b"));
+        assert!(names(&board, "done").is_empty());
+        assert_eq!(board.get_column("todo", Some("tag".to_owned())).len(), 1);
+        assert_eq!(board.get("a").unwrap().column, "todo");
+    }
+
+    #[test]
+    fn undone_changes_can_be_redone() {
+        let mut stores = Stores::new();
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+        board.create_task("b", None).unwrap();
+        board.top_priority("b").unwrap();
+
+        board.undo().unwrap();
+        board.undo().unwrap();
+        assert_eq!(names(&board, "todo"), vec!("a"));
+        board.redo().unwrap();
+
+        assert_eq!(names(&board, "todo"), vec!("a", "b"));
+        assert_eq!(board.get("b").unwrap().id, Some(2));
+        assert_eq!(board.redo().unwrap().unwrap().name, "move 'b' to the top");
+        assert_eq!(names(&board, "todo"), vec!("b", "a"));
+        assert_eq!(board.redo().unwrap(), None);
+    }
+
+    #[test]
+    fn undoing_a_delete_takes_the_task_out_of_the_trash() {
+        let mut stores = Stores::new();
+        let mut board = stores.board();
+        board.create_task("a", Some("tag".to_owned())).unwrap();
+        board.delete("a").unwrap();
+
+        board.undo().unwrap();
+
+        assert_eq!(names(&board, "todo"), vec!("a"));
+        assert_eq!(board.get_column("todo", Some("tag".to_owned())).len(), 1);
+        assert!(board.trashed().is_empty());
+    }
+
+    #[test]
+    fn undoing_never_hands_an_id_out_twice() {
+        let mut stores = Stores::new();
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+
+        board.undo().unwrap();
+        board.create_task("b", None).unwrap();
+
+        assert_eq!(board.get("b").unwrap().id, Some(2));
+    }
+
+    #[test]
+    fn something_new_drops_what_was_undone() {
+        let mut stores = Stores::new();
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+        board.create_task("b", None).unwrap();
+
+        board.undo().unwrap();
+        board.create_task("c", None).unwrap();
+
+        assert_eq!(board.journal().len(), 2);
+        assert_eq!(board.redo().unwrap(), None);
+    }
+
+    #[test]
+    fn only_so_many_changes_are_kept() {
+        let mut stores = Stores::new();
+        let mut board = stores.board();
+
+        for i in 0..JOURNAL_LENGTH + 5 {
+            board.create_task(&format!("task {}", i), None).unwrap();
+        }
+
+        let journal = board.journal();
+        assert_eq!(journal.len(), JOURNAL_LENGTH);
+        assert_eq!(journal[0].name, "add 'task 5'");
+    }
+
+    #[test]
+    fn it_wont_undo_over_changes_it_didnt_record() {
+        let mut stores = Stores::new();
+        {
+            let mut board = stores.board();
+            board.create_task("a", None).unwrap();
+        }
+        let mut task = stores.tasks.get("a").unwrap();
+        task.column = "doing".to_owned();
+        stores.tasks.set("a", task).unwrap();
+        let mut board = stores.board();
+
+        assert_eq!(
+            board.undo(),
+            Err(KanbenError::ChangedSince("add 'a'".to_owned()))
+        );
+        assert!(board.get("a").is_some());
+        assert!(!board.journal()[0].undone);
+    }
+
+    #[test]
+    fn a_change_that_cant_be_recorded_still_stands() {
+        let mut stores = Stores::new();
+        stores.journal.fail_writes();
+        let mut board = stores.board();
+
+        assert_eq!(board.create_task("a", None), Ok(()));
+
+        assert!(board.get("a").is_some());
+        assert!(board.journal().is_empty());
+    }
+
+    #[test]
+    fn moving_a_description_isnt_a_change_of_its_own() {
+        let mut stores = Stores::new();
+        let mut board = stores.board();
+        board.create_task("a", None).unwrap();
+
+        board.set_description("a", "files/1/description.md").unwrap();
+
+        assert_eq!(board.journal().len(), 1);
+        assert_eq!(board.history("a").len(), 1);
+        assert_eq!(board.undo().unwrap().unwrap().name, "add 'a'");
+        assert!(board.get("a").is_none());
+    }
+}
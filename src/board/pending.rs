@@ -0,0 +1,171 @@
+use serde::{Serialize, Deserialize};
+use crate::store::Store;
+use crate::error::KanbenError;
+use crate::opts::Task;
+use super::{Board, BoardConfig, Trashed, Entry, Event};
+
+pub const PENDING_KEY: &str = "pending";
+
+// what one store is about to write, None for a key it's removing
+type Writes<T> = Vec<(String, Option<T>)>;
+
+// every write a change makes, across all of the board's stores.
+// it's saved before any of them are committed and removed once
+// they all have been, so a change cut short part way through can
+// be finished from it
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct Pending {
+    pub config: Writes<BoardConfig>,
+    pub tasks: Writes<Task>,
+    pub columns: Writes<Vec<String>>,
+    pub tags: Writes<Vec<String>>,
+    pub trash: Writes<Trashed>,
+    pub history: Writes<Event>,
+    pub journal: Writes<Entry>
+}
+
+impl Pending {
+    fn is_empty(&self) -> bool {
+        self.config.is_empty()
+            && self.tasks.is_empty()
+            && self.columns.is_empty()
+            && self.tags.is_empty()
+            && self.trash.is_empty()
+            && self.history.is_empty()
+            && self.journal.is_empty()
+    }
+}
+
+fn put<T, St: Store<T> + ?Sized>(
+    store: &mut St,
+    writes: Writes<T>
+) -> Result<(), KanbenError> {
+    for (key, value) in writes {
+        match value {
+            Some(v) => store.set(&key, v)?,
+            None => store.rm(&key)?
+        }
+    }
+    Ok(())
+}
+
+impl <
+    'a, S: Store<Task>, C: Store<Vec<String>>, K: Store<BoardConfig>
+> Board<'a, S, C, K> {
+    // the stores are committed in the same order the record is
+    // written back in. tasks go before the trash, so a task coming
+    // out of it can still copy its description from there. a
+    // transaction around them all is committed last
+    pub(super) fn commit_stores(&mut self) -> Result<(), KanbenError> {
+        self.config_store.commit()?;
+        self.store.commit()?;
+        self.column_store.commit()?;
+        self.tag_store.commit()?;
+        if let Some(trash) = self.trash.as_deref_mut() {
+            trash.commit()?;
+        }
+        if let Some(history) = self.history.as_deref_mut() {
+            history.commit()?;
+        }
+        if let Some(journal) = self.journal.as_deref_mut() {
+            journal.commit()?;
+        }
+        match self.transaction {
+            Some(transaction) => transaction.commit(),
+            None => Ok(())
+        }
+    }
+
+    // saves what's about to be committed, if there's anywhere to
+    // save it and anything to save
+    pub(super) fn save_pending(&mut self) -> Result<bool, KanbenError> {
+        let staged = Pending{
+            config: self.config_store.staged(),
+            tasks: self.store.staged(),
+            columns: self.column_store.staged(),
+            tags: self.tag_store.staged(),
+            trash: self.trash.as_deref().map_or(vec!(), |t| t.staged()),
+            history: self.history.as_deref().map_or(vec!(), |h| h.staged()),
+            journal: self.journal.as_deref().map_or(vec!(), |j| j.staged())
+        };
+        match self.pending.as_deref_mut() {
+            Some(pending) if !staged.is_empty() => {
+                pending.set(PENDING_KEY, staged)?;
+                Ok(true)
+            },
+            _ => Ok(false)
+        }
+    }
+
+    // once everything's committed the record isn't needed. one
+    // left behind only writes the same values again
+    pub(super) fn clear_pending(&mut self) {
+        if let Some(pending) = self.pending.as_deref_mut() {
+            let _ = pending.rm(PENDING_KEY);
+        }
+    }
+
+    // finishes a change that was cut short, by a crash or a
+    // failed write, by writing all of it again. returns whether
+    // there was one
+    pub fn recover(&mut self) -> Result<bool, KanbenError> {
+        let record = match self.pending.as_deref()
+            .and_then(|p| p.get(PENDING_KEY)) {
+            Some(r) => r,
+            None => return Ok(false)
+        };
+
+        put(&mut *self.config_store, record.config)?;
+        put(&mut *self.store, record.tasks)?;
+        put(&mut *self.column_store, record.columns)?;
+        put(&mut *self.tag_store, record.tags)?;
+        if let Some(trash) = self.trash.as_deref_mut() {
+            put(trash, record.trash)?;
+        }
+        if let Some(history) = self.history.as_deref_mut() {
+            put(history, record.history)?;
+        }
+        if let Some(journal) = self.journal.as_deref_mut() {
+            put(journal, record.journal)?;
+        }
+        self.clear_pending();
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::BoardAccess;
+    use crate::test::Stores;
+
+    #[test]
+    fn a_change_cut_short_is_finished_when_the_board_is_opened() {
+        let mut stores = Stores::new();
+        stores.board().create_task("a", Some("tag".to_owned())).unwrap();
+        stores.tags.fail_next_commit();
+
+        let result = stores.board().delete("a");
+
+        assert!(result.is_err());
+        assert!(stores.tasks.get("a").is_none());
+        assert_eq!(stores.tags.get("tag"), Some(vec!("a".to_owned())));
+
+        let mut board = stores.board();
+        assert_eq!(board.recover(), Ok(true));
+
+        assert!(board.get_column("todo", Some("tag".to_owned())).is_empty());
+        assert_eq!(board.trashed().len(), 1);
+        assert_eq!(board.recover(), Ok(false));
+    }
+
+    #[test]
+    fn nothing_is_left_to_finish_once_a_change_is_committed() {
+        let mut stores = Stores::new();
+
+        stores.board().create_task("a", None).unwrap();
+
+        assert!(stores.pending.get(PENDING_KEY).is_none());
+        assert!(stores.pending.set_called());
+    }
+}
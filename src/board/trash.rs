@@ -0,0 +1,23 @@
+use serde::{Serialize, Deserialize};
+use crate::opts::Task;
+
+// a deleted task, kept whole with its tags and where it sat in
+// its column, so `kanben restore` can put it back as it was
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Trashed {
+    pub task: Task,
+    pub position: usize,
+    // seconds since the epoch
+    pub deleted: u64
+}
+
+impl Trashed {
+    // ids are never handed out twice, so they keep deleted tasks
+    // apart even when a title is used again
+    pub fn key(&self) -> String {
+        match self.task.id {
+            Some(id) => id.to_string(),
+            None => self.task.name.clone()
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::error::KanbenError;
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+// seconds since the epoch. a clock set before 1970 counts as 0
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// how long ago `then` was, in the largest whole unit. None when
+// it's under a minute
+fn elapsed(then: u64, now: u64) -> Option<(u64, &'static str)> {
+    let seconds = now.saturating_sub(then);
+    if seconds < MINUTE {
+        None
+    } else if seconds < HOUR {
+        Some((seconds / MINUTE, "minute"))
+    } else if seconds < DAY {
+        Some((seconds / HOUR, "hour"))
+    } else if seconds < WEEK {
+        Some((seconds / DAY, "day"))
+    } else {
+        Some((seconds / WEEK, "week"))
+    }
+}
+
+pub fn ago(then: u64, now: u64) -> String {
+    let (count, unit) = match elapsed(then, now) {
+        Some(e) => e,
+        None => return "just now".to_owned()
+    };
+
+    if count == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", count, unit)
+    }
+}
+
+// the same, short enough to fit in a column: 3d, 5h
+pub fn ago_short(then: u64, now: u64) -> String {
+    match elapsed(then, now) {
+        Some((count, unit)) => format!("{}{}", count, &unit[..1]),
+        None => "now".to_owned()
+    }
+}
+
+// `then` as a UTC date and time, like 2024-03-09 14:05. the
+// date is worked out from the days since 1970, using Howard
+// Hinnant's civil_from_days
+pub fn date(then: u64) -> String {
+    let days = (then / DAY) as i64;
+    let seconds = then % DAY;
+
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}",
+        year, month, day, seconds / HOUR, seconds % HOUR / MINUTE
+    )
+}
+
+// ages are written as a number and a unit, like 30d or 2w
+pub fn parse_age(age: &str) -> Result<u64, KanbenError> {
+    let age = age.trim();
+    let invalid = || KanbenError::InvalidAge(age.to_owned());
+    let split = age.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let count = age[..split].parse::<u64>().map_err(|_| invalid())?;
+    let unit = match &age[split..] {
+        "s" => 1,
+        "m" => MINUTE,
+        "h" => HOUR,
+        "d" => DAY,
+        "w" => WEEK,
+        _ => return Err(invalid())
+    };
+    count.checked_mul(unit).ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ages_are_shown_in_the_largest_whole_unit() {
+        assert_eq!(ago(100, 130), "just now");
+        assert_eq!(ago(0, 90), "1 minute ago");
+        assert_eq!(ago(0, 3 * HOUR + 5), "3 hours ago");
+        assert_eq!(ago(0, 2 * DAY), "2 days ago");
+        assert_eq!(ago(0, 3 * WEEK), "3 weeks ago");
+    }
+
+    #[test]
+    fn short_ages_fit_in_a_column() {
+        assert_eq!(ago_short(100, 130), "now");
+        assert_eq!(ago_short(0, 90), "1m");
+        assert_eq!(ago_short(0, 3 * HOUR + 5), "3h");
+        assert_eq!(ago_short(0, 2 * DAY), "2d");
+        assert_eq!(ago_short(0, 3 * WEEK), "3w");
+    }
+
+    #[test]
+    fn dates_are_shown_in_utc() {
+        assert_eq!(date(0), "1970-01-01 00:00");
+        assert_eq!(
+            date(951_782_400 + 13 * HOUR + 5 * MINUTE),
+            "2000-02-29 13:05"
+        );
+        assert_eq!(date(1_710_000_000), "2024-03-09 16:00");
+    }
+
+    #[test]
+    fn it_reads_ages_with_a_unit() {
+        assert_eq!(parse_age("30d"), Ok(30 * DAY));
+        assert_eq!(parse_age("12h"), Ok(12 * HOUR));
+        assert_eq!(parse_age("2w"), Ok(2 * WEEK));
+    }
+
+    #[test]
+    fn it_refuses_ages_it_cant_read() {
+        for age in ["30", "d", "30 days", "-1d", "99999999999999999w"].iter() {
+            assert_eq!(
+                parse_age(age),
+                Err(KanbenError::InvalidAge(age.to_string()))
+            );
+        }
+    }
+}
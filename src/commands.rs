@@ -1,10 +1,13 @@
 use crate::opts::*;
-use crate::board::BoardAccess;
+use crate::board::{BoardAccess, Position, OverLimit};
+use crate::error::KanbenError;
 use crate::editor::Editor;
+use crate::prompt::Prompt;
 use crate::file::Reader;
 use crate::archive::Archive;
 use std::io::Write;
 use crate::web::Web;
+use crate::clock;
 
 mod list;
 mod edit;
@@ -14,10 +17,27 @@ mod now;
 mod reindex;
 mod tag;
 mod backup;
+mod column;
+mod workflow;
+mod rename;
+mod fsck;
+mod init;
+mod boards;
+mod move_board;
+mod overview;
+mod plan;
+mod descriptions;
+mod gc;
+mod trash;
+mod undo;
+mod history;
+#[cfg(feature = "sqlite")]
+mod migrate_store;
 use list::{list_all, list_tasks};
-use edit::edit_item;
+use edit::{edit_item, edit_full};
 use view::view_item;
 use now::now;
+pub use now::now_all;
 use reindex::reindex;
 use standard_actions::{
     add_item,
@@ -25,51 +45,166 @@ use standard_actions::{
     delete_item,
     clear_done,
     complete_item,
-    top
+    top,
+    reposition,
+    move_within
 };
 use tag::tag;
 use backup::backup;
+use column::column;
+use workflow::workflow;
+use rename::rename_item;
+use fsck::fsck;
+use plan::plan;
+use gc::gc;
+use trash::{trash, restore};
+use undo::{undo, redo};
+use history::{history, task_history};
+pub use init::init;
+pub use boards::{boards, BoardStorage, BoardVisitor};
+pub use move_board::move_board;
+pub use overview::overview;
+pub use descriptions::migrate_descriptions;
+#[cfg(feature = "sqlite")]
+pub use migrate_store::migrate_store;
 
 pub fn handle<B: BoardAccess, W: Write, Wb: Web, A: Archive>(
     opts: Opts,
     board: &mut B,
     writer: &mut W,
     editor: &mut dyn Editor,
+    prompt: &mut dyn Prompt,
     file_reader: &dyn Reader,
     web: &mut Wb,
     archive: &A
-) {
-    match opts.subcmd {
-        None => list_tasks(opts.tag, board, writer),
+) -> Result<(), KanbenError> {
+    let result = match opts.subcmd {
+        None => {
+            list_tasks(opts.tag, clock::now(), board, writer);
+            Ok(())
+        },
         Some(SubCommand::Add(a)) => add_item(
-            a.title, a.tag, board
+            a.title, a.tag, a.suffix, a.open,
+            board, editor, file_reader, writer
+        ),
+        Some(SubCommand::Start(a)) => start_item(
+            resolve(a.title, board), a.force, board
+        ),
+        Some(SubCommand::Delete(a)) => delete_item(
+            resolve(a.title, board), board, editor, writer
+        ),
+        Some(SubCommand::Edit(a)) if a.full => edit_full(
+            resolve(a.title, board), board, editor, file_reader, writer
         ),
-        Some(SubCommand::Start(a)) => start_item(a.title, board),
-        Some(SubCommand::Delete(a)) => delete_item(a.title, board),
         Some(SubCommand::Edit(a)) => edit_item(
-            a.title, board, editor, writer
+            resolve(a.title, board), board, editor, file_reader, writer
+        ),
+        Some(SubCommand::View(a)) if a.history => task_history(
+            resolve(a.title, board), board, writer
         ),
         Some(SubCommand::View(a)) => view_item(
-            a.title, board, writer, file_reader
+            resolve(a.title, board), clock::now(), board, writer, file_reader
         ),
         Some(SubCommand::Complete(a)) => complete_item(
-            a.title, board
-        ),
-        Some(SubCommand::ClearDone) => clear_done(board),
-        Some(SubCommand::Now) => now(
-            board, writer, opts.no_newlines, opts.tag
+            resolve(a.title, board), a.force, board
         ),
+        Some(SubCommand::ClearDone) => clear_done(board, editor, writer),
+        Some(SubCommand::Now(_)) => {
+            now(board, writer, opts.no_newlines, opts.tag);
+            Ok(())
+        },
         Some(SubCommand::Reindex) => reindex(
             board, writer
         ),
-        Some(SubCommand::Top(a)) => top(a.title, board),
+        Some(SubCommand::Top(a)) => top(resolve(a.title, board), board),
+        Some(SubCommand::Bottom(a)) => reposition(
+            resolve(a.title, board), Position::Bottom, board
+        ),
+        Some(SubCommand::Up(a)) => reposition(
+            resolve(a.title, board), Position::Up(a.places), board
+        ),
+        Some(SubCommand::Down(a)) => reposition(
+            resolve(a.title, board), Position::Down(a.places), board
+        ),
+        Some(SubCommand::Move(m)) => move_within(m, board),
+        Some(SubCommand::Plan) => plan(board, editor, prompt, writer),
         Some(SubCommand::Tag(a)) => tag(
-            &a.title, a.tag, a.remove, board, writer
+            &resolve(a.title, board), a.tag, a.remove, board, writer
         ),
-        Some(SubCommand::Tasks) => list_all(
-            opts.tag, board, writer
+        Some(SubCommand::Rename(a)) => rename_item(
+            resolve(a.title, board), a.new_title, board
         ),
-        Some(SubCommand::Backup) => backup(web, archive)
+        Some(SubCommand::Tasks) => {
+            list_all(opts.tag, clock::now(), board, writer);
+            Ok(())
+        },
+        Some(SubCommand::Backup) => backup(web, archive),
+        Some(SubCommand::Column(c)) => column(
+            c.subcmd, board, writer
+        ),
+        Some(SubCommand::Workflow(w)) => workflow(
+            w.subcmd, board, writer
+        ),
+        Some(SubCommand::Fsck(f)) => fsck(f.repair, board, writer),
+        Some(SubCommand::Gc(g)) => gc(
+            g.purge, board, editor, file_reader, prompt, writer
+        ),
+        Some(SubCommand::Trash(t)) => trash(
+            t.subcmd, clock::now(), board, editor, writer
+        ),
+        Some(SubCommand::Restore(a)) => restore(a.title, board, editor, writer),
+        Some(SubCommand::Undo(u)) => undo(
+            u.times, u.list, clock::now(), board, editor, writer
+        ),
+        Some(SubCommand::Redo) => redo(board, editor, writer),
+        Some(SubCommand::History(h)) => history(
+            h.since, clock::now(), board, writer
+        ),
+        // these work on stores, or more than one board, so
+        // main deals with them before a board is opened
+        Some(SubCommand::MigrateStore)
+            | Some(SubCommand::Init(_))
+            | Some(SubCommand::Boards(_))
+            | Some(SubCommand::MoveBoard(_))
+            | Some(SubCommand::Overview) => Ok(())
+    };
+
+    warn_over_limit(board, writer);
+    result
+}
+
+// soft limits let tasks into a full column, so whichever columns
+// a command has left over their limits are pointed out after it
+pub fn warn_over_limit<B: BoardAccess>(board: &mut B, writer: &mut dyn Write) {
+    let mut warned: Vec<OverLimit> = vec!();
+    for over in board.take_over_limit() {
+        warned.retain(|w| w.column != over.column);
+        warned.push(over);
+    }
+
+    for over in warned {
+        let _ = write!(
+            writer,
+            "Warning: column '{}' is over its limit ({}/{}).\n",
+            over.column, over.count, over.limit
+        );
+    }
+}
+
+// tasks can be referred to by id as well as by title. a
+// matching title always wins, so a task called "12" can
+// still be found by name
+fn resolve<B: BoardAccess>(key: String, board: &B) -> String {
+    if board.get(&key).is_some() {
+        return key;
+    }
+
+    let task = key.trim().parse::<u64>().ok()
+        .and_then(|id| board.find_by_id(id));
+
+    match task {
+        Some(t) => t.name,
+        None => key
     }
 }
 
@@ -79,24 +214,29 @@ mod tests {
     use crate::test::{
         BoardMock,
         EditorMock,
+        PromptMock,
         ReaderMock,
         WebMock,
         ArchiveMock,
     };
     use std::io::Cursor;
+    use std::str;
 
     #[test]
     fn it_adds_a_new_item_to_the_store() {
         let mut writer = Cursor::new(vec!());
         let mut board = BoardMock::new();
         let mut editor = EditorMock::new();
+        let mut prompt = PromptMock::new();
         let mut web = WebMock::new();
         let archive = ArchiveMock::new();
         let reader = ReaderMock::new();
         let name = String::from("test");
         let item = NewItem{
             title: name.clone(),
-            tag: None
+            tag: None,
+            suffix: false,
+            open: false
         };
 
         let opts = Opts {
@@ -104,6 +244,8 @@ mod tests {
             no_newlines: false,
             tag: None,
             verbose: false,
+            global: false,
+            board: None,
         };
 
         handle(
@@ -111,10 +253,11 @@ mod tests {
             &mut board,
             &mut writer,
             &mut editor,
+            &mut prompt,
             &reader,
             &mut web,
             &archive
-        );
+        ).unwrap();
         assert!(board.create_task_called_with(&name));
     }
 
@@ -123,31 +266,38 @@ mod tests {
         let mut writer = Cursor::new(vec!());
         let mut board = BoardMock::new();
         let mut editor = EditorMock::new();
+        let mut prompt = PromptMock::new();
         let reader = ReaderMock::new();
         let mut web = WebMock::new();
         let archive = ArchiveMock::new();
         let name = String::from(" ");
         let item = NewItem{
             title: name.clone(),
-            tag: None
+            tag: None,
+            suffix: false,
+            open: false
         };
 
         let opts = Opts {
             subcmd: Some(SubCommand::Add(item.clone())),
             no_newlines: false,
             tag: None,
-            verbose: false
+            verbose: false,
+            global: false,
+            board: None
         };
 
-        handle(
+        let result = handle(
             opts,
             &mut board,
             &mut writer,
             &mut editor,
+            &mut prompt,
             &reader,
             &mut web,
             &archive
         );
+        assert_eq!(result, Err(KanbenError::InvalidTitle));
         assert!(!board.create_task_called_with(" "));
     }
 
@@ -156,6 +306,7 @@ mod tests {
         let mut writer = Cursor::new(vec!());
         let mut board = BoardMock::new();
         let mut editor = EditorMock::new();
+        let mut prompt = PromptMock::new();
         let reader = ReaderMock::new();
         let mut web = WebMock::new();
         let archive = ArchiveMock::new();
@@ -164,6 +315,8 @@ mod tests {
             no_newlines: false,
             tag: None,
             verbose: false,
+            global: false,
+            board: None,
         };
 
         handle(
@@ -171,10 +324,11 @@ mod tests {
             &mut board,
             &mut writer,
             &mut editor,
+            &mut prompt,
             &reader,
             &mut web,
             &archive
-        );
+        ).unwrap();
 
         let output = writer.get_ref();
         assert_eq!(output, b"TODO:\t\t\tDOING:\t\t\tDONE:\n\n");
@@ -185,6 +339,7 @@ mod tests {
         let mut writer = Cursor::new(vec!());
         let mut board = BoardMock::new();
         let mut editor = EditorMock::new();
+        let mut prompt = PromptMock::new();
         let reader = ReaderMock::new();
         let mut web = WebMock::new();
         let archive = ArchiveMock::new();
@@ -192,12 +347,15 @@ mod tests {
         let item = Item{
             title: name.clone()
         };
+        board.set(&name, get_task(&name, "todo"));
 
         let opts = Opts {
             subcmd: Some(SubCommand::Delete(item.clone())),
             no_newlines: false,
             tag: None,
-            verbose: false
+            verbose: false,
+            global: false,
+            board: None
         };
 
         handle(
@@ -205,11 +363,12 @@ mod tests {
             &mut board,
             &mut writer,
             &mut editor,
+            &mut prompt,
             &reader,
             &mut web,
             &archive
-        );
-        assert!(board.remove_called_with(&name));
+        ).unwrap();
+        assert!(board.delete_called_with(&name));
 
     }
 
@@ -218,23 +377,26 @@ mod tests {
         let mut writer = Cursor::new(vec!());
         let mut board = BoardMock::new();
         let mut editor = EditorMock::new();
+        let mut prompt = PromptMock::new();
         let reader = ReaderMock::new();
         let mut web = WebMock::new();
         let archive = ArchiveMock::new();
 
         board.set_tasks(vec!(
-            get_task("task1", Column::Doing),
-            get_task("task2", Column::Todo),
-            get_task("task3", Column::Done),
-            get_task("task4", Column::Done),
-            get_task("task5", Column::Done),
+            get_task("task1", "doing"),
+            get_task("task2", "todo"),
+            get_task("task3", "done"),
+            get_task("task4", "done"),
+            get_task("task5", "done"),
         ));
 
         let opts = Opts {
             subcmd: Some(SubCommand::ClearDone),
             no_newlines: false,
             tag: None,
-            verbose: false
+            verbose: false,
+            global: false,
+            board: None
         };
 
         handle(
@@ -242,15 +404,16 @@ mod tests {
             &mut board,
             &mut writer,
             &mut editor,
+            &mut prompt,
             &reader,
             &mut web,
             &archive
-        );
+        ).unwrap();
 
-        assert!(board.remove_called_with("task3"));
-        assert!(board.remove_called_with("task4"));
-        assert!(board.remove_called_with("task5"));
-        assert!(!board.remove_called_with("task1"));
+        assert!(board.delete_called_with("task3"));
+        assert!(board.delete_called_with("task4"));
+        assert!(board.delete_called_with("task5"));
+        assert!(!board.delete_called_with("task1"));
     }
 
     #[test]
@@ -258,15 +421,17 @@ mod tests {
         let mut writer = Cursor::new(vec!());
         let mut board = BoardMock::new();
         let mut editor = EditorMock::new();
+        let mut prompt = PromptMock::new();
         let reader = ReaderMock::new();
         let mut web = WebMock::new();
         let archive = ArchiveMock::new();
         let name = String::from("test");
-        let item = Item{
-            title: name.clone()
+        let item = EditItem{
+            title: name.clone(),
+            full: false
         };
 
-        let mut task = get_task(&name, Column::Todo);
+        let mut task = get_task(&name, "todo");
         task.description = Some("test".to_owned());
 
         board.set(&name, task);
@@ -275,7 +440,9 @@ mod tests {
             subcmd: Some(SubCommand::Edit(item.clone())),
             no_newlines: false,
             tag: None,
-            verbose: false
+            verbose: false,
+            global: false,
+            board: None
         };
 
         handle(
@@ -283,10 +450,11 @@ mod tests {
             &mut board,
             &mut writer,
             &mut editor,
+            &mut prompt,
             &reader,
             &mut web,
             &archive
-        );
+        ).unwrap();
         assert!(editor.open_called());
     }
 
@@ -295,15 +463,17 @@ mod tests {
         let mut writer = Cursor::new(vec!());
         let mut board = BoardMock::new();
         let mut editor = EditorMock::new();
+        let mut prompt = PromptMock::new();
         let mut reader = ReaderMock::new();
         let mut web = WebMock::new();
         let archive = ArchiveMock::new();
         let name = String::from("test");
-        let item = Item{
-            title: name.clone()
+        let item = ViewItem{
+            title: name.clone(),
+            history: false
         };
 
-        let mut task = get_task(&name, Column::Todo);
+        let mut task = get_task(&name, "todo");
         task.description = Some("test".to_owned());
 
         board.set(&name, task);
@@ -313,17 +483,20 @@ mod tests {
             subcmd: Some(SubCommand::View(item.clone())),
             no_newlines: false,
             tag: None,
-            verbose: false
+            verbose: false,
+            global: false,
+            board: None
         };
         handle(
             opts,
             &mut board,
             &mut writer,
             &mut editor,
+            &mut prompt,
             &reader,
             &mut web,
             &archive
-        );
+        ).unwrap();
 
         let output = writer.get_ref();
         assert_eq!(output, b"abcdef\n");
@@ -334,15 +507,18 @@ mod tests {
         let mut writer = Cursor::new(vec!());
         let mut board = BoardMock::new();
         let mut editor = EditorMock::new();
+        let mut prompt = PromptMock::new();
         let reader = ReaderMock::new();
         let mut web = WebMock::new();
         let archive = ArchiveMock::new();
 
         let opts = Opts{
-            subcmd: Some(SubCommand::Now),
+            subcmd: Some(SubCommand::Now(NowItem{ all_boards: false })),
             no_newlines: false,
             tag: None,
-            verbose: false
+            verbose: false,
+            global: false,
+            board: None
         };
 
         handle(
@@ -350,10 +526,11 @@ mod tests {
             &mut board,
             &mut writer,
             &mut editor,
+            &mut prompt,
             &reader,
             &mut web,
             &archive
-        );
+        ).unwrap();
 
         let output = writer.get_ref();
         assert_eq!(output, b"");
@@ -364,6 +541,7 @@ mod tests {
         let mut writer = Cursor::new(vec!());
         let mut board = BoardMock::new();
         let mut editor = EditorMock::new();
+        let mut prompt = PromptMock::new();
         let reader = ReaderMock::new();
         let mut web = WebMock::new();
         let archive = ArchiveMock::new();
@@ -372,7 +550,9 @@ mod tests {
             subcmd: Some(SubCommand::Backup),
             no_newlines: false,
             tag: None,
-            verbose: false
+            verbose: false,
+            global: false,
+            board: None
         };
 
         handle(
@@ -380,22 +560,130 @@ mod tests {
             &mut board,
             &mut writer,
             &mut editor,
+            &mut prompt,
             &reader,
             &mut web,
             &archive
-        );
+        ).unwrap();
 
         let output = writer.get_ref();
         assert_eq!(output, b"");
 
     }
 
-    fn get_task(key: &str, column: Column) -> Task {
+    #[test]
+    fn it_reports_when_starting_a_missing_task() {
+        let mut writer = Cursor::new(vec!());
+        let mut board = BoardMock::new();
+        let mut editor = EditorMock::new();
+        let mut prompt = PromptMock::new();
+        let reader = ReaderMock::new();
+        let mut web = WebMock::new();
+        let archive = ArchiveMock::new();
+        let item = MoveItem{
+            title: "test".to_owned(),
+            force: false
+        };
+
+        let opts = Opts{
+            subcmd: Some(SubCommand::Start(item)),
+            no_newlines: false,
+            tag: None,
+            verbose: false,
+            global: false,
+            board: None
+        };
+
+        let result = handle(
+            opts,
+            &mut board,
+            &mut writer,
+            &mut editor,
+            &mut prompt,
+            &reader,
+            &mut web,
+            &archive
+        );
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "No task named 'test' found."
+        );
+    }
+
+    #[test]
+    fn it_finds_tasks_by_id() {
+        let mut writer = Cursor::new(vec!());
+        let mut board = BoardMock::new();
+        let mut editor = EditorMock::new();
+        let mut prompt = PromptMock::new();
+        let reader = ReaderMock::new();
+        let mut web = WebMock::new();
+        let archive = ArchiveMock::new();
+        let mut task = get_task("a long task title", "todo");
+        task.id = Some(12);
+        board.set("a long task title", task);
+
+        let opts = Opts {
+            subcmd: Some(SubCommand::Delete(Item{
+                title: "12".to_owned()
+            })),
+            no_newlines: false,
+            tag: None,
+            verbose: false,
+            global: false,
+            board: None
+        };
+
+        handle(
+            opts,
+            &mut board,
+            &mut writer,
+            &mut editor,
+            &mut prompt,
+            &reader,
+            &mut web,
+            &archive
+        ).unwrap();
+        assert!(board.delete_called_with("a long task title"));
+    }
+
+    #[test]
+    fn a_title_takes_precedence_over_an_id() {
+        let mut board = BoardMock::new();
+        let mut task = get_task("other task", "todo");
+        task.id = Some(12);
+        board.set("other task", task);
+        board.set("12", get_task("12", "todo"));
+
+        assert_eq!(resolve("12".to_owned(), &board), "12");
+    }
+
+    #[test]
+    fn it_warns_once_for_each_column_over_its_limit() {
+        let mut writer = Cursor::new(vec!());
+        let mut board = BoardMock::new();
+        board.set_over_limit(vec!(
+            OverLimit{column: "doing".to_owned(), count: 2, limit: 1},
+            OverLimit{column: "todo".to_owned(), count: 4, limit: 3},
+            OverLimit{column: "doing".to_owned(), count: 3, limit: 1}
+        ));
+
+        warn_over_limit(&mut board, &mut writer);
+
+        assert_eq!(
+            str::from_utf8(writer.get_ref()).unwrap(),
+            "Warning: column 'todo' is over its limit (4/3).\n\
+             Warning: column 'doing' is over its limit (3/1).\n"
+        );
+        assert!(board.take_over_limit().is_empty());
+    }
+
+    fn get_task(key: &str, column: &str) -> Task {
         Task {
             name: key.to_owned(),
-            column,
-            description: None,
-            tags: None
+            column: column.to_owned(),
+            ..Task::default()
         }
     }
 }
@@ -1,17 +1,22 @@
 use crate::web::Web;
 use crate::archive::Archive;
+use crate::error::KanbenError;
 use log::info;
 
 // zip up config folder
 // send to web service
 // report back with success/failure
-pub fn backup<W: Web, A: Archive>(web: &mut W, archive: &A) {
+pub fn backup<W: Web, A: Archive>(
+    web: &mut W,
+    archive: &A
+) -> Result<(), KanbenError> {
     info!("backing up");
-    let file = archive.write();
+    let file = archive.write()?;
     info!("file written");
-    let r = web.send_backup("test-backup-1", file);
+    web.send_backup("test-backup-1", file)?;
 
-    info!("backup successful: {}", r.is_ok());
+    info!("backup successful");
+    Ok(())
 }
 
 #[cfg(test)]
@@ -25,9 +30,21 @@ mod tests {
         let mut web = WebMock::new();
         let mut archive = ArchiveMock::new();
 
-        backup(&mut web, &archive);
+        backup(&mut web, &archive).unwrap();
 
         assert!(web.send_backup_called());
     }
 
+    #[test]
+    fn it_reports_a_failed_upload() {
+        let mut web = WebMock::new();
+        let archive = ArchiveMock::new();
+        web.fail_backup();
+
+        assert_eq!(
+            backup(&mut web, &archive),
+            Err(KanbenError::Web("upload failed".to_owned()))
+        );
+    }
+
 }
@@ -0,0 +1,235 @@
+use std::io::Write;
+use crate::board::BoardAccess;
+use crate::settings::{Settings, MAIN_BOARD};
+use crate::opts::BoardsCommand;
+use crate::error::KanbenError;
+
+// something to do with an open board. each store builds its
+// boards differently, so they're handed over rather than returned
+pub trait BoardVisitor {
+    fn visit<B: BoardAccess>(
+        &mut self, board: &mut B
+    ) -> Result<(), KanbenError>;
+}
+
+// wherever the boards are kept. a board is created the first
+// time it's opened, so there's nothing to do until one is deleted
+pub trait BoardStorage {
+    fn task_count(&self, board: &str) -> Result<usize, KanbenError>;
+    fn drop_board(&mut self, board: &str) -> Result<(), KanbenError>;
+    fn with_board<V: BoardVisitor>(
+        &self, board: &str, visitor: &mut V
+    ) -> Result<(), KanbenError>;
+}
+
+// the list of boards lives in settings.yml, which the caller
+// saves once this returns
+pub fn boards<S: BoardStorage, W: Write>(
+    cmd: Option<BoardsCommand>,
+    settings: &mut Settings,
+    storage: &mut S,
+    writer: &mut W
+) -> Result<(), KanbenError> {
+    match cmd {
+        None => {
+            list_boards(settings, writer);
+            Ok(())
+        },
+        Some(BoardsCommand::Create(b)) => {
+            check_name(&b.name)?;
+            if settings.has_board(&b.name) {
+                return Err(KanbenError::BoardNameTaken(b.name));
+            }
+            settings.boards.push(b.name.clone());
+            let _ = write!(writer, "Created board '{}'.\n", b.name);
+            Ok(())
+        },
+        Some(BoardsCommand::Delete(b)) => {
+            if b.name == MAIN_BOARD {
+                return Err(KanbenError::MainBoard);
+            }
+            if !settings.has_board(&b.name) {
+                return Err(KanbenError::BoardNotFound(b.name));
+            }
+            if !b.force && storage.task_count(&b.name)? > 0 {
+                return Err(KanbenError::BoardNotEmpty(b.name));
+            }
+
+            storage.drop_board(&b.name)?;
+            settings.boards.retain(|n| *n != b.name);
+            if settings.default_board.as_ref() == Some(&b.name) {
+                settings.default_board = None;
+            }
+            let _ = write!(writer, "Deleted board '{}'.\n", b.name);
+            Ok(())
+        },
+        Some(BoardsCommand::Default(b)) => {
+            if !settings.has_board(&b.name) {
+                return Err(KanbenError::BoardNotFound(b.name));
+            }
+            settings.default_board = if b.name == MAIN_BOARD {
+                None
+            } else {
+                Some(b.name.clone())
+            };
+            let _ = write!(writer, "'{}' is now the default board.\n", b.name);
+            Ok(())
+        }
+    }
+}
+
+// the default board is marked with a '*'
+fn list_boards<W: Write>(settings: &Settings, writer: &mut W) {
+    let default = settings.default_board.as_deref().unwrap_or(MAIN_BOARD);
+    let boards = settings.board_names().iter().map(|b| {
+        let mark = if b == default { "*" } else { " " };
+        format!("{} {}", mark, b)
+    }).collect::<Vec<String>>().join("\n");
+    let _ = write!(writer, "{}\n", boards);
+}
+
+// names end up in bucket and file names, so keep them plain
+fn check_name(name: &str) -> Result<(), KanbenError> {
+    let valid = !name.is_empty() && name.chars().all(|c| {
+        c.is_ascii_alphanumeric() || c == '-' || c == '_'
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(KanbenError::InvalidBoardName(name.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opts::{BoardItem, DeleteBoardItem};
+    use crate::test::StorageMock;
+    use std::{str, io::Cursor};
+
+    fn settings() -> Settings {
+        Settings{
+            boards: vec!("work".to_owned()),
+            ..Settings::default()
+        }
+    }
+
+    fn item(name: &str) -> BoardItem {
+        BoardItem{ name: name.to_owned() }
+    }
+
+    #[test]
+    fn it_lists_the_boards() {
+        let mut writer = Cursor::new(vec!());
+        let mut settings = settings();
+        settings.default_board = Some("work".to_owned());
+
+        boards(None, &mut settings, &mut StorageMock::new(), &mut writer)
+            .unwrap();
+
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert_eq!(output, "  main\n* work\n");
+    }
+
+    #[test]
+    fn it_creates_a_board() {
+        let mut writer = Cursor::new(vec!());
+        let mut settings = settings();
+
+        boards(
+            Some(BoardsCommand::Create(item("home"))),
+            &mut settings,
+            &mut StorageMock::new(),
+            &mut writer
+        ).unwrap();
+
+        assert_eq!(settings.boards, vec!("work", "home"));
+    }
+
+    #[test]
+    fn board_names_are_checked() {
+        let mut writer = Cursor::new(vec!());
+        let mut settings = settings();
+        let mut storage = StorageMock::new();
+
+        let taken = boards(
+            Some(BoardsCommand::Create(item("main"))),
+            &mut settings, &mut storage, &mut writer
+        );
+        let invalid = boards(
+            Some(BoardsCommand::Create(item("my/board"))),
+            &mut settings, &mut storage, &mut writer
+        );
+
+        assert_eq!(taken, Err(KanbenError::BoardNameTaken("main".to_owned())));
+        assert_eq!(
+            invalid,
+            Err(KanbenError::InvalidBoardName("my/board".to_owned()))
+        );
+        assert_eq!(settings.boards, vec!("work"));
+    }
+
+    #[test]
+    fn it_only_deletes_a_board_with_tasks_when_forced() {
+        let mut writer = Cursor::new(vec!());
+        let mut settings = settings();
+        settings.default_board = Some("work".to_owned());
+        let mut storage = StorageMock::new();
+        storage.setup("work", |board| {
+            board.create_task("task", None).unwrap();
+        });
+
+        let result = boards(
+            Some(BoardsCommand::Delete(DeleteBoardItem{
+                name: "work".to_owned(), force: false
+            })),
+            &mut settings, &mut storage, &mut writer
+        );
+        assert_eq!(result, Err(KanbenError::BoardNotEmpty("work".to_owned())));
+        assert!(storage.dropped().is_empty());
+
+        boards(
+            Some(BoardsCommand::Delete(DeleteBoardItem{
+                name: "work".to_owned(), force: true
+            })),
+            &mut settings, &mut storage, &mut writer
+        ).unwrap();
+        assert_eq!(storage.dropped(), vec!("work"));
+        assert!(settings.boards.is_empty());
+        assert_eq!(settings.default_board, None);
+    }
+
+    #[test]
+    fn the_main_board_cant_be_deleted() {
+        let mut writer = Cursor::new(vec!());
+        let mut settings = settings();
+
+        let result = boards(
+            Some(BoardsCommand::Delete(DeleteBoardItem{
+                name: "main".to_owned(), force: true
+            })),
+            &mut settings, &mut StorageMock::new(), &mut writer
+        );
+
+        assert_eq!(result, Err(KanbenError::MainBoard));
+    }
+
+    #[test]
+    fn it_sets_the_default_board() {
+        let mut writer = Cursor::new(vec!());
+        let mut settings = settings();
+        let mut storage = StorageMock::new();
+
+        boards(
+            Some(BoardsCommand::Default(item("work"))),
+            &mut settings, &mut storage, &mut writer
+        ).unwrap();
+        assert_eq!(settings.default_board, Some("work".to_owned()));
+
+        let result = boards(
+            Some(BoardsCommand::Default(item("garden"))),
+            &mut settings, &mut storage, &mut writer
+        );
+        assert_eq!(result, Err(KanbenError::BoardNotFound("garden".to_owned())));
+    }
+}
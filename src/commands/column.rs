@@ -0,0 +1,290 @@
+use std::io::Write;
+use crate::board::{BoardAccess, column_label};
+use crate::error::KanbenError;
+use crate::opts::{ColumnCommand, LimitItem};
+
+pub fn column<B: BoardAccess, W: Write>(
+    cmd: Option<ColumnCommand>,
+    board: &mut B,
+    writer: &mut W
+) -> Result<(), KanbenError> {
+    match cmd {
+        None => {
+            list_columns(board, writer);
+            Ok(())
+        },
+        Some(ColumnCommand::Add(c)) => board.add_column(&c.name),
+        Some(ColumnCommand::Remove(c)) => board.remove_column(&c.name),
+        Some(ColumnCommand::Rename(c)) => board.rename_column(
+            &c.name, &c.new_name
+        ),
+        Some(ColumnCommand::Reorder(c)) => board.reorder_column(
+            &c.name, c.position.saturating_sub(1)
+        ),
+        Some(ColumnCommand::Limit(l)) => set_limit(l, board),
+        Some(ColumnCommand::LimitMode(m)) => {
+            match m.mode.as_str() {
+                "strict" => set_strict_limits(true, board),
+                "soft" => set_strict_limits(false, board),
+                _ => Err(KanbenError::InvalidLimitMode(m.mode))
+            }
+        }
+    }
+}
+
+fn list_columns<B: BoardAccess, W: Write>(board: &B, writer: &mut W) {
+    let config = board.get_config();
+    let columns = config.columns.iter().map(|c| {
+        match config.limit(c) {
+            Some(l) => format!("{} (limit {})", c, l),
+            None => c.clone()
+        }
+    }).collect::<Vec<String>>().join("\n");
+    let _ = write!(writer, "{}\n", columns);
+}
+
+fn set_limit<B: BoardAccess>(
+    item: LimitItem,
+    board: &mut B
+) -> Result<(), KanbenError> {
+    let mut config = board.get_config();
+    let label = column_label(&item.name);
+    if item.limit == 0 {
+        config.limits.remove(&label);
+    } else {
+        config.limits.insert(label, item.limit);
+    }
+    board.save_config(config)
+}
+
+fn set_strict_limits<B: BoardAccess>(
+    strict: bool,
+    board: &mut B
+) -> Result<(), KanbenError> {
+    let mut config = board.get_config();
+    config.strict_limits = strict;
+    board.save_config(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opts::{
+        ColumnItem,
+        RenameColumnItem,
+        ReorderColumnItem,
+        LimitModeItem
+    };
+    use crate::test::StoreMock;
+    use crate::board::Board;
+    use crate::commands::standard_actions::complete_item;
+    use std::{str, io::Cursor};
+
+    #[test]
+    fn it_lists_the_default_columns() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        column(None, &mut board, &mut writer).unwrap();
+
+        let output = writer.get_ref();
+        assert_eq!(output, b"todo\ndoing\ndone\n");
+    }
+
+    #[test]
+    fn it_adds_renames_and_reorders_columns() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        column(
+            Some(ColumnCommand::Add(ColumnItem{
+                name: "Review".to_owned()
+            })),
+            &mut board,
+            &mut writer
+        ).unwrap();
+        column(
+            Some(ColumnCommand::Reorder(ReorderColumnItem{
+                name: "review".to_owned(),
+                position: 3
+            })),
+            &mut board,
+            &mut writer
+        ).unwrap();
+        column(
+            Some(ColumnCommand::Rename(RenameColumnItem{
+                name: "review".to_owned(),
+                new_name: "code review".to_owned()
+            })),
+            &mut board,
+            &mut writer
+        ).unwrap();
+        column(None, &mut board, &mut writer).unwrap();
+
+        let output = writer.get_ref();
+        let str_output = str::from_utf8(&output).unwrap();
+        assert_eq!(str_output, "todo\ndoing\ncode review\ndone\n");
+    }
+
+    #[test]
+    fn a_new_column_doesnt_become_the_done_column() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+        board.create_task("task", None).unwrap();
+
+        column(
+            Some(ColumnCommand::Add(ColumnItem{
+                name: "review".to_owned()
+            })),
+            &mut board,
+            &mut writer
+        ).unwrap();
+        complete_item("task".to_owned(), false, &mut board).unwrap();
+
+        assert_eq!(board.get("task").unwrap().column, "done");
+        assert_eq!(board.get_config().in_progress(), vec!("doing", "review"));
+    }
+
+    #[test]
+    fn it_outputs_a_message_when_a_column_cant_be_removed() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+        board.create_task("task", None).unwrap();
+
+        let result = column(
+            Some(ColumnCommand::Remove(ColumnItem{
+                name: "todo".to_owned()
+            })),
+            &mut board,
+            &mut writer
+        );
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Column 'todo' still has tasks in it."
+        );
+    }
+
+    #[test]
+    fn it_shows_limits_when_listing_columns() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        column(
+            Some(ColumnCommand::Limit(LimitItem{
+                name: "Doing".to_owned(),
+                limit: 3
+            })),
+            &mut board,
+            &mut writer
+        ).unwrap();
+        column(None, &mut board, &mut writer).unwrap();
+
+        let output = writer.get_ref();
+        assert_eq!(output, b"todo\ndoing (limit 3)\ndone\n");
+    }
+
+    #[test]
+    fn a_limit_of_zero_removes_the_limit() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        column(
+            Some(ColumnCommand::Limit(LimitItem{
+                name: "doing".to_owned(),
+                limit: 3
+            })),
+            &mut board,
+            &mut writer
+        ).unwrap();
+        column(
+            Some(ColumnCommand::Limit(LimitItem{
+                name: "doing".to_owned(),
+                limit: 0
+            })),
+            &mut board,
+            &mut writer
+        ).unwrap();
+
+        assert_eq!(board.get_config().limit("doing"), None);
+    }
+
+    #[test]
+    fn it_can_make_limits_strict() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        column(
+            Some(ColumnCommand::LimitMode(LimitModeItem{
+                mode: "strict".to_owned()
+            })),
+            &mut board,
+            &mut writer
+        ).unwrap();
+
+        assert!(board.get_config().strict_limits);
+    }
+}
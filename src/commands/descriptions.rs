@@ -0,0 +1,184 @@
+use std::io::Write;
+use crate::board::BoardAccess;
+use crate::editor::{Editor, file_key};
+use crate::error::KanbenError;
+use crate::opts::Task;
+
+// descriptions used to be named after their task's title. any
+// that still are get moved to where `file_key` says they belong,
+// and the task is pointed at the new path. a file that can't be
+// moved is left where it is, the old path keeps working
+pub fn migrate_descriptions<B: BoardAccess>(
+    board: &mut B,
+    editor: &mut dyn Editor
+) -> Result<usize, KanbenError> {
+    let mut moved = 0;
+    for task in board.get_all_tasks() {
+        let description = match &task.description {
+            Some(d) if d.trim() != "" && editor.owns(d) => d.clone(),
+            _ => continue
+        };
+        let key = file_key(&task);
+        if description == editor.path_for(&key) {
+            continue;
+        }
+
+        if let Ok(path) = editor.adopt(&description, &key) {
+            board.set_description(&task.name, &path)?;
+            moved += 1;
+        }
+    }
+    Ok(moved)
+}
+
+// once a task is gone its description goes in the trash, where
+// it stays until `kanben gc` clears it out. the task has already
+// been deleted, so a file that can't be moved is only a warning
+pub fn trash_description(
+    task: &Task,
+    editor: &mut dyn Editor,
+    writer: &mut dyn Write
+) {
+    let description = match &task.description {
+        Some(d) if d.trim() != "" && editor.owns(d) => d,
+        _ => return
+    };
+
+    if editor.trash(description, &file_key(task)).is_err() {
+        let _ = write!(
+            writer,
+            "Unable to move the description file to the trash, \
+            it's still at '{}'.\n",
+            description
+        );
+    }
+}
+
+// the other way round, a task back from the trash takes its
+// description with it. the task is already back, so a file that
+// can't be moved is only a warning
+pub fn bring_back_description<B: BoardAccess>(
+    task: &mut Task,
+    board: &mut B,
+    editor: &mut dyn Editor,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    let key = file_key(task);
+    let trashed = editor.trash_path_for(&key);
+    if !editor.trashed_files().contains(&trashed) {
+        return Ok(());
+    }
+
+    match editor.adopt(&trashed, &key) {
+        Ok(path) if task.description.as_ref() != Some(&path) => {
+            board.set_description(&task.name, &path)?;
+            task.description = Some(path);
+        },
+        Ok(_) => {},
+        Err(_) => {
+            let _ = write!(
+                writer,
+                "Unable to bring back the description file, \
+                it's still at '{}'.\n",
+                trashed
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opts::Times;
+    use crate::test::{BoardMock, EditorMock};
+    use std::{str, io::Cursor};
+
+    fn task(description: &str) -> Task {
+        Task{
+            name: "a/task".to_owned(),
+            column: "todo".to_owned(),
+            description: Some(description.to_owned()),
+            tags: None,
+            id: Some(3),
+            times: Times::default()
+        }
+    }
+
+    #[test]
+    fn title_named_files_are_moved_to_the_task_id() {
+        let mut board = BoardMock::new();
+        board.set_tasks(vec!(task("files/a/task")));
+        let mut editor = EditorMock::new();
+        editor.set_owned("files/a/task");
+        editor.return_from_adopt(Ok("files/3/description.md".to_owned()));
+
+        let moved = migrate_descriptions(&mut board, &mut editor).unwrap();
+
+        assert_eq!(moved, 1);
+        assert!(editor.adopt_called_with("files/a/task", "3"));
+        assert!(board.set_description_called_with(
+            "a/task", "files/3/description.md"
+        ));
+    }
+
+    #[test]
+    fn files_already_in_place_or_kept_elsewhere_are_left_alone() {
+        let mut board = BoardMock::new();
+        board.set_tasks(vec!(
+            task("files/3/description.md"),
+            Task{ name: "other".to_owned(), ..task("/notes/other.md") }
+        ));
+        let mut editor = EditorMock::new();
+        editor.set_owned("files/3/description.md");
+
+        let moved = migrate_descriptions(&mut board, &mut editor).unwrap();
+
+        assert_eq!(moved, 0);
+        assert!(!editor.adopt_called_with("files/3/description.md", "3"));
+        assert!(!editor.adopt_called_with("/notes/other.md", "3"));
+    }
+
+    #[test]
+    fn a_deleted_tasks_description_goes_in_the_trash() {
+        let mut writer = Cursor::new(vec!());
+        let mut editor = EditorMock::new();
+        editor.set_owned("files/3/description.md");
+
+        trash_description(
+            &task("files/3/description.md"), &mut editor, &mut writer
+        );
+
+        assert!(editor.trash_called_with("files/3/description.md", "3"));
+        assert!(writer.get_ref().is_empty());
+    }
+
+    #[test]
+    fn a_description_kept_elsewhere_is_left_alone() {
+        let mut writer = Cursor::new(vec!());
+        let mut editor = EditorMock::new();
+
+        trash_description(&task("/notes/task.md"), &mut editor, &mut writer);
+
+        assert!(!editor.trash_called());
+    }
+
+    #[test]
+    fn it_warns_when_the_description_cant_be_trashed() {
+        let mut writer = Cursor::new(vec!());
+        let mut editor = EditorMock::new();
+        editor.set_owned("files/3/description.md");
+        editor.return_from_trash(Err(KanbenError::Editor("busy".to_owned())));
+
+        trash_description(
+            &task("files/3/description.md"), &mut editor, &mut writer
+        );
+
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert_eq!(
+            output,
+            "Unable to move the description file to the trash, \
+            it's still at 'files/3/description.md'.\n"
+        );
+    }
+}
@@ -1,6 +1,12 @@
 use std::io::Write;
-use crate::board::BoardAccess;
-use crate::editor::Editor;
+use serde::{Serialize, Deserialize};
+use crate::board::{BoardAccess, column_label};
+use crate::editor::{Editor, file_key};
+use crate::file::Reader;
+use crate::opts::Task;
+use crate::store::body;
+use crate::error::KanbenError;
+use super::rename::rename_item;
 
 // if task has an associated file, open that
 // otherwise create one and open it
@@ -9,34 +15,243 @@ pub fn edit_item<B: BoardAccess>(
     key: String,
     board: &mut B,
     editor: &mut dyn Editor,
+    file_reader: &dyn Reader,
+    _writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    let mut task = board.get(&key)
+        .ok_or(KanbenError::TaskNotFound(key.clone()))?;
+
+    // only a description that's been changed goes in the history
+    if let Some(description) = &task.description {
+        if description.trim() != "" {
+            let before = file_reader.read(description);
+            editor.open(description)?;
+            if file_reader.read(description) != before {
+                board.described(&key)?;
+            }
+            return Ok(());
+        }
+    }
+
+    task.description = Some(editor.create(&file_key(&task))?);
+    board.update(&key, task)
+}
+
+// what `edit --full` puts above the description
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct Details {
+    title: String,
+    column: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    // shown so the task can be recognised, but can't be changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<u64>
+}
+
+// the task's details go in front matter above its description.
+// if they can't be read or the board won't accept them, the
+// editor is opened again with the problem at the top. saving it
+// without any changes gives up
+pub fn edit_full<B: BoardAccess>(
+    key: String,
+    board: &mut B,
+    editor: &mut dyn Editor,
+    file_reader: &dyn Reader,
     writer: &mut dyn Write
-) {
-    let task_result = board.get(&key);
+) -> Result<(), KanbenError> {
+    let task = board.get(&key)
+        .ok_or(KanbenError::TaskNotFound(key.clone()))?;
+    let description = match &task.description {
+        Some(d) if d.trim() != "" => file_reader.read(d)
+            .map(|text| body(&text).to_owned())
+            .unwrap_or_default(),
+        _ => String::new()
+    };
+    let original = write_details(&task, &description);
 
-    if task_result.is_none() {
-        write!(writer, "No item named '{}' found.\n", &key).unwrap();
-        return;
+    let mut text = original.clone();
+    loop {
+        let edited = editor.edit_text("task", &text)?;
+        if edited == original {
+            let _ = write!(writer, "Nothing was changed.\n");
+            return Ok(());
+        }
+
+        // the board can have changed while the editor was open
+        let task = board.get(&key)
+            .ok_or(KanbenError::TaskNotFound(key.clone()))?;
+        let result = read_details(&edited).and_then(|(details, body)| {
+            apply_details(
+                &task, details, body, &description, board, editor, file_reader
+            )
+        });
+        match result {
+            Err(e) if can_retry(&e) && edited != text => {
+                let _ = write!(writer, "{}\n", e);
+                let problem = e.to_string().replace('\n', " ");
+                text = format!("# {}\n{}", problem, without_comments(&edited));
+            },
+            Err(e) => return Err(e),
+            Ok(title) => {
+                let _ = write!(writer, "Updated '{}'.\n", title);
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn write_details(task: &Task, description: &str) -> String {
+    let details = Details{
+        title: task.name.clone(),
+        column: task.column.clone(),
+        tags: task.tags.clone().unwrap_or_default(),
+        id: task.id
+    };
+    let yaml = serde_yaml::to_string(&details).unwrap_or_default();
+    let yaml = yaml.trim_start_matches("---\n").trim_end();
+    format!("---\n{}\n---\n{}", yaml, description)
+}
+
+// any problem from an earlier attempt is left above the front matter
+fn without_comments(text: &str) -> &str {
+    let mut rest = text;
+    while rest.starts_with('#') {
+        rest = match rest.find('\n') {
+            Some(i) => &rest[i + 1..],
+            None => ""
+        };
     }
+    rest
+}
+
+fn read_details(text: &str) -> Result<(Details, &str), KanbenError> {
+    let rest = without_comments(text).trim_start();
+    let rest = match rest.strip_prefix("---\n") {
+        Some(r) => r,
+        None => return Err(KanbenError::InvalidDetails(
+            "they need to start with a '---' line".to_owned()
+        ))
+    };
 
-    let mut task = task_result.unwrap();
-    if task.description.is_some() {
-        let description = task.description.unwrap();
-        if description.trim() != "".to_string() {
-            editor.open(&description);
-            return;
+    let mut end = None;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            end = Some((offset, offset + line.len()));
+            break;
         }
+        offset += line.len();
     }
+    let (yaml_end, body_start) = end.ok_or(KanbenError::InvalidDetails(
+        "they need to end with a '---' line".to_owned()
+    ))?;
+
+    let details = serde_yaml::from_str::<Details>(&rest[..yaml_end])
+        .map_err(|e| KanbenError::InvalidDetails(e.to_string()))?;
+    Ok((details, &rest[body_start..]))
+}
+
+// the whole edit is one change, so nothing is changed if the
+// board refuses any of it. the description is saved last, once
+// the board has taken everything else
+fn apply_details<B: BoardAccess>(
+    task: &Task,
+    details: Details,
+    new_body: &str,
+    description: &str,
+    board: &mut B,
+    editor: &mut dyn Editor,
+    file_reader: &dyn Reader
+) -> Result<String, KanbenError> {
+    let title = details.title.trim().to_owned();
+    if title.is_empty() {
+        return Err(KanbenError::InvalidTitle);
+    }
+    if details.id.is_some() && details.id != task.id {
+        return Err(KanbenError::InvalidDetails(
+            "the id can't be changed".to_owned()
+        ));
+    }
+    if title != task.name && board.get(&title).is_some() {
+        return Err(KanbenError::TaskExists(title));
+    }
+
+    // columns are matched however they're typed, as everywhere else
+    let column = column_label(&details.column);
+    let tags = if details.tags.is_empty() {
+        None
+    } else {
+        Some(details.tags)
+    };
+    let name = format!("edit '{}'", task.name);
+    board.together(&name, |board| {
+        if column != task.column || tags != task.tags {
+            let mut updated = task.clone();
+            updated.column = column;
+            updated.tags = tags;
+            board.update(&task.name, updated)?;
+        }
+
+        if title != task.name {
+            rename_item(task.name.clone(), title.clone(), board)?;
+        }
+
+        if new_body != description {
+            save_description(&title, new_body, board, editor, file_reader)?;
+        }
+        Ok(title)
+    })
+}
+
+fn save_description<B: BoardAccess>(
+    title: &str,
+    new_body: &str,
+    board: &mut B,
+    editor: &mut dyn Editor,
+    file_reader: &dyn Reader
+) -> Result<(), KanbenError> {
+    let mut updated = board.get(title)
+        .ok_or(KanbenError::TaskNotFound(title.to_owned()))?;
+    let path = updated.description.clone().filter(|d| d.trim() != "");
+    // a markdown board keeps the description in the task's own
+    // file, so whatever is above it is kept as it is now
+    let text = match path.as_deref().and_then(|p| file_reader.read(p)) {
+        Some(current) => {
+            let kept = current.len() - body(&current).len();
+            format!("{}{}", &current[..kept], new_body)
+        },
+        None => new_body.to_owned()
+    };
+    let saved = editor.save(path.as_deref(), &file_key(&updated), &text)?;
+    if Some(&saved) != path.as_ref() {
+        updated.description = Some(saved);
+        board.update(title, updated)
+    } else {
+        board.described(title)
+    }
+}
 
-    let result = editor.create(&key);
-    task.description = Some(result.unwrap().clone());
-    board.update(&key, task);
+// problems with what was typed, rather than with kanben itself
+fn can_retry(e: &KanbenError) -> bool {
+    matches!(
+        e,
+        KanbenError::InvalidDetails(_)
+            | KanbenError::InvalidTitle
+            | KanbenError::TaskExists(_)
+            | KanbenError::ColumnNotFound(_)
+            | KanbenError::IllegalMove{ .. }
+            | KanbenError::WipLimitReached{ .. }
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::opts::{Task, Column};
-    use crate::test::{BoardMock, EditorMock};
+    use crate::opts::Task;
+    use crate::board::{Transition, EventKind};
+    use crate::test::{BoardMock, EditorMock, ReaderMock, Stores};
     use std::{str, io::Cursor};
 
     #[test]
@@ -47,12 +262,14 @@ mod tests {
         let mut editor = EditorMock::new();
         let mut writer = Cursor::new(vec!());
 
-        let mut task = get_task(&key, Column::Todo);
+        let mut task = get_task(&key, "todo");
         task.description = Some(path_to_file.clone());
 
         board.set(&key, task);
         
-        edit_item(key, &mut board, &mut editor, &mut writer);
+        edit_item(
+            key, &mut board, &mut editor, &ReaderMock::new(), &mut writer
+        ).unwrap();
 
         assert!(editor.open_called_with(&path_to_file));
     }
@@ -63,10 +280,16 @@ mod tests {
         let mut board = BoardMock::new();
         let mut editor = EditorMock::new();
         let mut writer = Cursor::new(vec!());
-        let task = get_task(&key, Column::Todo);
+        let task = get_task(&key, "todo");
 
         board.set(&key, task);
-        edit_item(key.clone(), &mut board, &mut editor, &mut writer);
+        edit_item(
+            key.clone(),
+            &mut board,
+            &mut editor,
+            &ReaderMock::new(),
+            &mut writer
+        ).unwrap();
 
         assert!(editor.create_called_with(&key));
     }
@@ -78,12 +301,15 @@ mod tests {
         let mut editor = EditorMock::new();
         let mut writer = Cursor::new(vec!());
         
-        edit_item(key.clone(), &mut board, &mut editor, &mut writer);
-        let output = writer.get_ref();
-        let str_output = str::from_utf8(&output).unwrap();
-        let expected_output = "No item named 'test' found.\n";
+        let result = edit_item(
+            key.clone(),
+            &mut board,
+            &mut editor,
+            &ReaderMock::new(),
+            &mut writer
+        );
 
-        assert_eq!(str_output, expected_output);
+        assert_eq!(result, Err(KanbenError::TaskNotFound(key)));
     }
 
     #[test]
@@ -93,9 +319,9 @@ mod tests {
         let mut board = BoardMock::new();
         let mut editor = EditorMock::new();
         let mut writer = Cursor::new(vec!());
-        let task = get_task(&key, Column::Todo);
+        let task = get_task(&key, "todo");
 
-        let mut new_task = get_task(&key, Column::Todo);
+        let mut new_task = get_task(&key, "todo");
         new_task.description = Some(filepath.clone());
 
         board.set(&key, task);
@@ -105,8 +331,9 @@ mod tests {
             key.clone(),
             &mut board,
             &mut editor,
+            &ReaderMock::new(),
             &mut writer
-        );
+        ).unwrap();
 
         assert!(board.update_called_with(&key, &new_task));
     }
@@ -117,7 +344,7 @@ mod tests {
         let mut board = BoardMock::new();
         let mut editor = EditorMock::new();
         let mut writer = Cursor::new(vec!());
-        let mut task = get_task(&key, Column::Todo);
+        let mut task = get_task(&key, "todo");
         task.description = Some("".to_owned());
 
         board.set(&key, task);
@@ -125,18 +352,321 @@ mod tests {
             key.clone(),
             &mut board,
             &mut editor,
+            &ReaderMock::new(),
             &mut writer
-        );
+        ).unwrap();
 
         assert!(editor.create_called_with(&key));
     }
 
-    fn get_task(key: &str, column: Column) -> Task {
+    #[test]
+    fn a_description_opened_but_not_changed_isnt_in_the_history() {
+        let key = "test".to_owned();
+        let mut board = BoardMock::new();
+        let mut task = get_task(&key, "todo");
+        task.description = Some("files/test".to_owned());
+        board.set(&key, task);
+        let mut reader = ReaderMock::new();
+        reader.return_from_read("notes");
+        let mut writer = Cursor::new(vec!());
+
+        edit_item(
+            key.clone(), &mut board, &mut EditorMock::new(), &reader, &mut writer
+        ).unwrap();
+
+        assert!(!board.described_called_with(&key));
+    }
+
+    // a single task, 1 task, in todo
+    fn stores() -> Stores {
+        let mut stores = Stores::new();
+        stores.board().create_task("task", None).unwrap();
+        stores
+    }
+
+    fn details(title: &str, column: &str, tags: &str, body: &str) -> String {
+        format!(
+            "---\ntitle: {}\ncolumn: {}\ntags: {}\nid: 1\n---\n{}",
+            title, column, tags, body
+        )
+    }
+
+    fn edit_full_with(
+        stores: &mut Stores,
+        editor: &mut EditorMock,
+        reader: &ReaderMock
+    ) -> (Result<(), KanbenError>, String) {
+        let mut writer = Cursor::new(vec!());
+        let result = edit_full(
+            "task".to_owned(),
+            &mut stores.board(),
+            editor,
+            reader,
+            &mut writer
+        );
+        (result, str::from_utf8(writer.get_ref()).unwrap().to_owned())
+    }
+
+    #[test]
+    fn full_edits_show_the_details_above_the_description() {
+        let mut stores = stores();
+        let mut editor = EditorMock::new();
+        let mut reader = ReaderMock::new();
+        reader.return_from_read("notes\n");
+        {
+            let mut board = stores.board();
+            let mut task = board.get("task").unwrap();
+            task.description = Some("files/task".to_owned());
+            task.tags = Some(vec!("a".to_owned()));
+            board.update("task", task).unwrap();
+        }
+
+        let (result, output) = edit_full_with(&mut stores, &mut editor, &reader);
+
+        assert_eq!(result, Ok(()));
+        assert!(editor.edit_text_called_with(
+            "task",
+            "---\ntitle: task\ncolumn: todo\ntags:\n  - a\nid: 1\n---\nnotes\n"
+        ));
+        assert_eq!(output, "Nothing was changed.\n");
+    }
+
+    #[test]
+    fn full_edits_change_the_column_tags_and_title() {
+        let mut stores = stores();
+        let mut editor = EditorMock::new();
+        editor.return_from_edit_text(
+            Ok(details("renamed", "doing", "[b]", ""))
+        );
+
+        let (result, output) = edit_full_with(
+            &mut stores, &mut editor, &ReaderMock::new()
+        );
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(output, "Updated 'renamed'.\n");
+        assert!(!editor.save_called());
+        let board = stores.board();
+        let task = board.get("renamed").unwrap();
+        assert_eq!(task.column, "doing");
+        assert_eq!(task.tags, Some(vec!("b".to_owned())));
+        assert_eq!(task.id, Some(1));
+    }
+
+    #[test]
+    fn full_edits_take_the_column_however_its_typed() {
+        let mut stores = stores();
+        let mut editor = EditorMock::new();
+        editor.return_from_edit_text(Ok(details("task", "Doing", "[]", "")));
+
+        let (result, output) = edit_full_with(
+            &mut stores, &mut editor, &ReaderMock::new()
+        );
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(output, "Updated 'task'.\n");
+        assert_eq!(editor.edit_text_call_count(), 1);
+        assert_eq!(stores.board().get("task").unwrap().column, "doing");
+    }
+
+    #[test]
+    fn a_description_saved_in_place_goes_in_the_history() {
+        let mut stores = stores();
+        {
+            let mut board = stores.board();
+            let mut task = board.get("task").unwrap();
+            task.description = Some("saved-filepath".to_owned());
+            board.update("task", task).unwrap();
+        }
+        let mut editor = EditorMock::new();
+        editor.return_from_edit_text(
+            Ok(details("task", "todo", "[]", "new notes\n"))
+        );
+
+        edit_full_with(&mut stores, &mut editor, &ReaderMock::new())
+            .0.unwrap();
+
+        let history = stores.board().history("task");
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[2].kind, EventKind::Described);
+    }
+
+    #[test]
+    fn full_edits_save_a_changed_description() {
+        let mut stores = stores();
+        let mut editor = EditorMock::new();
+        editor.return_from_edit_text(
+            Ok(details("task", "todo", "[]", "new notes\n"))
+        );
+
+        let (result, _) = edit_full_with(
+            &mut stores, &mut editor, &ReaderMock::new()
+        );
+
+        assert_eq!(result, Ok(()));
+        assert!(editor.save_called_with(None, "1", "new notes\n"));
+        let task = stores.board().get("task").unwrap();
+        assert_eq!(task.description, Some("saved-filepath".to_owned()));
+    }
+
+    #[test]
+    fn full_edits_keep_a_task_files_front_matter() {
+        let mut stores = stores();
+        {
+            let mut board = stores.board();
+            let mut task = board.get("task").unwrap();
+            task.description = Some("tasks/task.md".to_owned());
+            board.update("task", task).unwrap();
+        }
+        let mut editor = EditorMock::new();
+        editor.return_from_edit_text(
+            Ok(details("task", "todo", "[]", "new notes\n"))
+        );
+        let mut reader = ReaderMock::new();
+        reader.return_from_read(
+            "---\nname: task\ncolumn: todo\n---\nold notes\n"
+        );
+
+        let (result, _) = edit_full_with(&mut stores, &mut editor, &reader);
+
+        assert_eq!(result, Ok(()));
+        assert!(editor.save_called_with(
+            Some("tasks/task.md"),
+            "1",
+            "---\nname: task\ncolumn: todo\n---\nnew notes\n"
+        ));
+    }
+
+    #[test]
+    fn full_edits_reopen_the_editor_when_the_details_cant_be_read() {
+        let mut stores = stores();
+        let mut editor = EditorMock::new();
+        editor.return_from_edit_text(Ok("title: task\n".to_owned()));
+        editor.return_from_edit_text(Ok(details("task", "doing", "[]", "")));
+
+        let (result, output) = edit_full_with(
+            &mut stores, &mut editor, &ReaderMock::new()
+        );
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(editor.edit_text_call_count(), 2);
+        assert!(editor.edit_text_called_with(
+            "task",
+            "# Unable to read the task's details: \
+            they need to start with a '---' line\ntitle: task\n"
+        ));
+        assert_eq!(
+            output,
+            "Unable to read the task's details: \
+            they need to start with a '---' line\nUpdated 'task'.\n"
+        );
+        assert_eq!(stores.board().get("task").unwrap().column, "doing");
+    }
+
+    #[test]
+    fn full_edits_give_up_when_a_problem_is_saved_unchanged() {
+        let mut stores = stores();
+        {
+            let mut board = stores.board();
+            let mut config = board.get_config();
+            config.workflow = Some(vec!(Transition::new("todo", "doing")));
+            board.save_config(config).unwrap();
+        }
+        let mut editor = EditorMock::new();
+        let edited = details("other", "done", "[]", "");
+        editor.return_from_edit_text(Ok(edited.clone()));
+        editor.return_from_edit_text(Ok(format!(
+            "# The workflow doesn't allow moving from 'todo' to 'done'. \
+            `start` and `complete` take --force to move it anyway.\n{}",
+            edited
+        )));
+
+        let (result, _) = edit_full_with(
+            &mut stores, &mut editor, &ReaderMock::new()
+        );
+
+        assert_eq!(result, Err(KanbenError::IllegalMove{
+            from: "todo".to_owned(),
+            to: "done".to_owned()
+        }));
+        let board = stores.board();
+        assert_eq!(board.get("other"), None);
+        assert_eq!(board.get("task").unwrap().column, "todo");
+    }
+
+    #[test]
+    fn full_edits_keep_the_id() {
+        let mut stores = stores();
+        let mut editor = EditorMock::new();
+        let edited = "---\ntitle: task\ncolumn: todo\nid: 7\n---\n";
+        editor.return_from_edit_text(Ok(edited.to_owned()));
+
+        let (result, _) = edit_full_with(
+            &mut stores, &mut editor, &ReaderMock::new()
+        );
+
+        assert_eq!(
+            result,
+            Err(KanbenError::InvalidDetails(
+                "the id can't be changed".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn a_full_edit_is_undone_in_one_go() {
+        let mut stores = stores();
+        let mut editor = EditorMock::new();
+        editor.return_from_edit_text(
+            Ok(details("renamed", "doing", "[b]", ""))
+        );
+        let journaled = stores.board().journal().len();
+
+        let (result, _) = edit_full_with(
+            &mut stores, &mut editor, &ReaderMock::new()
+        );
+
+        assert_eq!(result, Ok(()));
+        let mut board = stores.board();
+        assert_eq!(board.journal().len(), journaled + 1);
+        board.undo().unwrap();
+        let task = board.get("task").unwrap();
+        assert_eq!(task.column, "todo");
+        assert_eq!(task.tags, None);
+        assert_eq!(board.get("renamed"), None);
+    }
+
+    #[test]
+    fn a_full_edit_that_fails_part_way_changes_nothing() {
+        let mut stores = stores();
+        {
+            let mut board = stores.board();
+            let mut task = board.get("task").unwrap();
+            task.tags = Some(vec!("a".to_owned()));
+            board.update("task", task).unwrap();
+        }
+        // the move goes through, then renaming fails
+        stores.tags.fail_writes();
+        let mut editor = EditorMock::new();
+        editor.return_from_edit_text(
+            Ok(details("renamed", "doing", "[a]", ""))
+        );
+
+        let (result, _) = edit_full_with(
+            &mut stores, &mut editor, &ReaderMock::new()
+        );
+
+        assert!(result.is_err());
+        let board = stores.board();
+        assert_eq!(board.get("task").unwrap().column, "todo");
+        assert_eq!(board.get("renamed"), None);
+    }
+
+    fn get_task(key: &str, column: &str) -> Task {
         Task {
             name: key.to_owned(),
-            column,
-            description: None,
-            tags: None
+            column: column.to_owned(),
+            ..Task::default()
         }
     }
 }
@@ -0,0 +1,123 @@
+use std::io::Write;
+use crate::board::BoardAccess;
+use crate::error::KanbenError;
+
+// prints every problem found. without --repair any problem is
+// an error, so scripts can use fsck as a health check
+pub fn fsck<B: BoardAccess, W: Write>(
+    repair: bool,
+    board: &mut B,
+    writer: &mut W
+) -> Result<(), KanbenError> {
+    let problems = board.check();
+    if problems.is_empty() {
+        let _ = write!(writer, "No problems found.\n");
+        return Ok(());
+    }
+
+    for problem in problems.iter() {
+        let _ = write!(writer, "{}\n", problem);
+    }
+
+    if !repair {
+        return Err(KanbenError::BoardInconsistent(problems.len()));
+    }
+
+    let repaired = board.repair()?;
+    let _ = write!(writer, "Repaired {} problem(s).\n", repaired);
+
+    let remaining = board.check();
+    if remaining.is_empty() {
+        return Ok(());
+    }
+
+    let _ = write!(writer, "These need to be fixed by hand:\n");
+    for problem in remaining.iter() {
+        let _ = write!(writer, "{}\n", problem);
+    }
+    Err(KanbenError::BoardInconsistent(remaining.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Store;
+    use crate::test::StoreMock;
+    use crate::board::Board;
+    use std::{str, io::Cursor};
+
+    #[test]
+    fn it_reports_a_healthy_board() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+        board.create_task("task", None).unwrap();
+
+        fsck(false, &mut board, &mut writer).unwrap();
+
+        let output = writer.get_ref();
+        assert_eq!(output, b"No problems found.\n");
+    }
+
+    #[test]
+    fn it_lists_problems_without_fixing_them() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        col_store.bulk_insert(vec!(("todo", vec!("gone".to_owned()))));
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        let result = fsck(false, &mut board, &mut writer);
+
+        let output = writer.get_ref();
+        let str_output = str::from_utf8(&output).unwrap();
+        assert_eq!(
+            str_output,
+            "Column 'todo' lists 'gone', which doesn't exist.\n"
+        );
+        assert_eq!(result, Err(KanbenError::BoardInconsistent(1)));
+        assert_eq!(col_store.get("todo").unwrap(), vec!("gone"));
+    }
+
+    #[test]
+    fn it_repairs_problems_when_asked() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        col_store.bulk_insert(vec!(("todo", vec!("gone".to_owned()))));
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        fsck(true, &mut board, &mut writer).unwrap();
+
+        let output = writer.get_ref();
+        let str_output = str::from_utf8(&output).unwrap();
+        assert_eq!(
+            str_output,
+            "Column 'todo' lists 'gone', which doesn't exist.\n\
+            Repaired 1 problem(s).\n"
+        );
+        assert_eq!(col_store.get("todo").unwrap(), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,255 @@
+use std::io::Write;
+use std::collections::HashSet;
+use crate::board::BoardAccess;
+use crate::editor::{Editor, file_key};
+use crate::error::KanbenError;
+use crate::file::Reader;
+use crate::opts::Task;
+use crate::prompt::Prompt;
+
+// description files no task uses any more, the trash, and tasks
+// pointing at a description that's gone. everything is listed
+// first, and nothing is removed without --purge and a yes. the
+// files of tasks that can still be restored are left for
+// `kanben trash empty`
+pub fn gc<B: BoardAccess>(
+    purge: bool,
+    board: &mut B,
+    editor: &mut dyn Editor,
+    file_reader: &dyn Reader,
+    prompt: &mut dyn Prompt,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    let tasks = board.get_all_tasks();
+    let deleted = board.trashed();
+    // a deleted task whose file couldn't be trashed still points
+    // at it where it was
+    let used = tasks.iter().chain(deleted.iter().map(|t| &t.task))
+        .filter_map(|t| t.description.clone())
+        .collect::<HashSet<String>>();
+    let unused = editor.stored_files().into_iter()
+        .filter(|f| !used.contains(f))
+        .collect::<Vec<String>>();
+    let restorable = deleted.iter()
+        .map(|t| editor.trash_path_for(&file_key(&t.task)))
+        .collect::<HashSet<String>>();
+    let trashed = editor.trashed_files().into_iter()
+        .filter(|f| !restorable.contains(f))
+        .collect::<Vec<String>>();
+    let missing = tasks.into_iter().filter(|t| match &t.description {
+        Some(d) if d.trim() != "" => file_reader.read(d).is_none(),
+        _ => false
+    }).collect::<Vec<Task>>();
+
+    if unused.is_empty() && trashed.is_empty() && missing.is_empty() {
+        let _ = write!(writer, "Nothing to clean up.\n");
+        return Ok(());
+    }
+
+    list(writer, "Description files no task uses:", &unused);
+    list(writer, "In the trash:", &trashed);
+    let missing_labels = missing.iter().map(|t| format!(
+        "{} ({})", t.label(), t.description.clone().unwrap_or_default()
+    )).collect::<Vec<String>>();
+    list(writer, "Tasks whose description file is gone:", &missing_labels);
+
+    if !purge {
+        let _ = write!(writer, "Run `kanben gc --purge` to remove them.\n");
+        return Ok(());
+    }
+
+    let _ = write!(writer, "Purge these? [y/N] ");
+    let _ = writer.flush();
+    if !prompt.confirm() {
+        let _ = write!(writer, "Nothing was removed.\n");
+        return Ok(());
+    }
+
+    let mut removed = 0;
+    for file in unused.iter().chain(trashed.iter()) {
+        match editor.purge(file) {
+            Ok(_) => removed += 1,
+            Err(e) => {
+                let _ = write!(writer, "Unable to remove '{}': {}\n", file, e);
+            }
+        }
+    }
+    let cleared = missing.len();
+    for mut task in missing {
+        task.description = None;
+        board.update(&task.name.clone(), task)?;
+    }
+    let _ = write!(
+        writer,
+        "Removed {} file(s) and {} missing description(s).\n",
+        removed, cleared
+    );
+    Ok(())
+}
+
+fn list(writer: &mut dyn Write, heading: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+
+    let lines = items.iter()
+        .map(|i| format!("  {}\n", i))
+        .collect::<String>();
+    let _ = write!(writer, "{}\n{}", heading, lines);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opts::Times;
+    use crate::board::Trashed;
+    use crate::test::{BoardMock, EditorMock, ReaderMock, PromptMock};
+    use std::{str, io::Cursor};
+
+    fn task(name: &str, id: u64, description: Option<&str>) -> Task {
+        Task{
+            name: name.to_owned(),
+            column: "todo".to_owned(),
+            description: description.map(|d| d.to_owned()),
+            tags: None,
+            id: Some(id),
+            times: Times::default()
+        }
+    }
+
+    // 1 has its description, 2's has gone and files/3 is left over
+    fn setup() -> (BoardMock, EditorMock, ReaderMock) {
+        let mut board = BoardMock::new();
+        board.set_tasks(vec!(
+            task("kept", 1, Some("files/1/description.md")),
+            task("lost", 2, Some("files/2/description.md")),
+            task("plain", 4, None)
+        ));
+        let mut editor = EditorMock::new();
+        editor.set_stored(vec!(
+            "files/1/description.md", "files/3/description.md"
+        ));
+        editor.set_trashed(vec!("files/.trash/5/description.md"));
+        let mut reader = ReaderMock::new();
+        reader.return_from_read_when("files/1/description.md", "notes");
+        (board, editor, reader)
+    }
+
+    fn run(
+        purge: bool,
+        prompt: &mut PromptMock
+    ) -> (BoardMock, EditorMock, String) {
+        let (mut board, mut editor, reader) = setup();
+        let mut writer = Cursor::new(vec!());
+        gc(purge, &mut board, &mut editor, &reader, prompt, &mut writer)
+            .unwrap();
+        let output = str::from_utf8(writer.get_ref()).unwrap().to_owned();
+        (board, editor, output)
+    }
+
+    #[test]
+    fn it_only_lists_what_it_would_remove() {
+        let mut prompt = PromptMock::new();
+
+        let (_, editor, output) = run(false, &mut prompt);
+
+        assert_eq!(
+            output,
+            "Description files no task uses:\n  files/3/description.md\n\
+            In the trash:\n  files/.trash/5/description.md\n\
+            Tasks whose description file is gone:\n  \
+            2 lost (files/2/description.md)\n\
+            Run `kanben gc --purge` to remove them.\n"
+        );
+        assert!(!prompt.was_asked());
+        assert!(editor.purged().is_empty());
+    }
+
+    #[test]
+    fn purging_needs_a_yes() {
+        let mut prompt = PromptMock::new();
+
+        let (board, editor, output) = run(true, &mut prompt);
+
+        assert!(prompt.was_asked());
+        assert!(output.ends_with("Purge these? [y/N] Nothing was removed.\n"));
+        assert!(editor.purged().is_empty());
+        assert!(!board.update_called_with("lost", &task("lost", 2, None)));
+    }
+
+    #[test]
+    fn it_purges_once_confirmed() {
+        let mut prompt = PromptMock::new();
+        prompt.answer_yes();
+
+        let (board, editor, output) = run(true, &mut prompt);
+
+        assert_eq!(
+            editor.purged(),
+            vec!("files/3/description.md", "files/.trash/5/description.md")
+        );
+        assert!(board.update_called_with("lost", &task("lost", 2, None)));
+        assert!(output.ends_with(
+            "Removed 2 file(s) and 1 missing description(s).\n"
+        ));
+    }
+
+    #[test]
+    fn files_of_tasks_that_can_be_restored_are_kept() {
+        let (mut board, mut editor, reader) = setup();
+        board.set_trashed(vec!(Trashed{
+            task: task("deleted", 5, Some("files/5/description.md")),
+            position: 0,
+            deleted: 0
+        }));
+        let mut writer = Cursor::new(vec!());
+
+        gc(
+            false, &mut board, &mut editor, &reader,
+            &mut PromptMock::new(), &mut writer
+        ).unwrap();
+
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert!(!output.contains("files/.trash/5/description.md"));
+    }
+
+    #[test]
+    fn files_left_behind_by_deleted_tasks_are_kept() {
+        let (mut board, mut editor, reader) = setup();
+        board.set_trashed(vec!(Trashed{
+            task: task("deleted", 3, Some("files/3/description.md")),
+            position: 0,
+            deleted: 0
+        }));
+        let mut prompt = PromptMock::new();
+        prompt.answer_yes();
+        let mut writer = Cursor::new(vec!());
+
+        gc(
+            true, &mut board, &mut editor, &reader,
+            &mut prompt, &mut writer
+        ).unwrap();
+
+        assert!(editor.purged().iter().all(|f| f != "files/3/description.md"));
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert!(!output.contains("files/3/description.md"));
+    }
+
+    #[test]
+    fn it_says_when_theres_nothing_to_do() {
+        let mut board = BoardMock::new();
+        let mut writer = Cursor::new(vec!());
+
+        gc(
+            true,
+            &mut board,
+            &mut EditorMock::new(),
+            &ReaderMock::new(),
+            &mut PromptMock::new(),
+            &mut writer
+        ).unwrap();
+
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert_eq!(output, "Nothing to clean up.\n");
+    }
+}
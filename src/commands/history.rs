@@ -0,0 +1,192 @@
+use std::io::Write;
+use crate::board::{BoardAccess, Event, EventKind};
+use crate::clock::{date, parse_age};
+use crate::error::KanbenError;
+
+// everything that's happened to one task, oldest first
+pub fn task_history<B: BoardAccess>(
+    key: String,
+    board: &B,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    if board.get(&key).is_none() {
+        return Err(KanbenError::TaskNotFound(key));
+    }
+
+    let events = board.history(&key);
+    if events.is_empty() {
+        let _ = write!(writer, "No history recorded for '{}'.\n", key);
+        return Ok(());
+    }
+
+    let lines = events.iter().map(|e| format!(
+        "{}  {}{}\n", date(e.at), describe(&e.kind), by(&e.actor)
+    )).collect::<String>();
+    let _ = write!(writer, "{}", lines);
+    Ok(())
+}
+
+// everything that's happened on the board, oldest first
+pub fn history<B: BoardAccess>(
+    since: Option<String>,
+    now: u64,
+    board: &B,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    let age = match since {
+        Some(a) => Some(parse_age(&a)?),
+        None => None
+    };
+    let events = board.all_history().into_iter().filter(|e| match age {
+        Some(a) => now.saturating_sub(e.at) <= a,
+        None => true
+    }).collect::<Vec<Event>>();
+
+    if events.is_empty() {
+        let _ = write!(writer, "No history recorded.\n");
+        return Ok(());
+    }
+
+    let lines = events.iter().map(|e| format!(
+        "{}  {}: {}{}\n",
+        date(e.at), e.task, describe(&e.kind), by(&e.actor)
+    )).collect::<String>();
+    let _ = write!(writer, "{}", lines);
+    Ok(())
+}
+
+fn by(actor: &Option<String>) -> String {
+    match actor {
+        Some(a) => format!(" by {}", a),
+        None => String::new()
+    }
+}
+
+fn describe(kind: &EventKind) -> String {
+    match kind {
+        EventKind::Created{ column } => format!("created in {}", column),
+        EventKind::Moved{ from, to } => format!("moved from {} to {}", from, to),
+        EventKind::Tagged{ tag } => format!("tagged #{}", tag),
+        EventKind::Untagged{ tag } => format!("untagged #{}", tag),
+        EventKind::Prioritised{ column, from, to } => format!(
+            "moved from position {} to {} in {}", from, to, column
+        ),
+        EventKind::Described => "description edited".to_owned(),
+        EventKind::Renamed{ from } => format!("renamed from '{}'", from),
+        EventKind::Deleted => "deleted".to_owned(),
+        EventKind::Restored => "restored from the trash".to_owned(),
+        EventKind::Removed => "removed".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opts::{Task, Times};
+    use crate::test::BoardMock;
+    use std::{str, io::Cursor};
+
+    const DAY: u64 = 24 * 60 * 60;
+    // 2024-03-09 16:00
+    const NOW: u64 = 1_710_000_000;
+
+    fn event(task: &str, at: u64, kind: EventKind) -> Event {
+        Event{
+            at,
+            key: task.to_owned(),
+            task: task.to_owned(),
+            kind,
+            actor: None
+        }
+    }
+
+    fn board() -> BoardMock {
+        let mut board = BoardMock::new();
+        board.set("a", Task{
+            name: "a".to_owned(),
+            column: "doing".to_owned(),
+            description: None,
+            tags: None,
+            id: Some(1),
+            times: Times::default()
+        });
+        board.set_history(vec!(
+            event("a", NOW - 10 * DAY, EventKind::Created{
+                column: "todo".to_owned()
+            }),
+            event("b", NOW - 9 * DAY, EventKind::Deleted),
+            Event{
+                actor: Some("ben".to_owned()),
+                ..event("a", NOW - DAY, EventKind::Moved{
+                    from: "todo".to_owned(),
+                    to: "doing".to_owned()
+                })
+            },
+            event("a", NOW, EventKind::Prioritised{
+                column: "doing".to_owned(),
+                from: 3,
+                to: 1
+            })
+        ));
+        board
+    }
+
+    #[test]
+    fn it_shows_a_tasks_history() {
+        let mut writer = Cursor::new(vec!());
+
+        task_history("a".to_owned(), &board(), &mut writer).unwrap();
+
+        assert_eq!(
+            str::from_utf8(writer.get_ref()).unwrap(),
+            "2024-02-28 16:00  created in todo\n\
+            2024-03-08 16:00  moved from todo to doing by ben\n\
+            2024-03-09 16:00  moved from position 3 to 1 in doing\n"
+        );
+    }
+
+    #[test]
+    fn a_task_has_to_exist() {
+        let mut writer = Cursor::new(vec!());
+
+        let result = task_history("b".to_owned(), &board(), &mut writer);
+
+        assert_eq!(result, Err(KanbenError::TaskNotFound("b".to_owned())));
+    }
+
+    #[test]
+    fn it_shows_the_whole_boards_history() {
+        let mut writer = Cursor::new(vec!());
+
+        history(None, NOW, &board(), &mut writer).unwrap();
+
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert_eq!(output.lines().count(), 4);
+        assert!(output.contains("2024-02-29 16:00  b: deleted\n"));
+    }
+
+    #[test]
+    fn it_can_show_only_recent_history() {
+        let mut writer = Cursor::new(vec!());
+
+        history(Some("7d".to_owned()), NOW, &board(), &mut writer).unwrap();
+
+        assert_eq!(
+            str::from_utf8(writer.get_ref()).unwrap(),
+            "2024-03-08 16:00  a: moved from todo to doing by ben\n\
+            2024-03-09 16:00  a: moved from position 3 to 1 in doing\n"
+        );
+    }
+
+    #[test]
+    fn it_says_when_nothing_has_been_recorded() {
+        let mut writer = Cursor::new(vec!());
+
+        history(None, NOW, &BoardMock::new(), &mut writer).unwrap();
+
+        assert_eq!(
+            str::from_utf8(writer.get_ref()).unwrap(),
+            "No history recorded.\n"
+        );
+    }
+}
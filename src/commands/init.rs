@@ -0,0 +1,90 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use crate::location::BOARD_DIR;
+use crate::settings::{Settings, Backend};
+use crate::error::KanbenError;
+
+// starts a board in the given directory. any command run in it,
+// or below it, uses this board instead of the home one. the
+// settings file is what marks the folder as a board
+pub fn init<W: Write>(
+    dir: &Path,
+    store: Option<Backend>,
+    writer: &mut W
+) -> Result<(), KanbenError> {
+    let board_dir = dir.join(BOARD_DIR);
+    let board_path = board_dir.to_string_lossy().into_owned();
+    if board_dir.exists() {
+        return Err(KanbenError::BoardExists(board_path));
+    }
+
+    fs::create_dir_all(&board_dir)
+        .map_err(|e| KanbenError::Store(e.to_string()))?;
+
+    let settings = Settings{
+        store: store.unwrap_or_default(),
+        ..Settings::default()
+    };
+    settings.save(&board_path)?;
+
+    let _ = write!(writer, "Created a board in '{}'.\n", board_path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use std::{str, io::Cursor};
+
+    fn scratch(name: &str) -> std::path::PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH).unwrap()
+            .as_nanos();
+        temp_dir().join(format!("kanben-init-{}-{}", name, nanos))
+    }
+
+    #[test]
+    fn it_creates_a_board_in_the_directory() {
+        let mut writer = Cursor::new(vec!());
+        let dir = scratch("new");
+
+        init(&dir, None, &mut writer).unwrap();
+
+        let board = dir.join(".kanben");
+        assert_eq!(
+            fs::read_to_string(board.join("settings.yml")).unwrap(),
+            "store: kv\n"
+        );
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert_eq!(
+            output,
+            format!("Created a board in '{}'.\n", board.display())
+        );
+    }
+
+    #[test]
+    fn it_can_choose_the_store() {
+        let mut writer = Cursor::new(vec!());
+        let dir = scratch("store");
+
+        init(&dir, Some(Backend::Markdown), &mut writer).unwrap();
+
+        let board = dir.join(".kanben");
+        let settings = Settings::load(&board.to_string_lossy()).unwrap();
+        assert_eq!(settings.store, Backend::Markdown);
+    }
+
+    #[test]
+    fn it_wont_replace_an_existing_board() {
+        let mut writer = Cursor::new(vec!());
+        let dir = scratch("existing");
+        fs::create_dir_all(dir.join(".kanben")).unwrap();
+
+        let result = init(&dir, None, &mut writer);
+
+        assert!(matches!(result, Err(KanbenError::BoardExists(_))));
+    }
+}
@@ -1,65 +1,124 @@
 use std::io::Write;
 use math::round;
-use crate::board::BoardAccess;
+use colored::*;
+use crate::board::{BoardAccess, BoardConfig};
+use crate::clock::{ago, ago_short};
 use crate::opts::Task;
 
 pub fn list_tasks<B: BoardAccess, W: Write>(
-    tag: Option<String>, board: &B, writer: &mut W
+    tag: Option<String>, now: u64, board: &B, writer: &mut W
 ) {
-    writer.write(b"TODO:\t\t\tDOING:\t\t\tDONE:\n").unwrap();
-    let todo = get_task_labels(
-        board.get_column("todo", tag.clone())
-    );
-    let doing = get_task_labels(
-        board.get_column("doing", tag.clone())
-    );
-    let done = get_task_labels(
-        board.get_column("done", tag.clone())
-    );
+    let config = board.get_config();
+    let columns = config.columns.clone();
+    write_headers(&config, board, writer);
 
-    let col_max = find_col_max(vec![
-        todo.len(),
-        doing.len(),
-        done.len()
-    ]);
+    let tasks = columns.iter().map(|c| {
+        get_task_labels(board.get_column(c, tag.clone()), now)
+    }).collect::<Vec<Vec<String>>>();
+
+    let col_max = find_col_max(
+        tasks.iter().map(|t| t.len()).collect()
+    );
 
     for n in 0..col_max {
-        let next_todo = todo.get(n);
-        let next_doing = doing.get(n);
-        let next_done = done.get(n);
-        write!(writer, "{}", col_text(next_todo)).unwrap();
-        write!(writer, "{}", col_text(next_doing)).unwrap();
-        if next_done.is_some() {
-            write!(writer, "{}", next_done.unwrap()).unwrap();
-        }
-        write!(writer, "\n").unwrap();
+        let row = tasks.iter()
+            .map(|t| t.get(n))
+            .collect::<Vec<Option<&String>>>();
+        write_row(&row, writer);
     }
     write!(writer, "\n").unwrap();
 }
 
 pub fn list_all<B: BoardAccess, W: Write>(
-    tag: Option<String>, board: &B, writer: &mut W
+    tag: Option<String>, now: u64, board: &B, writer: &mut W
 ) {
-    let todo = get_task_labels(
-        board.get_column("todo", tag.clone())
-    );
-    let doing = get_task_labels(
-        board.get_column("doing", tag.clone())
-    );
-    let done = get_task_labels(
-        board.get_column("done", tag.clone())
-    );
-
-    let tasks = vec!(
-        todo,
-        doing,
-        done
-    ).into_iter().flatten().collect::<Vec<String>>().join("\n");
+    let tasks = board.get_config().columns.iter().flat_map(|c| {
+        board.get_column(c, tag.clone())
+    }).map(|t| match latest(&t) {
+        Some((event, at)) => {
+            format!("{} ({} {})", t.label(), event, ago(at, now))
+        },
+        None => t.label()
+    }).collect::<Vec<String>>().join("\n");
     write!(writer, "{}\n", tasks).unwrap();
 }
 
-fn get_task_labels(list: Vec<Task>) -> Vec<String> {
-    list.iter().map(|t: &Task| t.name.clone()).collect()
+// the latest of a task's times, which is how long it's been where
+// it is, and what happened then. a task sent back a column was
+// moved there, whenever it was started. tasks from before times
+// were kept have none
+fn latest(task: &Task) -> Option<(&'static str, u64)> {
+    let times = &task.times;
+    let at = [times.moved, times.completed, times.started, times.created]
+        .iter()
+        .flatten()
+        .max()
+        .copied()?;
+    let event = if times.completed == Some(at) {
+        "completed"
+    } else if times.started == Some(at) {
+        "started"
+    } else if times.created == Some(at) {
+        "created"
+    } else {
+        "moved"
+    };
+    Some((event, at))
+}
+
+// columns with a limit show how full they are, and any
+// column over its limit is highlighted
+fn write_headers<B: BoardAccess, W: Write>(
+    config: &BoardConfig, board: &B, writer: &mut W
+) {
+    let last = config.columns.len().saturating_sub(1);
+    for (i, column) in config.columns.iter().enumerate() {
+        let label = column.to_uppercase();
+        let (header, over_limit) = match config.limit(column) {
+            Some(limit) => {
+                let count = board.get_column(column, None).len();
+                (format!("{} ({}/{}):", label, count, limit), count > limit)
+            },
+            None => (format!("{}:", label), false)
+        };
+
+        let padding = if i < last {
+            col_text(Some(&header))[header.len()..].to_owned()
+        } else {
+            String::new()
+        };
+
+        if over_limit {
+            write!(writer, "{}{}", header.red(), padding).unwrap();
+        } else {
+            write!(writer, "{}{}", header, padding).unwrap();
+        }
+    }
+    write!(writer, "\n").unwrap();
+}
+
+// every column is padded out to a fixed width apart from
+// the last one, which runs to the end of the line
+fn write_row<W: Write>(
+    labels: &Vec<Option<&String>>, writer: &mut W
+) {
+    let last = labels.len().saturating_sub(1);
+    for (i, label) in labels.iter().enumerate() {
+        if i < last {
+            write!(writer, "{}", col_text(*label)).unwrap();
+        } else if label.is_some() {
+            write!(writer, "{}", label.unwrap()).unwrap();
+        }
+    }
+    write!(writer, "\n").unwrap();
+}
+
+// the board is narrow, so ages are kept short there
+fn get_task_labels(list: Vec<Task>, now: u64) -> Vec<String> {
+    list.iter().map(|t: &Task| match latest(t) {
+        Some((_, at)) => format!("{} ({})", t.label(), ago_short(at, now)),
+        None => t.label()
+    }).collect()
 }
 
 fn col_text(label: Option<&String>) -> String {
@@ -82,28 +141,35 @@ fn col_text(label: Option<&String>) -> String {
 }
 
 fn find_col_max(cols: Vec<usize>) -> usize {
-    *cols.iter().max().unwrap()
+    *cols.iter().max().unwrap_or(&0)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{test::StoreMock, board::Board, opts::Column};
+    use crate::{test::{StoreMock, Stores}, board::Board};
+    use crate::opts::Times;
+    use crate::store::Store;
     use std::{str, io::Cursor};
 
+    const DAY: u64 = 24 * 60 * 60;
+    const NOW: u64 = 1_710_000_000;
+
     #[test]
     fn it_outputs_the_kanban_headers_when_there_are_no_tasks() {
         let mut writer = Cursor::new(vec!());
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
-        );
+            &mut tag_store,
+            &mut config_store
+        ).with_clock(|| NOW);
 
-        list_tasks(None, &mut board, &mut writer);
+        list_tasks(None, NOW, &mut board, &mut writer);
 
         let output = writer.get_ref();
         assert_eq!(output, b"TODO:\t\t\tDOING:\t\t\tDONE:\n\n");
@@ -115,30 +181,32 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
-        );
+            &mut tag_store,
+            &mut config_store
+        ).with_clock(|| NOW);
 
-        board.create_task("task1", None);
-        board.create_task("task2", None);
-        board.create_task("task3", None);
-        board.create_task("task4", None);
-        board.create_task("task5", None);
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        board.create_task("task3", None).unwrap();
+        board.create_task("task4", None).unwrap();
+        board.create_task("task5", None).unwrap();
 
-        board.update("task4", get_task("task4", Column::Done));
-        board.update("task1", get_task("task1", Column::Doing));
-        board.update("task3", get_task("task3", Column::Doing));
+        board.update("task4", get_task("task4", "done")).unwrap();
+        board.update("task1", get_task("task1", "doing")).unwrap();
+        board.update("task3", get_task("task3", "doing")).unwrap();
 
 
-        list_tasks(None, &mut board, &mut writer);
+        list_tasks(None, NOW, &mut board, &mut writer);
 
         let output = writer.get_ref();
         let str_output = str::from_utf8(&output).unwrap();
         let expected_output = "TODO:\t\t\tDOING:\t\t\tDONE:
-task2\t\t\ttask1\t\t\ttask4
-task5\t\t\ttask3\t\t\t\n\n";
+2 task2 (now)\t\t1 task1 (now)\t\t4 task4 (now)
+5 task5 (now)\t\t3 task3 (now)\t\t\n\n";
         assert_eq!(str_output, expected_output);
     }
 
@@ -148,22 +216,24 @@ task5\t\t\ttask3\t\t\t\n\n";
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
-        );
+            &mut tag_store,
+            &mut config_store
+        ).with_clock(|| NOW);
 
 
-        board.create_task("task1", None);
-        board.update("task1", get_task("task1", Column::Doing));
+        board.create_task("task1", None).unwrap();
+        board.update("task1", get_task("task1", "doing")).unwrap();
 
-        list_tasks(None, &mut board, &mut writer);
+        list_tasks(None, NOW, &mut board, &mut writer);
 
         let output = writer.get_ref();
         let str_output = str::from_utf8(&output).unwrap();
         let expected_output = "TODO:\t\t\tDOING:\t\t\tDONE:
-\t\t\ttask1\t\t\t\n\n";
+\t\t\t1 task1 (now)\t\t\n\n";
         assert_eq!(str_output, expected_output);
     }
 
@@ -173,39 +243,131 @@ task5\t\t\ttask3\t\t\t\n\n";
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
-        );
-        board.create_task("task1-very-long", None);
-        board.create_task("task2-very-long", None);
-        board.create_task("task3", None);
-        board.create_task("task4", None);
-        board.create_task("task5", None);
+            &mut tag_store,
+            &mut config_store
+        ).with_clock(|| NOW);
+        board.create_task("task1-very-long", None).unwrap();
+        board.create_task("task2-very-long", None).unwrap();
+        board.create_task("task3", None).unwrap();
+        board.create_task("task4", None).unwrap();
+        board.create_task("task5", None).unwrap();
 
         board.update("task1-very-long",
-            get_task("task1-very-long", Column::Doing)
-        );
-        board.update("task3", get_task("task3", Column::Doing));
-        board.update("task4", get_task("task4", Column::Done));
+            get_task("task1-very-long", "doing")
+        ).unwrap();
+        board.update("task3", get_task("task3", "doing")).unwrap();
+        board.update("task4", get_task("task4", "done")).unwrap();
 
-        list_tasks(None, &mut board, &mut writer);
+        list_tasks(None, NOW, &mut board, &mut writer);
 
         let output = writer.get_ref();
         let str_output = str::from_utf8(&output).unwrap();
         let expected_output = "TODO:\t\t\tDOING:\t\t\tDONE:
-task2-very-long\t\ttask1-very-long\t\ttask4
-task5\t\t\ttask3\t\t\t\n\n";
+2 task2-very-long (now)\t1 task1-very-long (now)\t4 task4 (now)
+5 task5 (now)\t\t3 task3 (now)\t\t\n\n";
+        assert_eq!(str_output, expected_output);
+    }
+
+    #[test]
+    fn it_lists_the_columns_the_board_defines() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        ).with_clock(|| NOW);
+        board.add_column("review").unwrap();
+        board.reorder_column("review", 2).unwrap();
+
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        board.update("task2", get_task("task2", "review")).unwrap();
+
+        list_tasks(None, NOW, &mut board, &mut writer);
+
+        let output = writer.get_ref();
+        let str_output = str::from_utf8(&output).unwrap();
+        let expected_output = "TODO:\t\t\tDOING:\t\t\tREVIEW:\t\t\tDONE:
+1 task1 (now)\t\t\t\t\t2 task2 (now)\t\t\n\n";
+        assert_eq!(str_output, expected_output);
+    }
+
+    #[test]
+    fn it_shows_how_full_limited_columns_are() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        ).with_clock(|| NOW);
+        let mut config = board.get_config();
+        config.limits.insert("doing".to_owned(), 3);
+        board.save_config(config).unwrap();
+
+        board.create_task("task1", None).unwrap();
+        board.update("task1", get_task("task1", "doing")).unwrap();
+
+        list_tasks(None, NOW, &mut board, &mut writer);
+
+        let output = writer.get_ref();
+        let str_output = str::from_utf8(&output).unwrap();
+        let expected_output = "TODO:\t\t\tDOING (1/3):\t\tDONE:
+\t\t\t1 task1 (now)\t\t\n\n";
         assert_eq!(str_output, expected_output);
     }
 
-    fn get_task(name: &str, column: Column) -> Task {
+    #[test]
+    fn it_highlights_columns_over_their_limit() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        ).with_clock(|| NOW);
+        let mut config = board.get_config();
+        config.limits.insert("done".to_owned(), 1);
+        board.save_config(config).unwrap();
+
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        board.update("task1", get_task("task1", "done")).unwrap();
+        board.update("task2", get_task("task2", "done")).unwrap();
+
+        list_tasks(None, NOW, &mut board, &mut writer);
+
+        let output = writer.get_ref();
+        let str_output = str::from_utf8(&output).unwrap();
+        let expected_header = format!(
+            "TODO:\t\t\tDOING:\t\t\t{}\n",
+            "DONE (2/1):".red()
+        );
+        assert!(str_output.starts_with(&expected_header));
+    }
+
+    fn get_task(name: &str, column: &str) -> Task {
         Task{
             name: name.to_owned(),
-            column,
-            description: None,
-            tags: None
+            column: column.to_owned(),
+            ..Task::default()
         }
     }
 
@@ -215,21 +377,100 @@ task5\t\t\ttask3\t\t\t\n\n";
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
-        );
+            &mut tag_store,
+            &mut config_store
+        ).with_clock(|| NOW);
 
 
-        board.create_task("task1", None);
+        board.create_task("task1", None).unwrap();
 
-        list_all(None, &mut board, &mut writer);
+        list_all(None, NOW, &mut board, &mut writer);
 
         let output = writer.get_ref();
         let str_output = str::from_utf8(&output).unwrap();
-        let expected_output = "task1\n";
+        let expected_output = "1 task1 (created just now)\n";
         assert_eq!(str_output, expected_output);
     }
 
+    // one task from before times were kept, one started and one done
+    fn timed_stores() -> Stores {
+        let mut stores = Stores::new();
+        let mut old = get_task("old", "todo");
+        old.id = Some(1);
+        let mut started = get_task("started", "doing");
+        started.times = Times{
+            created: Some(NOW - 10 * DAY),
+            started: Some(NOW - 3 * DAY),
+            ..Times::default()
+        };
+        let mut done = get_task("done", "done");
+        done.times = Times{
+            started: Some(NOW - 3 * DAY),
+            completed: Some(NOW - 2 * 60 * 60),
+            ..Times::default()
+        };
+        for task in [old, started, done].iter() {
+            stores.columns.set(&task.column, vec!(task.name.clone())).unwrap();
+            stores.tasks.set(&task.name, task.clone()).unwrap();
+        }
+        stores
+    }
+
+    #[test]
+    fn the_list_shows_how_long_ago_tasks_started() {
+        let mut writer = Cursor::new(vec!());
+        let mut stores = timed_stores();
+
+        list_tasks(None, NOW, &stores.board(), &mut writer);
+
+        assert_eq!(
+            str::from_utf8(writer.get_ref()).unwrap(),
+            "TODO:\t\t\tDOING:\t\t\tDONE:\n\
+            1 old\t\t\tstarted (3d)\t\tdone (2h)\n\n"
+        );
+    }
+
+    #[test]
+    fn a_task_sent_back_says_when_it_moved() {
+        let mut stores = Stores::new();
+        let mut back = get_task("back", "todo");
+        back.times = Times{
+            created: Some(NOW - 10 * DAY),
+            started: Some(NOW - 3 * DAY),
+            moved: Some(NOW - DAY),
+            ..Times::default()
+        };
+        stores.columns.set("todo", vec!("back".to_owned())).unwrap();
+        stores.tasks.set("back", back).unwrap();
+        let mut list = Cursor::new(vec!());
+        let mut all = Cursor::new(vec!());
+
+        list_tasks(None, NOW, &stores.board(), &mut list);
+        list_all(None, NOW, &stores.board(), &mut all);
+
+        assert!(str::from_utf8(list.get_ref()).unwrap().contains("back (1d)"));
+        assert_eq!(
+            str::from_utf8(all.get_ref()).unwrap(),
+            "back (moved 1 day ago)\n"
+        );
+    }
+
+    #[test]
+    fn the_task_list_says_how_long_ago_each_task_started() {
+        let mut writer = Cursor::new(vec!());
+        let mut stores = timed_stores();
+
+        list_all(None, NOW, &stores.board(), &mut writer);
+
+        assert_eq!(
+            str::from_utf8(writer.get_ref()).unwrap(),
+            "1 old\nstarted (started 3 days ago)\n\
+            done (completed 2 hours ago)\n"
+        );
+    }
+
 }
@@ -0,0 +1,175 @@
+use std::io::Write;
+use crate::store::Store;
+use crate::board::{BoardConfig, Trashed, Entry, Event};
+use crate::opts::Task;
+use crate::error::KanbenError;
+
+// copies every record across in one go, so a failure leaves
+// that part of the new store empty rather than half filled
+fn copy<T, A: Store<T> + ?Sized, B: Store<T> + ?Sized>(
+    from: &A,
+    to: &mut B
+) -> Result<usize, KanbenError> {
+    to.begin();
+    let mut count = 0;
+    for key in from.keys() {
+        if let Some(value) = from.get(&key) {
+            if let Err(e) = to.set(&key, value) {
+                to.rollback();
+                return Err(e);
+            }
+            count += 1;
+        }
+    }
+    to.commit()?;
+    Ok(count)
+}
+
+// a board's trash, undo journal and history
+pub type Records<'a> = (
+    &'a dyn Store<Trashed>, &'a dyn Store<Entry>, &'a dyn Store<Event>
+);
+pub type RecordsMut<'a> = (
+    &'a mut dyn Store<Trashed>,
+    &'a mut dyn Store<Entry>,
+    &'a mut dyn Store<Event>
+);
+
+// copies a kv board into an sqlite one, along with everything
+// kept about it. the kv store is left as it was, so nothing is
+// lost until settings.yml points at the new store
+pub fn migrate_store<
+    S: Store<Task>, C: Store<Vec<String>>, K: Store<BoardConfig>,
+    S2: Store<Task>, C2: Store<Vec<String>>, K2: Store<BoardConfig>,
+    W: Write
+>(
+    from: (&S, &C, &C, &K),
+    to: (&mut S2, &mut C2, &mut C2, &mut K2),
+    from_records: Records,
+    to_records: RecordsMut,
+    writer: &mut W
+) -> Result<(), KanbenError> {
+    if !to.0.keys().is_empty() {
+        return Err(KanbenError::Store(
+            "the SQLite store already has tasks in it".to_owned()
+        ));
+    }
+
+    let tasks = copy(from.0, to.0)?;
+    copy(from.1, to.1)?;
+    copy(from.2, to.2)?;
+    copy(from.3, to.3)?;
+    copy(from_records.0, to_records.0)?;
+    copy(from_records.1, to_records.1)?;
+    copy(from_records.2, to_records.2)?;
+
+    let _ = write!(
+        writer,
+        "Copied {} task(s) to the SQLite store.\n\
+        Set `store: sqlite` in settings.yml to start using it.\n",
+        tasks
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opts::Times;
+    use crate::board::BoardAccess;
+    use crate::test::{StoreMock, Stores};
+    use std::{str, io::Cursor};
+
+    fn task(name: &str) -> Task {
+        Task{
+            name: name.to_owned(),
+            column: "todo".to_owned(),
+            description: None,
+            tags: None,
+            id: Some(1),
+            times: Times::default()
+        }
+    }
+
+    #[test]
+    fn it_copies_every_bucket() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        store.bulk_insert(vec!(("a", task("a")), ("b", task("b"))));
+        let mut col_store = StoreMock::new();
+        col_store.bulk_insert(vec!(("todo", vec!("a".to_owned(), "b".to_owned()))));
+        let tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        config_store.bulk_insert(vec!(("board", BoardConfig::default())));
+        let mut new_store = StoreMock::new();
+        let mut new_col_store = StoreMock::new();
+        let mut new_tag_store = StoreMock::new();
+        let mut new_config_store = StoreMock::new();
+
+        migrate_store(
+            (&store, &col_store, &tag_store, &config_store),
+            (&mut new_store, &mut new_col_store, &mut new_tag_store, &mut new_config_store),
+            (&StoreMock::new(), &StoreMock::new(), &StoreMock::new()),
+            (&mut StoreMock::new(), &mut StoreMock::new(), &mut StoreMock::new()),
+            &mut writer
+        ).unwrap();
+
+        assert_eq!(new_store.keys(), vec!("a", "b"));
+        assert_eq!(new_col_store.get("todo").unwrap(), vec!("a", "b"));
+        assert!(new_config_store.get("board").is_some());
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert!(output.starts_with("Copied 2 task(s) to the SQLite store.\n"));
+    }
+
+    #[test]
+    fn it_wont_copy_over_an_existing_board() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        store.bulk_insert(vec!(("a", task("a"))));
+        let col_store = StoreMock::new();
+        let tag_store = StoreMock::new();
+        let config_store = StoreMock::new();
+        let mut new_store = StoreMock::new();
+        new_store.bulk_insert(vec!(("b", task("b"))));
+        let mut new_col_store = StoreMock::new();
+        let mut new_tag_store = StoreMock::new();
+        let mut new_config_store = StoreMock::new();
+
+        let result = migrate_store(
+            (&store, &col_store, &tag_store, &config_store),
+            (&mut new_store, &mut new_col_store, &mut new_tag_store, &mut new_config_store),
+            (&StoreMock::new(), &StoreMock::new(), &StoreMock::new()),
+            (&mut StoreMock::new(), &mut StoreMock::new(), &mut StoreMock::new()),
+            &mut writer
+        );
+
+        assert!(result.is_err());
+        assert_eq!(new_store.keys(), vec!("b"));
+    }
+
+    #[test]
+    fn the_trash_journal_and_history_come_too() {
+        let mut writer = Cursor::new(vec!());
+        let mut old = Stores::new();
+        old.board().create_task("a", None).unwrap();
+        old.board().create_task("b", None).unwrap();
+        old.board().delete("b").unwrap();
+        let mut new = Stores::new();
+
+        migrate_store(
+            (&old.tasks, &old.columns, &old.tags, &old.config),
+            (&mut new.tasks, &mut new.columns, &mut new.tags, &mut new.config),
+            (&old.trash, &old.journal, &old.history),
+            (&mut new.trash, &mut new.journal, &mut new.history),
+            &mut writer
+        ).unwrap();
+
+        let old_board = old.board();
+        let mut new_board = new.board();
+        assert_eq!(new_board.trashed(), old_board.trashed());
+        assert_eq!(new_board.journal(), old_board.journal());
+        assert_eq!(new_board.all_history(), old_board.all_history());
+        new_board.undo().unwrap();
+        assert!(new_board.get("b").is_some());
+    }
+}
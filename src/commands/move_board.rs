@@ -0,0 +1,232 @@
+use std::io::Write;
+use crate::board::BoardAccess;
+use crate::editor::{Editor, file_key};
+use crate::error::KanbenError;
+use crate::opts::MoveBoardItem;
+use super::{resolve, warn_over_limit};
+
+// the task is added to the target board before it's taken off
+// this one, so a failure part way leaves it where it was. its
+// description file follows it if this board's editor made it,
+// filed under the id the other board gave it
+pub fn move_board<S: BoardAccess, T: BoardAccess>(
+    item: MoveBoardItem,
+    source: &mut S,
+    target: &mut T,
+    source_editor: &dyn Editor,
+    target_editor: &mut dyn Editor,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    let key = resolve(item.title, source);
+    let task = source.get(&key)
+        .ok_or(KanbenError::TaskNotFound(key.clone()))?;
+
+    let column = target.insert_task(task.clone(), item.top)?;
+    if let Err(e) = source.remove(&key) {
+        let _ = target.remove(&key);
+        return Err(e);
+    }
+    warn_over_limit(target, writer);
+
+    let _ = write!(writer, "Moved '{}' to the '{}' board.\n", key, item.board);
+    if column != task.column {
+        let _ = write!(
+            writer,
+            "The '{}' board can't take it into '{}', so it's in '{}'.\n",
+            item.board, task.column, column
+        );
+    }
+
+    let description = match &task.description {
+        Some(d) if d.trim() != "" && source_editor.owns(d) => d.clone(),
+        _ => return Ok(())
+    };
+
+    let moved = target.get(&key)
+        .ok_or(KanbenError::TaskNotFound(key.clone()))?;
+    match target_editor.adopt(&description, &file_key(&moved)) {
+        Ok(path) => target.set_description(&key, &path),
+        Err(_) => {
+            let _ = write!(
+                writer,
+                "Unable to move the description file, it's still at '{}'.\n",
+                description
+            );
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardConfig, CONFIG_KEY};
+    use crate::opts::{Task, Times};
+    use crate::store::Store;
+    use crate::test::{BoardMock, EditorMock, Stores};
+    use std::{str, io::Cursor};
+
+    fn task(description: Option<&str>) -> Task {
+        Task{
+            name: "task".to_owned(),
+            column: "doing".to_owned(),
+            description: description.map(|d| d.to_owned()),
+            tags: Some(vec!("tag".to_owned())),
+            id: Some(4),
+            times: Times::default()
+        }
+    }
+
+    fn item(top: bool) -> MoveBoardItem {
+        MoveBoardItem{
+            title: "task".to_owned(),
+            board: "work".to_owned(),
+            top
+        }
+    }
+
+    #[test]
+    fn it_moves_a_task_to_the_other_board() {
+        let mut writer = Cursor::new(vec!());
+        let mut source = BoardMock::new();
+        source.set("task", task(None));
+        let mut target = BoardMock::new();
+        let source_editor = EditorMock::new();
+        let mut target_editor = EditorMock::new();
+
+        move_board(
+            item(true),
+            &mut source, &mut target,
+            &source_editor, &mut target_editor,
+            &mut writer
+        ).unwrap();
+
+        assert!(target.insert_called_with(&task(None), true));
+        assert!(source.remove_called_with("task"));
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert_eq!(output, "Moved 'task' to the 'work' board.\n");
+    }
+
+    #[test]
+    fn it_leaves_the_task_if_the_other_board_has_one_by_that_name() {
+        let mut writer = Cursor::new(vec!());
+        let mut source = BoardMock::new();
+        source.set("task", task(None));
+        let mut target = BoardMock::new();
+        target.set("task", task(None));
+        let source_editor = EditorMock::new();
+        let mut target_editor = EditorMock::new();
+
+        let result = move_board(
+            item(false),
+            &mut source, &mut target,
+            &source_editor, &mut target_editor,
+            &mut writer
+        );
+
+        assert_eq!(result, Err(KanbenError::TaskExists("task".to_owned())));
+        assert!(!source.remove_called_with("task"));
+    }
+
+    #[test]
+    fn the_description_file_moves_with_the_task() {
+        let mut writer = Cursor::new(vec!());
+        let mut source = BoardMock::new();
+        source.set("task", task(Some("/files/task")));
+        let mut target = BoardMock::new();
+        let mut source_editor = EditorMock::new();
+        source_editor.set_owned("/files/task");
+        let mut target_editor = EditorMock::new();
+
+        move_board(
+            item(false),
+            &mut source, &mut target,
+            &source_editor, &mut target_editor,
+            &mut writer
+        ).unwrap();
+
+        assert!(target_editor.adopt_called_with("/files/task", "4"));
+        assert!(target.set_description_called_with(
+            "task", "adopted-filepath"
+        ));
+    }
+
+    #[test]
+    fn a_description_that_cant_be_moved_is_left_behind() {
+        let mut writer = Cursor::new(vec!());
+        let mut source = BoardMock::new();
+        source.set("task", task(Some("/files/task")));
+        let mut target = BoardMock::new();
+        let mut source_editor = EditorMock::new();
+        source_editor.set_owned("/files/task");
+        let mut target_editor = EditorMock::new();
+        target_editor.return_from_adopt(
+            Err(KanbenError::Editor("exists".to_owned()))
+        );
+
+        move_board(
+            item(false),
+            &mut source, &mut target,
+            &source_editor, &mut target_editor,
+            &mut writer
+        ).unwrap();
+
+        assert!(source.remove_called_with("task"));
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert!(output.ends_with(
+            "Unable to move the description file, it's still at '/files/task'.\n"
+        ));
+    }
+
+    #[test]
+    fn a_description_kept_elsewhere_is_left_alone() {
+        let mut writer = Cursor::new(vec!());
+        let mut source = BoardMock::new();
+        source.set("task", task(Some("/notes/task.md")));
+        let mut target = BoardMock::new();
+        let source_editor = EditorMock::new();
+        let mut target_editor = EditorMock::new();
+
+        move_board(
+            item(false),
+            &mut source, &mut target,
+            &source_editor, &mut target_editor,
+            &mut writer
+        ).unwrap();
+
+        assert!(!target_editor.adopt_called_with("/notes/task.md", "4"));
+        assert!(target.insert_called_with(&task(Some("/notes/task.md")), false));
+    }
+
+    #[test]
+    fn a_board_without_the_tasks_column_puts_it_in_its_backlog() {
+        let mut writer = Cursor::new(vec!());
+        let mut home = Stores::new();
+        home.board().create_task("task", None).unwrap();
+        home.board().update("task", task(None)).unwrap();
+        let mut work = Stores::new();
+        let config = BoardConfig{
+            columns: vec!(
+                "backlog".to_owned(), "review".to_owned(), "shipped".to_owned()
+            ),
+            ..BoardConfig::default()
+        };
+        work.config.set(CONFIG_KEY, config).unwrap();
+        let source_editor = EditorMock::new();
+        let mut target_editor = EditorMock::new();
+
+        move_board(
+            item(false),
+            &mut home.board(), &mut work.board(),
+            &source_editor, &mut target_editor,
+            &mut writer
+        ).unwrap();
+
+        assert!(home.board().get("task").is_none());
+        assert_eq!(work.board().get("task").unwrap().column, "backlog");
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert!(output.ends_with(
+            "The 'work' board can't take it into 'doing', so it's in 'backlog'.\n"
+        ));
+    }
+}
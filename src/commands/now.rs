@@ -1,5 +1,7 @@
 use std::io::Write;
 use crate::board::BoardAccess;
+use crate::error::KanbenError;
+use super::{BoardStorage, BoardVisitor};
 
 pub fn now<B: BoardAccess>(
     board: &B,
@@ -14,12 +16,7 @@ pub fn now<B: BoardAccess>(
         "\n"
     };
 
-    let tasks = board.get_column("doing", tag)
-        .iter()
-        .map(|item| {
-            item.name.clone()
-        }).collect::<Vec<String>>()
-        .join(delimiter);
+    let tasks = in_progress(board, &tag).join(delimiter);
 
     if tasks != "" {
         let end_char = if no_newlines {
@@ -31,11 +28,75 @@ pub fn now<B: BoardAccess>(
     }
 }
 
+// each board's in-progress tasks under its name, so a status
+// bar can show them on one line. boards with nothing in progress
+// are left out
+pub fn now_all<S: BoardStorage>(
+    names: Vec<String>,
+    storage: &S,
+    writer: &mut dyn Write,
+    no_newlines: bool,
+    tag: Option<String>
+) -> Result<(), KanbenError> {
+    let mut boards = vec!();
+    for name in names {
+        let mut visitor = InProgress{ tag: tag.clone(), tasks: vec!() };
+        storage.with_board(&name, &mut visitor)?;
+        if !visitor.tasks.is_empty() {
+            boards.push((name, visitor.tasks));
+        }
+    }
+
+    if boards.is_empty() {
+        return Ok(());
+    }
+
+    let output = if no_newlines {
+        boards.iter()
+            .map(|(name, tasks)| format!("{}: {}", name, tasks.join(",")))
+            .collect::<Vec<String>>()
+            .join(" | ")
+    } else {
+        boards.iter()
+            .map(|(name, tasks)| format!("{}:\n{}\n", name, tasks.join("\n")))
+            .collect::<String>()
+    };
+    write!(writer, "{}", output).unwrap();
+    Ok(())
+}
+
+pub fn in_progress<B: BoardAccess>(
+    board: &B,
+    tag: &Option<String>
+) -> Vec<String> {
+    board.get_config().in_progress()
+        .iter()
+        .flat_map(|column| board.get_column(column, tag.clone()))
+        .map(|item| {
+            item.label()
+        }).collect()
+}
+
+struct InProgress {
+    tag: Option<String>,
+    tasks: Vec<String>
+}
+
+impl BoardVisitor for InProgress {
+    fn visit<B: BoardAccess>(
+        &mut self,
+        board: &mut B
+    ) -> Result<(), KanbenError> {
+        self.tasks = in_progress(board, &self.tag);
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::opts::{Task, Column};
-    use crate::test::StoreMock;
+    use crate::opts::Task;
+    use crate::test::{StoreMock, StorageMock};
     use crate::board::Board;
     use std::{str, io::Cursor};
 
@@ -45,10 +106,12 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
         now(&mut board, &mut writer, false, None);
@@ -63,27 +126,29 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        board.create_task("task1", None);
-        board.create_task("task2", None);
-        board.create_task("task3", None);
-        board.create_task("task4", None);
-        board.create_task("task5", None);
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        board.create_task("task3", None).unwrap();
+        board.create_task("task4", None).unwrap();
+        board.create_task("task5", None).unwrap();
 
-        board.update("task4", get_task("task4", Column::Done));
-        board.update("task1", get_task("task1", Column::Doing));
-        board.update("task3", get_task("task3", Column::Done));
+        board.update("task4", get_task("task4", "done")).unwrap();
+        board.update("task1", get_task("task1", "doing")).unwrap();
+        board.update("task3", get_task("task3", "done")).unwrap();
 
 
         now(&mut board, &mut writer, false, None);
 
         let output = writer.get_ref();
-        assert_eq!(output, b"task1\n");
+        assert_eq!(output, b"1 task1\n");
     }
 
     #[test]
@@ -92,26 +157,28 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        board.create_task("task1", None);
-        board.create_task("task2", None);
-        board.create_task("task3", None);
-        board.create_task("task4", None);
-        board.create_task("task5", None);
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        board.create_task("task3", None).unwrap();
+        board.create_task("task4", None).unwrap();
+        board.create_task("task5", None).unwrap();
 
-        board.update("task4", get_task("task4", Column::Done));
-        board.update("task1", get_task("task1", Column::Doing));
-        board.update("task3", get_task("task3", Column::Done));
+        board.update("task4", get_task("task4", "done")).unwrap();
+        board.update("task1", get_task("task1", "doing")).unwrap();
+        board.update("task3", get_task("task3", "done")).unwrap();
 
         now(&mut board, &mut writer, true, None);
 
         let output = writer.get_ref();
-        assert_eq!(output, b"task1");
+        assert_eq!(output, b"1 task1");
     }
 
     #[test]
@@ -120,26 +187,28 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        board.create_task("task1", None);
-        board.create_task("task2", None);
-        board.create_task("task3", None);
-        board.create_task("task4", None);
-        board.create_task("task5", None);
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        board.create_task("task3", None).unwrap();
+        board.create_task("task4", None).unwrap();
+        board.create_task("task5", None).unwrap();
 
-        board.update("task1", get_task("task1", Column::Doing));
-        board.update("task2", get_task("task2", Column::Doing));
-        board.update("task3", get_task("task3", Column::Done));
+        board.update("task1", get_task("task1", "doing")).unwrap();
+        board.update("task2", get_task("task2", "doing")).unwrap();
+        board.update("task3", get_task("task3", "done")).unwrap();
 
         now(&mut board, &mut writer, false, None);
 
         let output = writer.get_ref();
-        assert_eq!(output, b"task1\ntask2\n");
+        assert_eq!(output, b"1 task1\n2 task2\n");
     }
 
     #[test]
@@ -148,27 +217,29 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        board.create_task("task1", None);
-        board.create_task("task2", None);
-        board.create_task("task3", None);
-        board.create_task("task4", None);
-        board.create_task("task5", None);
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        board.create_task("task3", None).unwrap();
+        board.create_task("task4", None).unwrap();
+        board.create_task("task5", None).unwrap();
 
-        board.update("task1", get_task("task1", Column::Doing));
-        board.update("task2", get_task("task2", Column::Doing));
-        board.update("task3", get_task("task3", Column::Done));
+        board.update("task1", get_task("task1", "doing")).unwrap();
+        board.update("task2", get_task("task2", "doing")).unwrap();
+        board.update("task3", get_task("task3", "done")).unwrap();
        
         now(&mut board, &mut writer, true, None);
 
         let output = writer.get_ref();
         let str_output = str::from_utf8(&output).unwrap();
-        assert_eq!(str_output, "task1,task2".to_string());
+        assert_eq!(str_output, "1 task1,2 task2".to_string());
     }
 
     #[test]
@@ -177,23 +248,25 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        board.create_task("task1", None);
-        board.create_task("task2", None);
-        board.create_task("task3", None);
-        board.create_task("task4", None);
-        board.create_task("task5", None);
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        board.create_task("task3", None).unwrap();
+        board.create_task("task4", None).unwrap();
+        board.create_task("task5", None).unwrap();
 
-        let mut task1 = get_task("task1", Column::Doing);
+        let mut task1 = get_task("task1", "doing");
         task1.tags = Some(vec!("tag".to_owned()));
-        board.update("task1", task1);
-        board.update("task2", get_task("task2", Column::Doing));
-        board.update("task3", get_task("task3", Column::Done));
+        board.update("task1", task1).unwrap();
+        board.update("task2", get_task("task2", "doing")).unwrap();
+        board.update("task3", get_task("task3", "done")).unwrap();
        
         now(
             &mut board,
@@ -203,16 +276,67 @@ mod tests {
 
         let output = writer.get_ref();
         let str_output = str::from_utf8(&output).unwrap();
-        assert_eq!(str_output, "task1\n".to_string());
+        assert_eq!(str_output, "1 task1\n".to_string());
 
     }
 
-    fn get_task(key: &str, column: Column) -> Task {
+    #[test]
+    fn it_can_show_every_board() {
+        let mut writer = Cursor::new(vec!());
+        let mut storage = StorageMock::new();
+        storage.setup("main", |board| {
+            board.create_task("task1", None).unwrap();
+            board.update("task1", get_task("task1", "doing")).unwrap();
+        });
+        storage.setup("home", |board| {
+            board.create_task("task2", None).unwrap();
+        });
+        storage.setup("work", |board| {
+            board.create_task("task3", None).unwrap();
+            board.create_task("task4", None).unwrap();
+            board.update("task3", get_task("task3", "doing")).unwrap();
+            board.update("task4", get_task("task4", "doing")).unwrap();
+        });
+        let names = vec!("main", "home", "work")
+            .into_iter().map(|n| n.to_owned()).collect();
+
+        now_all(names, &storage, &mut writer, false, None).unwrap();
+
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert_eq!(output, "main:\n1 task1\nwork:\n1 task3\n2 task4\n");
+    }
+
+    #[test]
+    fn every_board_fits_on_one_line_without_newlines() {
+        let mut writer = Cursor::new(vec!());
+        let mut storage = StorageMock::new();
+        storage.setup("main", |board| {
+            board.create_task("task1", None).unwrap();
+            board.update("task1", get_task("task1", "doing")).unwrap();
+        });
+        storage.setup("work", |board| {
+            board.create_task("task2", Some("tag".to_owned())).unwrap();
+            board.create_task("task3", None).unwrap();
+            let mut task2 = get_task("task2", "doing");
+            task2.tags = Some(vec!("tag".to_owned()));
+            board.update("task2", task2).unwrap();
+            board.update("task3", get_task("task3", "doing")).unwrap();
+        });
+        let names = vec!("main".to_owned(), "work".to_owned());
+
+        now_all(
+            names, &storage, &mut writer, true, Some("tag".to_owned())
+        ).unwrap();
+
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert_eq!(output, "work: 1 task2");
+    }
+
+    fn get_task(key: &str, column: &str) -> Task {
         Task {
             name: key.to_owned(),
-            column,
-            description: None,
-            tags: None
+            column: column.to_owned(),
+            ..Task::default()
         }
     }
 
@@ -0,0 +1,155 @@
+use std::io::Write;
+use crate::board::BoardAccess;
+use crate::error::KanbenError;
+use super::{BoardStorage, BoardVisitor};
+use super::now::in_progress;
+
+// how many tasks each board has in each column, followed by
+// what's in progress on it. --tag narrows both down
+pub fn overview<S: BoardStorage>(
+    names: Vec<String>,
+    storage: &S,
+    writer: &mut dyn Write,
+    no_newlines: bool,
+    tag: Option<String>
+) -> Result<(), KanbenError> {
+    let mut boards = vec!();
+    for name in names {
+        let mut visitor = Summary{
+            tag: tag.clone(),
+            counts: vec!(),
+            tasks: vec!()
+        };
+        storage.with_board(&name, &mut visitor)?;
+        boards.push((name, visitor));
+    }
+
+    let output = if no_newlines {
+        boards.iter().map(|(name, summary)| {
+            let tasks = if summary.tasks.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", summary.tasks.join(","))
+            };
+            format!("{}: {}{}", name, summary.counts(), tasks)
+        }).collect::<Vec<String>>().join(" | ")
+    } else {
+        boards.iter().map(|(name, summary)| {
+            let tasks = summary.tasks.iter()
+                .map(|t| format!("  {}\n", t))
+                .collect::<String>();
+            format!("{}: {}\n{}", name, summary.counts(), tasks)
+        }).collect::<String>()
+    };
+    write!(writer, "{}", output).unwrap();
+    Ok(())
+}
+
+struct Summary {
+    tag: Option<String>,
+    counts: Vec<(String, usize)>,
+    tasks: Vec<String>
+}
+
+impl Summary {
+    fn counts(&self) -> String {
+        self.counts.iter()
+            .map(|(column, count)| format!("{} {}", column, count))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+impl BoardVisitor for Summary {
+    fn visit<B: BoardAccess>(
+        &mut self,
+        board: &mut B
+    ) -> Result<(), KanbenError> {
+        self.counts = board.get_config().columns.iter().map(|c| {
+            (c.clone(), board.get_column(c, self.tag.clone()).len())
+        }).collect();
+        self.tasks = in_progress(board, &self.tag);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opts::Task;
+    use crate::test::StorageMock;
+    use std::{str, io::Cursor};
+
+    fn storage() -> StorageMock {
+        let mut storage = StorageMock::new();
+        storage.setup("main", |board| {
+            board.create_task("task1", None).unwrap();
+            board.create_task("task2", Some("tag".to_owned())).unwrap();
+            board.update("task1", get_task("task1", "doing", None)).unwrap();
+        });
+        storage.setup("work", |board| {
+            board.create_task("task3", Some("tag".to_owned())).unwrap();
+            board.update(
+                "task3", get_task("task3", "done", Some("tag"))
+            ).unwrap();
+        });
+        storage
+    }
+
+    fn names() -> Vec<String> {
+        vec!("main".to_owned(), "work".to_owned())
+    }
+
+    #[test]
+    fn it_summarises_every_board() {
+        let mut writer = Cursor::new(vec!());
+
+        overview(names(), &storage(), &mut writer, false, None).unwrap();
+
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert_eq!(
+            output,
+            "main: todo 1, doing 1, done 0\n  1 task1\n\
+            work: todo 0, doing 0, done 1\n"
+        );
+    }
+
+    #[test]
+    fn it_fits_on_one_line_without_newlines() {
+        let mut writer = Cursor::new(vec!());
+
+        overview(names(), &storage(), &mut writer, true, None).unwrap();
+
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert_eq!(
+            output,
+            "main: todo 1, doing 1, done 0 (1 task1) | \
+            work: todo 0, doing 0, done 1"
+        );
+    }
+
+    #[test]
+    fn it_only_counts_tagged_tasks_when_filtered() {
+        let mut writer = Cursor::new(vec!());
+
+        overview(
+            names(), &storage(), &mut writer, false, Some("tag".to_owned())
+        ).unwrap();
+
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert_eq!(
+            output,
+            "main: todo 1, doing 0, done 0\n\
+            work: todo 0, doing 0, done 1\n"
+        );
+    }
+
+    fn get_task(key: &str, column: &str, tag: Option<&str>) -> Task {
+        Task {
+            name: key.to_owned(),
+            column: column.to_owned(),
+            tags: tag.map(|t| vec!(t.to_owned())),
+            ..Task::default()
+        }
+    }
+}
@@ -0,0 +1,565 @@
+use std::io::Write;
+use std::collections::HashSet;
+use crate::board::{BoardAccess, Position};
+use crate::editor::Editor;
+use crate::error::KanbenError;
+use crate::opts::Task;
+use crate::prompt::Prompt;
+use super::rename::rename_item;
+use super::descriptions::trash_description;
+
+const HELP: &str = "\
+# Reorder the tasks, or move them under another column, then save
+# and quit. Deleting a line deletes its task and changing a title
+# renames it, you'll be asked before either happens. Lines starting
+# with a single '#' are ignored.
+";
+
+// a task's place in the plan. tasks are matched up by id, so
+// their titles can be changed
+struct Line {
+    id: Option<u64>,
+    title: String
+}
+
+// everything the edited plan would do to the board
+struct Changes {
+    deleted: Vec<Task>,
+    renamed: Vec<(Task, String)>,
+    moved: Vec<(Task, String)>,
+    order: Vec<(String, Vec<Task>)>
+}
+
+// the whole board is written out one column at a time, and
+// whatever comes back from the editor becomes its new order.
+// nothing is changed until the plan has been checked, and
+// anything that loses a task needs a yes first
+pub fn plan<B: BoardAccess>(
+    board: &mut B,
+    editor: &mut dyn Editor,
+    prompt: &mut dyn Prompt,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    let text = write_plan(board);
+    let edited = editor.edit_text("plan", &text)?;
+    if edited == text {
+        let _ = write!(writer, "Nothing was changed.\n");
+        return Ok(());
+    }
+
+    let columns = read_plan(&edited, &board.get_config().columns)?;
+    let changes = compare(board, columns)?;
+    check(board, &changes)?;
+
+    if !changes.deleted.is_empty() || !changes.renamed.is_empty() {
+        for task in &changes.deleted {
+            let _ = write!(writer, "Deleting '{}'.\n", task.label());
+        }
+        for (task, title) in &changes.renamed {
+            let _ = write!(
+                writer, "Renaming '{}' to '{}'.\n", task.label(), title
+            );
+        }
+        let _ = write!(writer, "Apply these changes? [y/N] ");
+        let _ = writer.flush();
+        if !prompt.confirm() {
+            let _ = write!(writer, "Nothing was changed.\n");
+            return Ok(());
+        }
+    }
+
+    // descriptions are only trashed once the plan's been applied,
+    // files can't be put back if the board refuses part of it
+    board.together("apply a plan", |board| apply(board, &changes))?;
+    for task in &changes.deleted {
+        trash_description(task, editor, writer);
+    }
+    let _ = write!(writer, "Applied the plan.\n");
+    Ok(())
+}
+
+fn write_plan<B: BoardAccess>(board: &B) -> String {
+    let columns = board.get_config().columns.iter().map(|column| {
+        let tasks = board.get_column(column, None).iter()
+            .map(|t| format!("{}\n", t.label()))
+            .collect::<String>();
+        format!("## {}\n{}", column, tasks)
+    }).collect::<Vec<String>>().join("\n");
+    format!("{}\n{}", HELP, columns)
+}
+
+fn read_plan(
+    text: &str,
+    columns: &[String]
+) -> Result<Vec<(String, Vec<Line>)>, KanbenError> {
+    let mut planned: Vec<(String, Vec<Line>)> = vec!();
+    for line in text.lines().map(|l| l.trim()) {
+        if let Some(column) = line.strip_prefix("## ") {
+            let column = column.trim();
+            if !columns.iter().any(|c| c == column) {
+                return Err(KanbenError::ColumnNotFound(column.to_owned()));
+            }
+            if planned.iter().any(|(c, _)| c == column) {
+                return Err(KanbenError::InvalidPlan(
+                    format!("'{}' is in the plan twice", column)
+                ));
+            }
+            planned.push((column.to_owned(), vec!()));
+        } else if line.is_empty() || line.starts_with('#') {
+            continue;
+        } else {
+            let tasks = match planned.last_mut() {
+                Some((_, tasks)) => tasks,
+                None => return Err(KanbenError::InvalidPlan(format!(
+                    "'{}' needs to be under a column", line
+                )))
+            };
+            tasks.push(read_line(line)?);
+        }
+    }
+    Ok(planned)
+}
+
+fn read_line(line: &str) -> Result<Line, KanbenError> {
+    let mut parts = line.splitn(2, ' ');
+    let first = parts.next().unwrap_or("");
+    match first.parse::<u64>() {
+        Ok(id) => {
+            let title = parts.next().unwrap_or("").trim();
+            if title.is_empty() {
+                return Err(KanbenError::InvalidPlan(
+                    format!("task {} needs a title", id)
+                ));
+            }
+            Ok(Line{ id: Some(id), title: title.to_owned() })
+        },
+        Err(_) => Ok(Line{ id: None, title: line.to_owned() })
+    }
+}
+
+fn compare<B: BoardAccess>(
+    board: &B,
+    columns: Vec<(String, Vec<Line>)>
+) -> Result<Changes, KanbenError> {
+    let mut seen = HashSet::new();
+    let mut renamed = vec!();
+    let mut moved = vec!();
+    let mut order = vec!();
+
+    for (column, lines) in columns {
+        let mut tasks = vec!();
+        for line in lines {
+            let task = match line.id {
+                Some(id) => board.find_by_id(id),
+                None => board.get(&line.title).filter(|t| t.id.is_none())
+            }.ok_or(KanbenError::InvalidPlan(format!(
+                "'{}' isn't on the board, new tasks can't be added here",
+                line.title
+            )))?;
+
+            if !seen.insert(task.name.clone()) {
+                return Err(KanbenError::InvalidPlan(
+                    format!("'{}' is in the plan twice", task.label())
+                ));
+            }
+            if task.name != line.title {
+                renamed.push((task.clone(), line.title.clone()));
+            }
+            if task.column != column {
+                moved.push((task.clone(), column.clone()));
+            }
+            tasks.push(task);
+        }
+        order.push((column, tasks));
+    }
+
+    let deleted = board.get_all_tasks().into_iter()
+        .filter(|t| !seen.contains(&t.name))
+        .collect();
+    Ok(Changes{ deleted, renamed, moved, order })
+}
+
+// anything the board would refuse is refused up front, before
+// anyone's asked to confirm the plan
+fn check<B: BoardAccess>(
+    board: &B,
+    changes: &Changes
+) -> Result<(), KanbenError> {
+    for (task, title) in &changes.renamed {
+        if title.trim().is_empty() {
+            return Err(KanbenError::InvalidTitle);
+        }
+        let taken = board.get(title).is_some()
+            && !changes.deleted.iter().any(|t| t.name == *title);
+        if taken {
+            return Err(KanbenError::TaskExists(title.clone()));
+        }
+        if changes.renamed.iter().any(|(t, n)| n == title && t != task) {
+            return Err(KanbenError::TaskExists(title.clone()));
+        }
+    }
+
+    let config = board.get_config();
+    for (task, column) in &changes.moved {
+        if !config.allows(&task.column, column) {
+            return Err(KanbenError::IllegalMove{
+                from: task.column.clone(),
+                to: column.clone()
+            });
+        }
+    }
+
+    if config.strict_limits {
+        for (column, tasks) in &changes.order {
+            let before = board.get_column(column, None).len();
+            match config.limit(column) {
+                Some(limit) if tasks.len() > limit && tasks.len() > before => {
+                    return Err(KanbenError::WipLimitReached{
+                        column: column.clone(),
+                        limit
+                    });
+                },
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+// moves go through the same checks as any other, so a column's
+// workflow and limits still apply
+fn apply<B: BoardAccess>(
+    board: &mut B,
+    changes: &Changes
+) -> Result<(), KanbenError> {
+    for task in &changes.deleted {
+        board.delete(&task.name)?;
+    }
+    for (task, title) in &changes.renamed {
+        rename_item(task.name.clone(), title.clone(), board)?;
+    }
+
+    let title = |task: &Task| changes.renamed.iter()
+        .find(|(t, _)| t.name == task.name)
+        .map(|(_, title)| title.clone())
+        .unwrap_or(task.name.clone());
+
+    for (task, column) in &changes.moved {
+        let key = title(task);
+        let mut moved = board.get(&key)
+            .ok_or(KanbenError::TaskNotFound(key.clone()))?;
+        moved.column = column.clone();
+        board.update(&key, moved)?;
+    }
+    // only the tasks out of place are moved, so the rest don't
+    // pick up moves in their history they were never given
+    for (column, tasks) in &changes.order {
+        let mut current = board.get_column(column, None).into_iter()
+            .map(|t| t.name)
+            .collect::<Vec<String>>();
+        for (i, task) in tasks.iter().enumerate() {
+            let key = title(task);
+            if current.get(i) == Some(&key) {
+                continue;
+            }
+            board.move_within(&key, Position::At(i))?;
+            current.retain(|k| *k != key);
+            current.insert(i.min(current.len()), key);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{EventKind, Transition};
+    use crate::store::Store;
+    use crate::test::{Stores, EditorMock, PromptMock};
+    use std::{str, io::Cursor};
+
+    // todo holds 1 a, 2 b and 3 c, doing holds 4 d
+    fn stores() -> Stores {
+        let mut stores = Stores::new();
+        {
+            let mut board = stores.board();
+            for name in &["a", "b", "c", "d"] {
+                board.create_task(name, None).unwrap();
+            }
+            let mut d = board.get("d").unwrap();
+            d.column = "doing".to_owned();
+            board.update("d", d).unwrap();
+        }
+        stores
+    }
+
+    fn edited(columns: &[(&str, &[&str])]) -> String {
+        columns.iter().map(|(column, tasks)| {
+            let tasks = tasks.iter()
+                .map(|t| format!("{}\n", t))
+                .collect::<String>();
+            format!("## {}\n{}", column, tasks)
+        }).collect::<Vec<String>>().join("\n")
+    }
+
+    fn names<B: BoardAccess>(board: &B, column: &str) -> Vec<String> {
+        board.get_column(column, None).into_iter().map(|t| t.name).collect()
+    }
+
+    fn run(
+        stores: &mut Stores,
+        text: &str,
+        prompt: &mut PromptMock
+    ) -> (Result<(), KanbenError>, String) {
+        let mut writer = Cursor::new(vec!());
+        let mut editor = EditorMock::new();
+        editor.return_from_edit_text(Ok(text.to_owned()));
+        let result = plan(
+            &mut stores.board(), &mut editor, prompt, &mut writer
+        );
+        (result, str::from_utf8(writer.get_ref()).unwrap().to_owned())
+    }
+
+    #[test]
+    fn it_writes_each_column_with_its_tasks() {
+        let mut stores = stores();
+        let mut writer = Cursor::new(vec!());
+        let mut editor = EditorMock::new();
+
+        plan(
+            &mut stores.board(),
+            &mut editor,
+            &mut PromptMock::new(),
+            &mut writer
+        ).unwrap();
+
+        let expected = format!(
+            "{}\n{}",
+            HELP,
+            edited(&[
+                ("todo", &["1 a", "2 b", "3 c"]),
+                ("doing", &["4 d"]),
+                ("done", &[])
+            ])
+        );
+        assert!(editor.edit_text_called_with("plan", &expected));
+        let output = str::from_utf8(writer.get_ref()).unwrap();
+        assert_eq!(output, "Nothing was changed.\n");
+    }
+
+    #[test]
+    fn it_reorders_and_moves_tasks() {
+        let mut stores = stores();
+        let mut prompt = PromptMock::new();
+        let text = edited(&[
+            ("todo", &["3 c", "1 a"]),
+            ("doing", &["4 d", "2 b"]),
+            ("done", &[])
+        ]);
+
+        let (result, output) = run(&mut stores, &text, &mut prompt);
+
+        assert_eq!(result, Ok(()));
+        assert!(!prompt.was_asked());
+        assert_eq!(output, "Applied the plan.\n");
+        let board = stores.board();
+        assert_eq!(names(&board, "todo"), vec!("c", "a"));
+        assert_eq!(names(&board, "doing"), vec!("d", "b"));
+    }
+
+    #[test]
+    fn deleted_and_renamed_tasks_need_a_yes() {
+        let mut stores = stores();
+        let mut prompt = PromptMock::new();
+        let text = edited(&[
+            ("todo", &["1 a", "2 bee"]),
+            ("doing", &["4 d"]),
+            ("done", &[])
+        ]);
+
+        let (result, output) = run(&mut stores, &text, &mut prompt);
+
+        assert_eq!(result, Ok(()));
+        assert!(prompt.was_asked());
+        assert_eq!(
+            output,
+            "Deleting '3 c'.\nRenaming '2 b' to 'bee'.\n\
+            Apply these changes? [y/N] Nothing was changed.\n"
+        );
+        let board = stores.board();
+        assert_eq!(names(&board, "todo"), vec!("a", "b", "c"));
+    }
+
+    #[test]
+    fn deleted_and_renamed_tasks_are_applied_once_confirmed() {
+        let mut stores = stores();
+        let mut prompt = PromptMock::new();
+        prompt.answer_yes();
+        let text = edited(&[
+            ("todo", &["2 bee", "1 a"]),
+            ("doing", &["4 d"]),
+            ("done", &[])
+        ]);
+
+        let (result, _) = run(&mut stores, &text, &mut prompt);
+
+        assert_eq!(result, Ok(()));
+        {
+            let board = stores.board();
+            assert_eq!(names(&board, "todo"), vec!("bee", "a"));
+            assert_eq!(board.get("bee").unwrap().id, Some(2));
+        }
+        assert!(stores.tasks.rm_called_with("c"));
+        assert_eq!(stores.trash.get("3").unwrap().task.name, "c");
+    }
+
+    #[test]
+    fn moves_the_workflow_forbids_change_nothing() {
+        let mut stores = stores();
+        {
+            let mut board = stores.board();
+            let mut config = board.get_config();
+            config.workflow = Some(vec!(Transition::new("todo", "doing")));
+            board.save_config(config).unwrap();
+        }
+        let text = edited(&[
+            ("todo", &["2 b", "1 a"]),
+            ("doing", &["4 d"]),
+            ("done", &["3 c"])
+        ]);
+
+        let (result, _) = run(&mut stores, &text, &mut PromptMock::new());
+
+        assert_eq!(result, Err(KanbenError::IllegalMove{
+            from: "todo".to_owned(),
+            to: "done".to_owned()
+        }));
+        let board = stores.board();
+        assert_eq!(names(&board, "todo"), vec!("a", "b", "c"));
+    }
+
+    #[test]
+    fn a_plan_it_cant_read_changes_nothing() {
+        let cases = vec!(
+            ("1 a\n## todo\n", "'1 a' needs to be under a column"),
+            ("## todo\n1 a\n1 a\n", "'1 a' is in the plan twice"),
+            (
+                "## todo\n9 z\n",
+                "'z' isn't on the board, new tasks can't be added here"
+            ),
+            ("## todo\n1\n", "task 1 needs a title"),
+            ("## todo\n1 a\n## todo\n", "'todo' is in the plan twice"),
+        );
+
+        for (text, error) in cases {
+            let mut stores = stores();
+            let (result, _) = run(&mut stores, text, &mut PromptMock::new());
+
+            assert_eq!(result, Err(KanbenError::InvalidPlan(error.to_owned())));
+            assert_eq!(stores.board().get_all_tasks().len(), 4);
+        }
+    }
+
+    #[test]
+    fn columns_must_already_exist() {
+        let mut stores = stores();
+
+        let (result, _) = run(
+            &mut stores, "## later\n1 a\n", &mut PromptMock::new()
+        );
+
+        assert_eq!(result, Err(KanbenError::ColumnNotFound("later".to_owned())));
+    }
+
+    #[test]
+    fn a_rename_cant_take_another_tasks_title() {
+        let mut stores = stores();
+        let text = edited(&[
+            ("todo", &["1 b", "2 b2", "3 c"]),
+            ("doing", &["4 d"])
+        ]);
+
+        let (result, _) = run(&mut stores, &text, &mut PromptMock::new());
+
+        assert_eq!(result, Err(KanbenError::TaskExists("b".to_owned())));
+    }
+
+    #[test]
+    fn a_plan_is_undone_in_one_go() {
+        let mut stores = stores();
+        let mut prompt = PromptMock::new();
+        prompt.answer_yes();
+        let text = edited(&[
+            ("todo", &["1 a"]),
+            ("doing", &["4 d", "2 bee"]),
+            ("done", &[])
+        ]);
+        let journaled = stores.board().journal().len();
+
+        let (result, _) = run(&mut stores, &text, &mut prompt);
+
+        assert_eq!(result, Ok(()));
+        let mut board = stores.board();
+        assert_eq!(board.journal().len(), journaled + 1);
+        board.undo().unwrap();
+        assert_eq!(names(&board, "todo"), vec!("a", "b", "c"));
+        assert_eq!(names(&board, "doing"), vec!("d"));
+    }
+
+    #[test]
+    fn only_the_tasks_that_were_moved_are_recorded() {
+        let mut stores = stores();
+        let recorded = stores.board().all_history().len();
+        let text = edited(&[
+            ("todo", &["3 c", "1 a"]),
+            ("doing", &["4 d", "2 b"]),
+            ("done", &[])
+        ]);
+
+        let (result, _) = run(&mut stores, &text, &mut PromptMock::new());
+
+        assert_eq!(result, Ok(()));
+        let events = stores.board().all_history().into_iter()
+            .skip(recorded)
+            .map(|e| (e.task, e.kind))
+            .collect::<Vec<(String, EventKind)>>();
+        assert_eq!(events, vec!(
+            ("c".to_owned(), EventKind::Prioritised{
+                column: "todo".to_owned(),
+                from: 2,
+                to: 1
+            }),
+            ("b".to_owned(), EventKind::Moved{
+                from: "todo".to_owned(),
+                to: "doing".to_owned()
+            })
+        ));
+    }
+
+    #[test]
+    fn a_plan_the_board_fails_part_way_through_changes_nothing() {
+        let mut stores = stores();
+        {
+            let mut board = stores.board();
+            let mut b = board.get("b").unwrap();
+            b.tags = Some(vec!("tag".to_owned()));
+            board.update("b", b).unwrap();
+        }
+        // c is deleted before b is renamed, which fails
+        stores.tags.fail_writes();
+        let mut prompt = PromptMock::new();
+        prompt.answer_yes();
+        let text = edited(&[
+            ("todo", &["1 a", "2 bee"]),
+            ("doing", &["4 d"]),
+            ("done", &[])
+        ]);
+
+        let (result, _) = run(&mut stores, &text, &mut prompt);
+
+        assert!(result.is_err());
+        let board = stores.board();
+        assert_eq!(names(&board, "todo"), vec!("a", "b", "c"));
+        assert!(board.trashed().is_empty());
+    }
+}
@@ -1,11 +1,12 @@
 use crate::board::BoardAccess;
+use crate::error::KanbenError;
 use std::io::Write;
 
 pub fn reindex<B: BoardAccess>(
     board: &mut B,
     _writer: &mut dyn Write,
-){
-    let _ = board.reindex_columns();
+) -> Result<(), KanbenError> {
+    board.reindex_columns().map(|_| ())
 }
 
 /*
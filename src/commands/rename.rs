@@ -0,0 +1,67 @@
+use crate::board::BoardAccess;
+use crate::error::KanbenError;
+
+// descriptions are kept by id, so only the board needs to know
+// about the new title
+pub fn rename_item<B: BoardAccess>(
+    key: String,
+    new_key: String,
+    board: &mut B
+) -> Result<(), KanbenError> {
+    if new_key.trim().is_empty() {
+        return Err(KanbenError::InvalidTitle);
+    }
+
+    board.rename(&key, &new_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opts::Task;
+    use crate::test::BoardMock;
+
+    #[test]
+    fn it_renames_the_task_on_the_board() {
+        let mut board = BoardMock::new();
+        board.set("old", get_task("old", None));
+
+        rename_item("old".to_owned(), "new".to_owned(), &mut board).unwrap();
+
+        assert!(board.rename_called_with("old", "new"));
+    }
+
+    #[test]
+    fn it_leaves_the_description_file_where_it_is() {
+        let mut board = BoardMock::new();
+        board.set("old", get_task("old", Some("files/4/description.md")));
+
+        rename_item("old".to_owned(), "new".to_owned(), &mut board).unwrap();
+
+        assert!(board.rename_called_with("old", "new"));
+        assert_eq!(
+            board.get("new").unwrap().description,
+            Some("files/4/description.md".to_owned())
+        );
+    }
+
+    #[test]
+    fn it_wont_rename_to_an_empty_title() {
+        let mut board = BoardMock::new();
+        board.set("old", get_task("old", None));
+
+        let result = rename_item("old".to_owned(), " ".to_owned(), &mut board);
+
+        assert_eq!(result, Err(KanbenError::InvalidTitle));
+        assert!(!board.rename_called_with("old", " "));
+    }
+
+    fn get_task(key: &str, description: Option<&str>) -> Task {
+        Task {
+            name: key.to_owned(),
+            column: "todo".to_owned(),
+            description: description.map(|d| d.to_owned()),
+            ..Task::default()
+        }
+    }
+}
@@ -1,44 +1,375 @@
-use crate::opts::Column;
-use crate::board::BoardAccess;
+use std::io::Write;
+use crate::board::{BoardAccess, Position};
+use crate::error::KanbenError;
+use crate::editor::Editor;
+use crate::file::Reader;
+use crate::opts::{MoveItemWithin, Task};
+use super::edit::edit_item;
+use super::descriptions::trash_description;
+use super::resolve;
 
+// an existing title is never overwritten - the new task can
+// take a numbered title instead, or the existing one is opened
 pub fn add_item<B: BoardAccess>(
     name: String,
     tag: Option<String>,
-    board: &mut B
-){
-    if is_valid_key(&name) {
-        board.create_task(&name, tag);
+    suffix: bool,
+    open: bool,
+    board: &mut B,
+    editor: &mut dyn Editor,
+    file_reader: &dyn Reader,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    if !is_valid_key(&name) {
+        return Err(KanbenError::InvalidTitle);
+    }
+
+    match board.create_task(&name, tag.clone()) {
+        Err(KanbenError::TaskExists(_)) if suffix => {
+            let title = next_free_title(&name, board);
+            board.create_task(&title, tag)?;
+            let _ = write!(writer, "Added as '{}'.\n", title);
+            Ok(())
+        },
+        Err(KanbenError::TaskExists(_)) if open => {
+            edit_item(name, board, editor, file_reader, writer)
+        },
+        result => result
     }
 }
 
+fn next_free_title<B: BoardAccess>(name: &str, board: &B) -> String {
+    (2..).map(|n| format!("{}-{}", name, n))
+        .find(|title| board.get(title).is_none())
+        .expect("ran out of titles")
+}
+
 fn is_valid_key(name: &str) -> bool {
     name.trim().len() > 0
 }
 
-pub fn start_item<B: BoardAccess>(name: String, board: &mut B) {
-    move_item(name, board, Column::Doing);
+pub fn start_item<B: BoardAccess>(
+    name: String,
+    force: bool,
+    board: &mut B
+) -> Result<(), KanbenError> {
+    let column = board.get_config().start_column();
+    move_item(name, force, board, column)
 }
 
-pub fn complete_item<B: BoardAccess>(name: String, board: &mut B) {
-    move_item(name, board, Column::Done);
+pub fn complete_item<B: BoardAccess>(
+    name: String,
+    force: bool,
+    board: &mut B
+) -> Result<(), KanbenError> {
+    let column = board.get_config().done();
+    move_item(name, force, board, column)
 }
 
-fn move_item<B: BoardAccess>(name: String, board: &mut B, column: Column) {
-    let mut item = board.get(&name).unwrap();
+fn move_item<B: BoardAccess>(
+    name: String,
+    force: bool,
+    board: &mut B,
+    column: String
+) -> Result<(), KanbenError> {
+    let mut item = board.get(&name)
+        .ok_or(KanbenError::TaskNotFound(name.clone()))?;
+
     item.column = column;
-    board.update(&name, item);
+    if force {
+        board.force_update(&name, item)
+    } else {
+        board.update(&name, item)
+    }
 }
 
-pub fn delete_item<B: BoardAccess>(name: String, board: &mut B) {
-    board.remove(&name);
+pub fn delete_item<B: BoardAccess>(
+    name: String,
+    board: &mut B,
+    editor: &mut dyn Editor,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    let task = board.get(&name)
+        .ok_or(KanbenError::TaskNotFound(name.clone()))?;
+    board.delete(&name)?;
+    trash_description(&task, editor, writer);
+    Ok(())
 }
 
-pub fn clear_done<B: BoardAccess>(board: &mut B) {
-    board.get_all_tasks().iter().filter(|task| {
-        task.column == Column::Done
-    }).for_each(|task| board.remove(&task.name));
+pub fn clear_done<B: BoardAccess>(
+    board: &mut B,
+    editor: &mut dyn Editor,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    let done = board.get_config().done();
+    let tasks: Vec<Task> = board.get_all_tasks().into_iter()
+        .filter(|t| t.column == done)
+        .collect();
+    board.together("clear done", |board| {
+        tasks.iter().try_for_each(|task| board.delete(&task.name))
+    })?;
+    for task in tasks.iter() {
+        trash_description(task, editor, writer);
+    }
+    Ok(())
 }
 
-pub fn top<B: BoardAccess>(name: String, board: &mut B) {
-    board.top_priority(&name);
+pub fn top<B: BoardAccess>(
+    name: String,
+    board: &mut B
+) -> Result<(), KanbenError> {
+    board.top_priority(&name)
+}
+
+pub fn reposition<B: BoardAccess>(
+    name: String,
+    position: Position,
+    board: &mut B
+) -> Result<(), KanbenError> {
+    board.move_within(&name, position)
+}
+
+// clap makes sure exactly one of --before, --after and
+// --position is given. positions start from 1
+pub fn move_within<B: BoardAccess>(
+    item: MoveItemWithin,
+    board: &mut B
+) -> Result<(), KanbenError> {
+    let position = match (item.before, item.after, item.position) {
+        (Some(other), _, _) => Position::Before(resolve(other, board)),
+        (_, Some(other), _) => Position::After(resolve(other, board)),
+        (_, _, Some(0)) => return Err(KanbenError::InvalidPosition(0)),
+        (_, _, Some(n)) => Position::At(n - 1),
+        (None, None, None) => return Ok(())
+    };
+    board.move_within(&resolve(item.title, board), position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{StoreMock, EditorMock, BoardMock, ReaderMock};
+    use crate::opts::{Task, Times};
+    use crate::board::{Board, OverLimit};
+    use std::io::Cursor;
+
+    #[test]
+    fn it_refuses_to_add_a_duplicate_title() {
+        let mut writer = Cursor::new(vec!());
+        let mut editor = EditorMock::new();
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        add_item(
+            "task".to_owned(), None, false, false,
+            &mut board, &mut editor, &ReaderMock::new(), &mut writer
+        ).unwrap();
+        let result = add_item(
+            "task".to_owned(), None, false, false,
+            &mut board, &mut editor, &ReaderMock::new(), &mut writer
+        );
+
+        assert_eq!(result, Err(KanbenError::TaskExists("task".to_owned())));
+        assert_eq!(board.get_column("todo", None).len(), 1);
+    }
+
+    #[test]
+    fn it_can_suffix_a_duplicate_title() {
+        let mut writer = Cursor::new(vec!());
+        let mut editor = EditorMock::new();
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        for _ in 0..3 {
+            add_item(
+                "task".to_owned(), None, true, false,
+                &mut board, &mut editor, &ReaderMock::new(), &mut writer
+            ).unwrap();
+        }
+
+        let titles = board.get_column("todo", None).into_iter()
+            .map(|t| t.name)
+            .collect::<Vec<String>>();
+        assert_eq!(titles, vec!("task", "task-2", "task-3"));
+    }
+
+    #[test]
+    fn it_can_open_the_existing_task_instead() {
+        let mut writer = Cursor::new(vec!());
+        let mut editor = EditorMock::new();
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        add_item(
+            "task".to_owned(), None, false, false,
+            &mut board, &mut editor, &ReaderMock::new(), &mut writer
+        ).unwrap();
+        add_item(
+            "task".to_owned(), None, false, true,
+            &mut board, &mut editor, &ReaderMock::new(), &mut writer
+        ).unwrap();
+
+        assert!(editor.create_called_with("1"));
+        assert_eq!(board.get_column("todo", None).len(), 1);
+    }
+
+    #[test]
+    fn it_warns_when_a_start_goes_over_a_soft_limit() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+        let mut config = board.get_config();
+        config.limits.insert("doing".to_owned(), 1);
+        board.save_config(config).unwrap();
+
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        start_item("task1".to_owned(), false, &mut board).unwrap();
+        start_item("task2".to_owned(), false, &mut board).unwrap();
+
+        assert_eq!(board.take_over_limit(), vec!(OverLimit{
+            column: "doing".to_owned(),
+            count: 2,
+            limit: 1
+        }));
+    }
+
+    #[test]
+    fn it_refuses_a_start_over_a_strict_limit() {
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+        let mut config = board.get_config();
+        config.limits.insert("doing".to_owned(), 1);
+        config.strict_limits = true;
+        board.save_config(config).unwrap();
+
+        board.create_task("task1", None).unwrap();
+        board.create_task("task2", None).unwrap();
+        start_item("task1".to_owned(), false, &mut board).unwrap();
+        let result = start_item("task2".to_owned(), false, &mut board);
+
+        assert_eq!(
+            result,
+            Err(KanbenError::WipLimitReached{
+                column: "doing".to_owned(),
+                limit: 1
+            })
+        );
+        assert_eq!(board.get("task2").unwrap().column, "todo");
+    }
+
+    #[test]
+    fn deleting_a_task_puts_its_description_in_the_trash() {
+        let mut writer = Cursor::new(vec!());
+        let mut board = BoardMock::new();
+        let mut editor = EditorMock::new();
+        board.set("task", Task{
+            name: "task".to_owned(),
+            column: "todo".to_owned(),
+            description: Some("files/2/description.md".to_owned()),
+            tags: None,
+            id: Some(2),
+            times: Times::default()
+        });
+        editor.set_owned("files/2/description.md");
+
+        delete_item(
+            "task".to_owned(), &mut board, &mut editor, &mut writer
+        ).unwrap();
+
+        assert!(board.delete_called_with("task"));
+        assert!(editor.trash_called_with("files/2/description.md", "2"));
+    }
+
+    #[test]
+    fn positions_start_from_one() {
+        let mut board = BoardMock::new();
+        let item = MoveItemWithin{
+            title: "task".to_owned(),
+            before: None,
+            after: None,
+            position: Some(1)
+        };
+
+        move_within(item, &mut board).unwrap();
+
+        assert!(board.move_called_with("task", &Position::At(0)));
+    }
+
+    #[test]
+    fn theres_no_position_zero() {
+        let mut board = BoardMock::new();
+        let item = MoveItemWithin{
+            title: "task".to_owned(),
+            before: None,
+            after: None,
+            position: Some(0)
+        };
+
+        let result = move_within(item, &mut board);
+
+        assert_eq!(result, Err(KanbenError::InvalidPosition(0)));
+        assert!(!board.move_called_with("task", &Position::At(0)));
+    }
+
+    #[test]
+    fn the_other_task_can_be_given_by_id() {
+        let mut board = BoardMock::new();
+        board.set("other", Task{
+            name: "other".to_owned(),
+            id: Some(7),
+            ..Task::default()
+        });
+        let item = MoveItemWithin{
+            title: "task".to_owned(),
+            before: None,
+            after: Some("7".to_owned()),
+            position: None
+        };
+
+        move_within(item, &mut board).unwrap();
+
+        assert!(board.move_called_with(
+            "task", &Position::After("other".to_owned())
+        ));
+    }
 }
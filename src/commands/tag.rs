@@ -1,5 +1,6 @@
 use std::io::Write;
 use crate::board::BoardAccess;
+use crate::error::KanbenError;
 use crate::opts::Task;
 
 pub fn tag<B: BoardAccess, W: Write>(
@@ -8,20 +9,15 @@ pub fn tag<B: BoardAccess, W: Write>(
     remove: bool,
     board: &mut B,
     writer: &mut W
-) {
-    let task_result = board.get(key);
-
-    if task_result.is_none() {
-        let _ = write!(
-            writer, "No task called '{}' found.\n", key
-        );
-        return;
-    }
-
-    let task = task_result.expect("unable to unwrap task");
+) -> Result<(), KanbenError> {
+    let task = board.get(key)
+        .ok_or(KanbenError::TaskNotFound(key.to_owned()))?;
 
     match tag_label {
-        None => view_tags(task, writer),
+        None => {
+            view_tags(task, writer);
+            Ok(())
+        },
         Some(t) => if remove { 
             remove_tag(task, t, board)
         } else {
@@ -44,21 +40,21 @@ fn add_tag<B: BoardAccess>(
     task: Task,
     tag: String,
     board: &mut B
-) {
+) -> Result<(), KanbenError> {
     let mut new_task = task.clone();
     let mut tag_list = new_task.tags.unwrap_or(vec!());
 
     tag_list.push(tag.clone()); 
     new_task.tags = Some(tag_list);
 
-    board.update(&task.name, new_task);
+    board.update(&task.name, new_task)
 }
 
 fn remove_tag<B: BoardAccess>(
     task: Task,
     tag: String,
     board: &mut B
-) {
+) -> Result<(), KanbenError> {
     let mut new_task = task.clone();
     match new_task.tags {
         Some(l) => {
@@ -68,9 +64,9 @@ fn remove_tag<B: BoardAccess>(
                 .map(|x| x.clone())
                 .collect();
             new_task.tags = Some(new_list);
-            board.update(&task.name, new_task);
+            board.update(&task.name, new_task)
         },
-        None => ()
+        None => Ok(())
     }
 }
 
@@ -80,26 +76,27 @@ mod tests {
     use crate::store::Store;
     use crate::test::StoreMock;
     use crate::board::Board;
-    use crate::opts::Column;
     use std::{str, io::Cursor};
 
     #[test]
     fn it_outputs_the_tasks_tags_when_no_new_tag_is_set() {
         let mut writer = Cursor::new(vec!());
         let mut store = StoreMock::new();
-        let mut task = get_task("task", Column::Todo);
+        let mut task = get_task("task", "todo");
         task.tags = Some(vec!("tag1".to_owned()));
 
-        store.set("task", task);
+        store.set("task", task).unwrap();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        tag("task", None, false, &mut board, &mut writer);
+        tag("task", None, false, &mut board, &mut writer).unwrap();
 
         let output = writer.get_ref();
         assert_eq!(output, b"tag1\n");
@@ -109,18 +106,20 @@ mod tests {
     fn it_outputs_a_message_when_there_are_no_tags() {
         let mut writer = Cursor::new(vec!());
         let mut store = StoreMock::new();
-        let task = get_task("task", Column::Todo);
+        let task = get_task("task", "todo");
 
-        store.set("task", task);
+        store.set("task", task).unwrap();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        tag("task", None, false, &mut board, &mut writer);
+        tag("task", None, false, &mut board, &mut writer).unwrap();
 
         let output = writer.get_ref();
         assert_eq!(output, b"[No tags]\n");
@@ -130,22 +129,24 @@ mod tests {
     fn it_outputs_multiple_tags_with_a_comma_delimit() {
         let mut writer = Cursor::new(vec!());
         let mut store = StoreMock::new();
-        let mut task = get_task("task", Column::Todo);
+        let mut task = get_task("task", "todo");
         task.tags = Some(vec!(
             "tag1".to_owned(),
             "tag2".to_owned()
         ));
 
-        store.set("task", task);
+        store.set("task", task).unwrap();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        tag("task", None, false, &mut board, &mut writer);
+        tag("task", None, false, &mut board, &mut writer).unwrap();
 
         let output = writer.get_ref();
         assert_eq!(output, b"tag1, tag2\n");
@@ -157,17 +158,18 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
 
-        tag("task", None, false, &mut board, &mut writer);
+        let result = tag("task", None, false, &mut board, &mut writer);
 
-        let output = writer.get_ref();
-        assert_eq!(output, b"No task called 'task' found.\n");
+        assert_eq!(result, Err(KanbenError::TaskNotFound("task".to_owned())));
 
     }
 
@@ -177,16 +179,18 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        board.create_task("task", None);
+        board.create_task("task", None).unwrap();
         let tag_label = Some("tag".to_string());
 
-        tag("task", tag_label, false, &mut board, &mut writer);
+        tag("task", tag_label, false, &mut board, &mut writer).unwrap();
 
         let changed_task = store.get("task").unwrap();
 
@@ -202,15 +206,17 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
-        board.create_task("task", None);
+        board.create_task("task", None).unwrap();
         let tag_label = Some("tag".to_string());
 
-        tag("task", tag_label, false, &mut board, &mut writer);
+        tag("task", tag_label, false, &mut board, &mut writer).unwrap();
 
         let tag_index = tag_store.get("tag").unwrap();
 
@@ -226,12 +232,14 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
-        board.create_task("task", None);
+        board.create_task("task", None).unwrap();
         let tag_label = Some("tag".to_string());
 
         tag(
@@ -240,14 +248,14 @@ mod tests {
             false,
             &mut board,
             &mut writer
-        );
+        ).unwrap();
         tag(
             "task",
             tag_label.clone(),
             true,
             &mut board,
             &mut writer
-        );
+        ).unwrap();
 
         let tag_index = tag_store.get("tag").unwrap();
         let expected_list: Vec<String> = vec!();
@@ -264,17 +272,19 @@ mod tests {
         let mut store = StoreMock::new();
         let mut col_store = StoreMock::new();
         let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
         let mut board = Board::new(
             &mut store,
             &mut col_store,
-            &mut tag_store
+            &mut tag_store,
+            &mut config_store
         );
 
-        board.create_task("task", None);
-        board.create_task("task2", None);
+        board.create_task("task", None).unwrap();
+        board.create_task("task2", None).unwrap();
         let tag_label = Some("tag".to_string());
 
-        tag("task", tag_label, false, &mut board, &mut writer);
+        tag("task", tag_label, false, &mut board, &mut writer).unwrap();
 
         let column = board.get_column("todo", None).iter()
             .map(|task| task.name.clone())
@@ -288,12 +298,11 @@ mod tests {
     }
 
 
-    fn get_task(key: &str, column: Column) -> Task {
+    fn get_task(key: &str, column: &str) -> Task {
         Task {
             name: key.to_owned(),
-            column,
-            description: None,
-            tags: None
+            column: column.to_owned(),
+            ..Task::default()
         }
     }
 }
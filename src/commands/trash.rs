@@ -0,0 +1,277 @@
+use std::io::Write;
+use crate::board::{BoardAccess, Trashed};
+use crate::clock::{ago, parse_age};
+use crate::editor::{Editor, file_key};
+use crate::error::KanbenError;
+use crate::opts::TrashCommand;
+use super::descriptions::bring_back_description;
+
+pub fn trash<B: BoardAccess>(
+    cmd: Option<TrashCommand>,
+    now: u64,
+    board: &mut B,
+    editor: &mut dyn Editor,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    match cmd {
+        None | Some(TrashCommand::List) => {
+            list_trash(now, board, writer);
+            Ok(())
+        },
+        Some(TrashCommand::Empty(e)) => empty_trash(
+            e.older_than, now, board, editor, writer
+        )
+    }
+}
+
+// most recently deleted first
+fn list_trash<B: BoardAccess>(now: u64, board: &B, writer: &mut dyn Write) {
+    let trashed = board.trashed();
+    if trashed.is_empty() {
+        let _ = write!(writer, "The trash is empty.\n");
+        return;
+    }
+
+    let lines = trashed.iter().rev().map(|t| format!(
+        "{} ({}, deleted {})\n",
+        t.task.label(), t.task.column, ago(t.deleted, now)
+    )).collect::<String>();
+    let _ = write!(writer, "{}", lines);
+}
+
+// the description files of the tasks that go are purged along
+// with them, there's nothing left to restore them to
+fn empty_trash<B: BoardAccess>(
+    older_than: Option<String>,
+    now: u64,
+    board: &mut B,
+    editor: &mut dyn Editor,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    let age = match older_than {
+        Some(a) => Some(parse_age(&a)?),
+        None => None
+    };
+    let gone = board.trashed().into_iter().filter(|t| match age {
+        Some(a) => now.saturating_sub(t.deleted) >= a,
+        None => true
+    }).collect::<Vec<Trashed>>();
+
+    if gone.is_empty() {
+        let _ = write!(writer, "Nothing to remove from the trash.\n");
+        return Ok(());
+    }
+
+    let keys = gone.iter().map(|t| t.key()).collect::<Vec<String>>();
+    board.empty_trash(&keys)?;
+
+    let files = editor.trashed_files();
+    for t in gone.iter() {
+        let path = editor.trash_path_for(&file_key(&t.task));
+        if !files.contains(&path) {
+            continue;
+        }
+        if let Err(e) = editor.purge(&path) {
+            let _ = write!(writer, "Unable to remove '{}': {}\n", path, e);
+        }
+    }
+    let _ = write!(writer, "Removed {} task(s) from the trash.\n", gone.len());
+    Ok(())
+}
+
+// a title matches the task deleted most recently under it, and
+// an id matches as it does everywhere else
+pub fn restore<B: BoardAccess>(
+    name: String,
+    board: &mut B,
+    editor: &mut dyn Editor,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    let trashed = board.trashed();
+    let id = name.trim().parse::<u64>().ok();
+    let found = trashed.iter().rev()
+        .find(|t| t.task.name == name)
+        .or_else(|| trashed.iter().find(|t| id.is_some() && t.task.id == id))
+        .ok_or(KanbenError::NotInTrash(name.clone()))?;
+
+    let mut task = board.restore(&found.key())?;
+
+    bring_back_description(&mut task, board, editor, writer)?;
+
+    let _ = write!(
+        writer, "Restored '{}' to {}.\n", task.label(), task.column
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opts::{Task, Times, EmptyTrashItem};
+    use crate::test::{BoardMock, EditorMock, Stores};
+    use std::{str, io::Cursor};
+
+    const DAY: u64 = 24 * 60 * 60;
+    const NOW: u64 = 100 * DAY;
+
+    fn trashed(name: &str, id: u64, deleted: u64) -> Trashed {
+        Trashed{
+            task: Task{
+                name: name.to_owned(),
+                column: "todo".to_owned(),
+                description: None,
+                tags: None,
+                id: Some(id),
+                times: Times::default()
+            },
+            position: 0,
+            deleted
+        }
+    }
+
+    fn board() -> BoardMock {
+        let mut board = BoardMock::new();
+        board.set_trashed(vec!(
+            trashed("old", 1, NOW - 40 * DAY),
+            trashed("new", 2, NOW - 2 * DAY)
+        ));
+        board
+    }
+
+    fn run<B: BoardAccess>(
+        cmd: Option<TrashCommand>,
+        board: &mut B,
+        editor: &mut EditorMock
+    ) -> Result<String, KanbenError> {
+        let mut writer = Cursor::new(vec!());
+        trash(cmd, NOW, board, editor, &mut writer)?;
+        Ok(str::from_utf8(writer.get_ref()).unwrap().to_owned())
+    }
+
+    fn empty(older_than: Option<&str>) -> Option<TrashCommand> {
+        Some(TrashCommand::Empty(EmptyTrashItem{
+            older_than: older_than.map(|a| a.to_owned())
+        }))
+    }
+
+    #[test]
+    fn it_lists_the_newest_first() {
+        let output = run(None, &mut board(), &mut EditorMock::new()).unwrap();
+
+        assert_eq!(
+            output,
+            "2 new (todo, deleted 2 days ago)\n\
+            1 old (todo, deleted 5 weeks ago)\n"
+        );
+    }
+
+    #[test]
+    fn it_says_when_the_trash_is_empty() {
+        let output = run(
+            Some(TrashCommand::List), &mut BoardMock::new(), &mut EditorMock::new()
+        ).unwrap();
+
+        assert_eq!(output, "The trash is empty.\n");
+    }
+
+    #[test]
+    fn emptying_can_keep_recent_deletions() {
+        let mut board = board();
+        let mut editor = EditorMock::new();
+        editor.set_trashed(vec!("files/.trash/1/description.md"));
+
+        let output = run(empty(Some("30d")), &mut board, &mut editor).unwrap();
+
+        assert_eq!(board.emptied(), vec!("1"));
+        assert_eq!(editor.purged(), vec!("files/.trash/1/description.md"));
+        assert_eq!(output, "Removed 1 task(s) from the trash.\n");
+    }
+
+    #[test]
+    fn emptying_everything() {
+        let mut board = board();
+
+        run(empty(None), &mut board, &mut EditorMock::new()).unwrap();
+
+        assert_eq!(board.emptied(), vec!("1", "2"));
+    }
+
+    #[test]
+    fn an_age_it_cant_read_empties_nothing() {
+        let mut board = board();
+
+        let result = run(empty(Some("soon")), &mut board, &mut EditorMock::new());
+
+        assert_eq!(result, Err(KanbenError::InvalidAge("soon".to_owned())));
+        assert!(board.emptied().is_empty());
+    }
+
+    #[test]
+    fn a_deleted_task_goes_back_where_it_was() {
+        let mut stores = Stores::new();
+        {
+            let mut board = stores.board();
+            for name in ["a", "b", "c"].iter() {
+                board.create_task(name, Some("tag".to_owned())).unwrap();
+            }
+            board.delete("b").unwrap();
+            assert_eq!(board.trashed()[0].position, 1);
+        }
+        let mut writer = Cursor::new(vec!());
+
+        restore(
+            "b".to_owned(), &mut stores.board(), &mut EditorMock::new(), &mut writer
+        ).unwrap();
+
+        let board = stores.board();
+        let names = board.get_column("todo", None).into_iter()
+            .map(|t| t.name)
+            .collect::<Vec<String>>();
+        assert_eq!(names, vec!("a", "b", "c"));
+        assert_eq!(board.get_column("todo", Some("tag".to_owned())).len(), 3);
+        assert_eq!(board.get("b").unwrap().id, Some(2));
+        assert_eq!(
+            str::from_utf8(writer.get_ref()).unwrap(),
+            "Restored '2 b' to todo.\n"
+        );
+    }
+
+    #[test]
+    fn a_task_can_be_restored_by_id() {
+        let mut board = board();
+        let mut writer = Cursor::new(vec!());
+
+        restore("1".to_owned(), &mut board, &mut EditorMock::new(), &mut writer)
+            .unwrap();
+
+        assert!(board.restore_called_with("1"));
+    }
+
+    #[test]
+    fn its_description_comes_back_with_it() {
+        let mut board = board();
+        let mut editor = EditorMock::new();
+        editor.set_trashed(vec!("files/.trash/2/description.md"));
+        editor.return_from_adopt(Ok("files/2/description.md".to_owned()));
+        let mut writer = Cursor::new(vec!());
+
+        restore("new".to_owned(), &mut board, &mut editor, &mut writer)
+            .unwrap();
+
+        assert!(editor.adopt_called_with("files/.trash/2/description.md", "2"));
+        assert!(board.set_description_called_with(
+            "new", "files/2/description.md"
+        ));
+    }
+
+    #[test]
+    fn only_tasks_in_the_trash_can_be_restored() {
+        let mut writer = Cursor::new(vec!());
+
+        let result = restore(
+            "gone".to_owned(), &mut board(), &mut EditorMock::new(), &mut writer
+        );
+
+        assert_eq!(result, Err(KanbenError::NotInTrash("gone".to_owned())));
+    }
+}
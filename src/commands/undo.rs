@@ -0,0 +1,256 @@
+use std::io::Write;
+use crate::board::{BoardAccess, Entry, Change};
+use crate::clock::ago;
+use crate::editor::Editor;
+use crate::opts::Task;
+use crate::error::KanbenError;
+use super::descriptions::{trash_description, bring_back_description};
+
+// how many changes `kanben undo --list` shows
+const LIST_LENGTH: usize = 20;
+
+pub fn undo<B: BoardAccess>(
+    times: usize,
+    list: bool,
+    now: u64,
+    board: &mut B,
+    editor: &mut dyn Editor,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    if list {
+        list_journal(now, board, writer);
+        return Ok(());
+    }
+
+    for done in 0..times {
+        match board.undo()? {
+            Some(entry) => {
+                follow_descriptions(&entry, true, board, editor, writer)?;
+                let _ = write!(writer, "Undone: {}\n", entry.name);
+            },
+            None => {
+                if done == 0 {
+                    let _ = write!(writer, "Nothing to undo.\n");
+                }
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn redo<B: BoardAccess>(
+    board: &mut B,
+    editor: &mut dyn Editor,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    match board.redo()? {
+        Some(entry) => {
+            follow_descriptions(&entry, false, board, editor, writer)?;
+            let _ = write!(writer, "Redone: {}\n", entry.name);
+        },
+        None => {
+            let _ = write!(writer, "Nothing to redo.\n");
+        }
+    }
+    Ok(())
+}
+
+// most recent first, with whatever's been undone marked
+fn list_journal<B: BoardAccess>(now: u64, board: &B, writer: &mut dyn Write) {
+    let journal = board.journal();
+    if journal.is_empty() {
+        let _ = write!(writer, "Nothing to undo.\n");
+        return;
+    }
+
+    let lines = journal.iter().rev().take(LIST_LENGTH).map(|e| format!(
+        "{} ({}{})\n",
+        e.name,
+        ago(e.at, now),
+        if e.undone { ", undone" } else { "" }
+    )).collect::<String>();
+    let _ = write!(writer, "{}", lines);
+}
+
+// tasks that go take their description to the trash, as they do
+// when deleted, and tasks that come back bring theirs out again
+fn follow_descriptions<B: BoardAccess>(
+    entry: &Entry,
+    undo: bool,
+    board: &mut B,
+    editor: &mut dyn Editor,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    for change in entry.tasks.iter() {
+        follow_description(change, undo, board, editor, writer)?;
+    }
+    Ok(())
+}
+
+fn follow_description<B: BoardAccess>(
+    change: &Change<Task>,
+    undo: bool,
+    board: &mut B,
+    editor: &mut dyn Editor,
+    writer: &mut dyn Write
+) -> Result<(), KanbenError> {
+    match change.sides(undo) {
+        (Some(task), None) => trash_description(task, editor, writer),
+        (None, Some(task)) => {
+            let mut task = task.clone();
+            bring_back_description(&mut task, board, editor, writer)?;
+        },
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opts::Times;
+    use crate::test::{BoardMock, EditorMock};
+    use std::{str, io::Cursor};
+
+    const NOW: u64 = 10 * 60 * 60;
+
+    fn task(name: &str) -> Task {
+        Task{
+            name: name.to_owned(),
+            column: "todo".to_owned(),
+            description: Some(format!("files/{}/description.md", name)),
+            tags: None,
+            id: None,
+            times: Times::default()
+        }
+    }
+
+    fn entry(
+        name: &str,
+        at: u64,
+        undone: bool,
+        tasks: Vec<Change<Task>>
+    ) -> Entry {
+        Entry{
+            name: name.to_owned(),
+            at,
+            undone,
+            tasks,
+            columns: vec!(),
+            tags: vec!(),
+            config: vec!(),
+            trash: vec!()
+        }
+    }
+
+    fn board() -> BoardMock {
+        let mut board = BoardMock::new();
+        board.set_journal(vec!(
+            entry("add 'a'", NOW - 3 * 60 * 60, false, vec!(Change{
+                key: "a".to_owned(),
+                before: None,
+                after: Some(task("a"))
+            })),
+            entry("delete 'b'", NOW - 5 * 60, false, vec!(Change{
+                key: "b".to_owned(),
+                before: Some(task("b")),
+                after: None
+            })),
+            entry("move 'c' to done", NOW, true, vec!())
+        ));
+        board
+    }
+
+    fn run_undo(
+        times: usize,
+        list: bool,
+        board: &mut BoardMock,
+        editor: &mut EditorMock
+    ) -> String {
+        let mut writer = Cursor::new(vec!());
+        undo(times, list, NOW, board, editor, &mut writer).unwrap();
+        str::from_utf8(writer.get_ref()).unwrap().to_owned()
+    }
+
+    #[test]
+    fn it_undoes_the_latest_change() {
+        let output = run_undo(1, false, &mut board(), &mut EditorMock::new());
+
+        assert_eq!(output, "Undone: delete 'b'\n");
+    }
+
+    #[test]
+    fn it_can_undo_several_changes() {
+        let output = run_undo(5, false, &mut board(), &mut EditorMock::new());
+
+        assert_eq!(output, "Undone: delete 'b'\nUndone: add 'a'\n");
+    }
+
+    #[test]
+    fn it_says_when_theres_nothing_to_undo() {
+        let output = run_undo(
+            1, false, &mut BoardMock::new(), &mut EditorMock::new()
+        );
+
+        assert_eq!(output, "Nothing to undo.\n");
+    }
+
+    #[test]
+    fn a_task_that_comes_back_brings_its_description() {
+        let mut editor = EditorMock::new();
+        editor.set_trashed(vec!("files/.trash/b/description.md"));
+        editor.return_from_adopt(Ok("files/b/description.md".to_owned()));
+
+        run_undo(1, false, &mut board(), &mut editor);
+
+        assert!(editor.adopt_called_with("files/.trash/b/description.md", "b"));
+    }
+
+    #[test]
+    fn a_task_that_goes_takes_its_description_to_the_trash() {
+        let mut editor = EditorMock::new();
+        editor.set_owned("files/a/description.md");
+
+        run_undo(2, false, &mut board(), &mut editor);
+
+        assert!(editor.trash_called_with("files/a/description.md", "a"));
+    }
+
+    #[test]
+    fn it_redoes_what_was_undone() {
+        let mut writer = Cursor::new(vec!());
+
+        redo(&mut board(), &mut EditorMock::new(), &mut writer).unwrap();
+
+        assert_eq!(
+            str::from_utf8(writer.get_ref()).unwrap(),
+            "Redone: move 'c' to done\n"
+        );
+    }
+
+    #[test]
+    fn it_says_when_theres_nothing_to_redo() {
+        let mut writer = Cursor::new(vec!());
+
+        redo(&mut BoardMock::new(), &mut EditorMock::new(), &mut writer)
+            .unwrap();
+
+        assert_eq!(
+            str::from_utf8(writer.get_ref()).unwrap(),
+            "Nothing to redo.\n"
+        );
+    }
+
+    #[test]
+    fn it_lists_the_latest_changes_first() {
+        let output = run_undo(1, true, &mut board(), &mut EditorMock::new());
+
+        assert_eq!(
+            output,
+            "move 'c' to done (just now, undone)\n\
+            delete 'b' (5 minutes ago)\n\
+            add 'a' (3 hours ago)\n"
+        );
+    }
+}
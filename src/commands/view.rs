@@ -1,27 +1,22 @@
 use std::io::Write;
 use crate::board::BoardAccess;
+use crate::clock::{ago, date};
 use crate::file::Reader;
+use crate::error::KanbenError;
+use crate::opts::Times;
+use crate::store::body;
 use colored::*;
 use termimad;
 
 pub fn view_item<B: BoardAccess>(
     key: String,
+    now: u64,
     board: &mut B,
     writer: &mut dyn Write,
     reader: &dyn Reader
-) {
-    let task_result = board.get(&key);
-
-    if task_result.is_none() {
-        write!(
-            writer,
-            "No task named '{}' found.\n",
-            &key
-        ).unwrap();
-        return;
-    }
-
-    let task = task_result.unwrap();
+) -> Result<(), KanbenError> {
+    let task = board.get(&key)
+        .ok_or(KanbenError::TaskNotFound(key.clone()))?;
 
     if task.tags.is_some() {
         let tags = task.tags.unwrap()
@@ -35,37 +30,61 @@ pub fn view_item<B: BoardAccess>(
         ).unwrap();
     }
 
+    if !task.times.is_empty() {
+        write!(writer, "{}\n", times(&task.times, now)).unwrap();
+    }
+
     if task.description.is_none() {
         write!(
             writer,
             "Empty description\n",
         ).unwrap();
-        return;
+        return Ok(());
     }
 
-    let read_result = reader.read(&task.description.unwrap());
+    let file_contents = reader.read(&task.description.unwrap())
+        .ok_or(KanbenError::UnreadableDescription(key))?;
 
-    if read_result.is_none() {
+    // markdown boards keep the task's details above the
+    // description, which aren't shown here
+    let description = body(&file_contents);
+    if description.trim().is_empty() {
         write!(
             writer,
-            "Error loading file for '{}'\n",
-            &key
+            "Empty description\n",
         ).unwrap();
-        return;
+        return Ok(());
     }
 
-    let file_contents = read_result.unwrap();
-    let parsed_markdown = termimad::inline(&file_contents);
+    let parsed_markdown = termimad::inline(description);
     write!(writer, "{}\n", parsed_markdown).unwrap();
+    Ok(())
+}
+
+// one line for each time that's been recorded
+fn times(times: &Times, now: u64) -> String {
+    let rows = [
+        ("created", times.created),
+        ("started", times.started),
+        ("completed", times.completed),
+        ("moved", times.moved)
+    ];
+    rows.iter().filter_map(|(label, at)| at.map(|at| format!(
+        "{:<11}{} ({})\n", label, date(at), ago(at, now)
+    ))).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::opts::{Task, Column};
+    use crate::opts::Task;
     use crate::test::{BoardMock, ReaderMock};
     use std::{str, io::Cursor};
 
+    const DAY: u64 = 24 * 60 * 60;
+    // 2024-03-09 16:00
+    const NOW: u64 = 1_710_000_000;
+
     #[test]
     fn it_outputs_to_writer_from_reader() {
         let mut writer = Cursor::new(vec!());
@@ -73,7 +92,7 @@ mod tests {
         let mut reader = ReaderMock::new();
         let name = "test".to_string();
 
-        let mut task = get_task(&name, Column::Todo);
+        let mut task = get_task(&name, "todo");
         task.description = Some("test".to_owned());
 
         board.set(&name, task);
@@ -81,15 +100,46 @@ mod tests {
 
         view_item(
             name,
+            NOW,
             &mut board,
             &mut writer,
             &reader
-        );
+        ).unwrap();
 
         let output = writer.get_ref();
         assert_eq!(output, b"abcdef\n");
     }
 
+    #[test]
+    fn it_shows_when_things_happened_to_the_task() {
+        let mut writer = Cursor::new(vec!());
+        let mut board = BoardMock::new();
+        let mut task = get_task("test", "doing");
+        task.times = Times{
+            created: Some(NOW - 10 * DAY),
+            started: Some(NOW - 3 * DAY),
+            completed: None,
+            moved: Some(NOW - 3 * DAY)
+        };
+        board.set("test", task);
+
+        view_item(
+            "test".to_owned(),
+            NOW,
+            &mut board,
+            &mut writer,
+            &ReaderMock::new()
+        ).unwrap();
+
+        assert_eq!(
+            str::from_utf8(writer.get_ref()).unwrap(),
+            "created    2024-02-28 16:00 (1 week ago)\n\
+            started    2024-03-06 16:00 (3 days ago)\n\
+            moved      2024-03-06 16:00 (3 days ago)\n\
+            \nEmpty description\n"
+        );
+    }
+
     #[test]
     fn it_opens_the_file_in_description() {
         let mut writer = Cursor::new(vec!());
@@ -97,7 +147,7 @@ mod tests {
         let mut reader = ReaderMock::new();
         let name = "test";
 
-        let mut task = get_task(name, Column::Todo);
+        let mut task = get_task(name, "todo");
         task.description = Some(name.to_string());
 
         board.set(name, task);
@@ -105,11 +155,40 @@ mod tests {
 
         view_item(
             name.to_string(),
+            NOW,
             &mut board,
             &mut writer,
             &reader
+        ).unwrap();
+
+        let output = writer.get_ref();
+        assert_eq!(output, b"file contents\n");
+    }
+
+    #[test]
+    fn it_leaves_out_the_front_matter_of_a_task_file() {
+        let mut writer = Cursor::new(vec!());
+        let mut board = BoardMock::new();
+        let mut reader = ReaderMock::new();
+        let name = "test";
+
+        let mut task = get_task(name, "todo");
+        task.description = Some("test.md".to_string());
+
+        board.set(name, task);
+        reader.return_from_read_when(
+            "test.md",
+            "---\nname: test\ncolumn: todo\n---\nfile contents"
         );
 
+        view_item(
+            name.to_string(),
+            NOW,
+            &mut board,
+            &mut writer,
+            &reader
+        ).unwrap();
+
         let output = writer.get_ref();
         assert_eq!(output, b"file contents\n");
     }
@@ -121,15 +200,18 @@ mod tests {
         let reader = ReaderMock::new();
         let name = "test";
 
-        view_item(
+        let result = view_item(
             name.to_string(),
+            NOW,
             &mut board,
             &mut writer,
             &reader
         );
 
-        let output = writer.get_ref();
-        assert_eq!(output, b"No task named 'test' found.\n");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "No task named 'test' found."
+        );
     }
 
     #[test]
@@ -139,16 +221,17 @@ mod tests {
         let reader = ReaderMock::new();
         let name = "test";
 
-        let task = get_task(name, Column::Todo);
+        let task = get_task(name, "todo");
 
         board.set(name, task);
 
         view_item(
             name.to_string(),
+            NOW,
             &mut board,
             &mut writer,
             &reader
-        );
+        ).unwrap();
 
         let output = writer.get_ref();
         assert_eq!(output, b"Empty description\n");
@@ -162,21 +245,24 @@ mod tests {
         let mut reader = ReaderMock::new();
         let name = "test";
 
-        let mut task = get_task(name, Column::Todo);
+        let mut task = get_task(name, "todo");
         task.description = Some(name.to_string());
 
         board.set(name, task);
         reader.return_from_read_when("fakeroute", "file contents");
 
-        view_item(
+        let result = view_item(
             name.to_string(),
+            NOW,
             &mut board,
             &mut writer,
             &reader
         );
 
-        let output = writer.get_ref();
-        assert_eq!(output, b"Error loading file for 'test'\n");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Error loading file for 'test'"
+        );
     }
 
     #[test]
@@ -186,7 +272,7 @@ mod tests {
         let mut reader = ReaderMock::new();
         let name = "test";
 
-        let mut task = get_task(name, Column::Todo);
+        let mut task = get_task(name, "todo");
         task.description = Some(name.to_owned());
         task.tags = Some(vec!("tag1".to_owned()));
 
@@ -195,10 +281,11 @@ mod tests {
 
         view_item(
             name.to_string(),
+            NOW,
             &mut board,
             &mut writer,
             &reader
-        );
+        ).unwrap();
         let output = writer.get_ref();
         let str_output = str::from_utf8(&output).unwrap();
         let expected = format!(
@@ -210,12 +297,11 @@ mod tests {
 
     }
 
-    fn get_task(key: &str, column: Column) -> Task {
+    fn get_task(key: &str, column: &str) -> Task {
         Task {
             name: key.to_owned(),
-            column,
-            description: None,
-            tags: None
+            column: column.to_owned(),
+            ..Task::default()
         }
     }
 }
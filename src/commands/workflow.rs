@@ -0,0 +1,197 @@
+use std::io::Write;
+use crate::board::{BoardAccess, Transition, column_label};
+use crate::opts::WorkflowCommand;
+use crate::error::KanbenError;
+
+pub fn workflow<B: BoardAccess, W: Write>(
+    cmd: Option<WorkflowCommand>,
+    board: &mut B,
+    writer: &mut W
+) -> Result<(), KanbenError> {
+    let mut config = board.get_config();
+
+    match cmd {
+        None => {
+            view_workflow(config.workflow, writer);
+            return Ok(());
+        },
+        Some(WorkflowCommand::Allow(t)) => {
+            let transition = Transition::new(
+                &column_label(&t.from),
+                &column_label(&t.to)
+            );
+            let mut workflow = config.workflow_or_all();
+            if !workflow.contains(&transition) {
+                workflow.push(transition);
+            }
+            config.workflow = Some(workflow);
+        },
+        Some(WorkflowCommand::Forbid(t)) => {
+            let transition = Transition::new(
+                &column_label(&t.from),
+                &column_label(&t.to)
+            );
+            let mut workflow = config.workflow_or_all();
+            workflow.retain(|x| x != &transition);
+            config.workflow = Some(workflow);
+        },
+        Some(WorkflowCommand::Linear) => {
+            config.workflow = Some(config.linear_workflow());
+        },
+        Some(WorkflowCommand::Clear) => {
+            config.workflow = None;
+        }
+    }
+
+    board.save_config(config)
+}
+
+fn view_workflow<W: Write>(
+    workflow: Option<Vec<Transition>>,
+    writer: &mut W
+) {
+    let output = match workflow {
+        None => "[Any move is allowed]".to_owned(),
+        Some(w) => w.iter()
+            .map(|t| format!("{} -> {}", t.from, t.to))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    let _ = write!(writer, "{}\n", output);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opts::TransitionItem;
+    use crate::test::StoreMock;
+    use crate::board::Board;
+    use std::{str, io::Cursor};
+
+    #[test]
+    fn it_outputs_a_message_when_there_is_no_workflow() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        workflow(None, &mut board, &mut writer).unwrap();
+
+        let output = writer.get_ref();
+        assert_eq!(output, b"[Any move is allowed]\n");
+    }
+
+    #[test]
+    fn it_can_set_a_linear_workflow() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        workflow(Some(WorkflowCommand::Linear), &mut board, &mut writer).unwrap();
+        workflow(None, &mut board, &mut writer).unwrap();
+
+        let output = writer.get_ref();
+        let str_output = str::from_utf8(&output).unwrap();
+        assert_eq!(str_output, "todo -> doing\ndoing -> done\n");
+    }
+
+    #[test]
+    fn it_can_allow_a_move_back_a_column() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        workflow(Some(WorkflowCommand::Linear), &mut board, &mut writer).unwrap();
+        workflow(
+            Some(WorkflowCommand::Allow(TransitionItem{
+                from: "Doing".to_owned(),
+                to: "Todo".to_owned()
+            })),
+            &mut board,
+            &mut writer
+        ).unwrap();
+
+        assert!(board.get_config().allows("doing", "todo"));
+    }
+
+    #[test]
+    fn forbidding_a_move_keeps_every_other_move() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        workflow(
+            Some(WorkflowCommand::Forbid(TransitionItem{
+                from: "todo".to_owned(),
+                to: "done".to_owned()
+            })),
+            &mut board,
+            &mut writer
+        ).unwrap();
+
+        let config = board.get_config();
+        assert!(!config.allows("todo", "done"));
+        assert!(config.allows("todo", "doing"));
+        assert!(config.allows("done", "todo"));
+    }
+
+    #[test]
+    fn it_outputs_a_message_for_unknown_columns() {
+        let mut writer = Cursor::new(vec!());
+        let mut store = StoreMock::new();
+        let mut col_store = StoreMock::new();
+        let mut tag_store = StoreMock::new();
+        let mut config_store = StoreMock::new();
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        );
+
+        let result = workflow(
+            Some(WorkflowCommand::Allow(TransitionItem{
+                from: "todo".to_owned(),
+                to: "review".to_owned()
+            })),
+            &mut board,
+            &mut writer
+        );
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "No column named 'review' found."
+        );
+    }
+}
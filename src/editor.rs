@@ -1,15 +1,35 @@
+use walkdir::WalkDir;
 use std::{
-    path::PathBuf,
-    fs, fs::File,
-    process::Command,
+    env,
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+    fs, fs::{File, OpenOptions},
+    process::{self, Command},
 };
+use crate::error::KanbenError;
+use crate::opts::Task;
 
-
+// descriptions are found by key rather than by path, see `file_key`
 pub trait Editor {
-    fn open(&mut self, path: &str);
-    fn create(&mut self, key: &str) -> Result<String, ()>;
+    fn open(&mut self, path: &str) -> Result<(), KanbenError>;
+    fn create(&mut self, key: &str) -> Result<String, KanbenError>;
+    fn path_for(&self, key: &str) -> String;
+    fn owns(&self, path: &str) -> bool;
+    fn adopt(&mut self, path: &str, key: &str) -> Result<String, KanbenError>;
+    fn edit_text(&mut self, name: &str, text: &str) -> Result<String, KanbenError>;
+    fn save(
+        &mut self, path: Option<&str>, key: &str, text: &str
+    ) -> Result<String, KanbenError>;
+    fn trash(&mut self, path: &str, key: &str) -> Result<String, KanbenError>;
+    fn trash_path_for(&self, key: &str) -> String;
+    fn stored_files(&self) -> Vec<String>;
+    fn trashed_files(&self) -> Vec<String>;
+    fn purge(&mut self, path: &str) -> Result<(), KanbenError>;
 }
 
+// descriptions of deleted tasks wait in here until `kanben gc`
+const TRASH: &str = ".trash";
+
 pub struct FileEditor {
     default_editor: Option<String>,
     root_path: String
@@ -23,42 +43,296 @@ impl FileEditor {
         }
     }
 
-    fn open_editor(&self, path: &str) {
-        if self.default_editor.as_ref().is_none() {
-            // error
-            return
-        }
-
-        let editor = self.default_editor.as_ref().unwrap();
+    fn open_editor(&self, path: &str) -> Result<(), KanbenError> {
+        let editor = self.default_editor.as_ref()
+            .ok_or(KanbenError::NoEditor)?;
 
         Command::new(&editor)
             .arg(path)
             .status()
-            .expect("Something went wrong");
+            .map(|_| ())
+            .map_err(|e| KanbenError::Editor(e.to_string()))
+    }
+
+    // each description gets a folder of its own, named by key
+    fn description_path(&self, key: &str) -> PathBuf {
+        let mut path = PathBuf::new();
+        path.push(&self.root_path);
+        path.push(key);
+        path.push("description.md");
+        path
+    }
+
+    fn prepare(&self, key: &str) -> Result<PathBuf, KanbenError> {
+        let path = self.description_path(key);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| KanbenError::Editor(e.to_string()))?;
+        }
+        Ok(path)
+    }
+
+    // the folder a file leaves behind is tidied up if it's empty
+    fn move_in(&self, path: &str, key: &str) -> Result<String, KanbenError> {
+        let new_path = self.description_path(key);
+        if new_path.exists() {
+            return Err(KanbenError::Editor(
+                format!("{} already exists", new_path.display())
+            ));
+        }
+
+        self.prepare(key)?;
+        fs::rename(path, &new_path)
+            .map_err(|e| KanbenError::Editor(e.to_string()))?;
+        self.tidy(path);
+        to_string(&new_path)
+    }
+
+    fn tidy(&self, path: &str) {
+        if let Some(old_dir) = Path::new(path).parent() {
+            if old_dir != Path::new(&self.root_path)
+                && old_dir != self.trash_path().as_path() {
+                let _ = fs::remove_dir(old_dir);
+            }
+        }
+    }
+
+    fn trash_path(&self) -> PathBuf {
+        Path::new(&self.root_path).join(TRASH)
+    }
+
+    // every file under `dir`, leaving out anything under `skip`
+    fn files_in(&self, dir: &Path, skip: Option<&Path>) -> Vec<String> {
+        let mut files: Vec<String> = WalkDir::new(dir).into_iter()
+            .filter_entry(|e| Some(e.path()) != skip)
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.path().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        files
+    }
+}
+
+// where a task's files are kept. ids never change, and are
+// safe to use as a file name whatever the title is. a task
+// from before ids falls back to a tidied up title
+pub fn file_key(task: &Task) -> String {
+    match task.id {
+        Some(id) => id.to_string(),
+        None => task.name.to_lowercase().chars().map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        }).collect()
     }
 }
 
+fn to_string(path: &Path) -> Result<String, KanbenError> {
+    path.to_str()
+        .map(|p| p.to_string())
+        .ok_or(KanbenError::Editor(format!("bad path {:?}", path)))
+}
+
 impl Editor for FileEditor {
-    fn open(&mut self, path: &str) {
-        self.open_editor(path);
+    fn open(&mut self, path: &str) -> Result<(), KanbenError> {
+        self.open_editor(path)
     }
 
-    fn create(&mut self, key: &str) -> Result<String, ()> {
-        let mut path = PathBuf::new();
-        path.push(&self.root_path);
-        path.push(key);
-        fs::create_dir_all(&self.root_path)
-            .expect("unable to create files dir");
-        File::create(&path).expect("Could not create file");
+    fn create(&mut self, key: &str) -> Result<String, KanbenError> {
+        if self.default_editor.is_none() {
+            return Err(KanbenError::NoEditor);
+        }
+
+        // a file left behind by an earlier attempt is kept
+        let path = self.prepare(key)?;
+        if !path.exists() {
+            File::create(&path)
+                .map_err(|e| KanbenError::Editor(e.to_string()))?;
+        }
+        let output_path = to_string(&path)?;
+        self.open_editor(&output_path)?;
+        Ok(output_path)
+    }
+
+    fn path_for(&self, key: &str) -> String {
+        self.description_path(key).to_string_lossy().into_owned()
+    }
+
+    // anything kanben made itself, in either layout. a description
+    // living anywhere else is left where it is. a path that climbs
+    // back out with `..` could lead anywhere, so isn't one
+    fn owns(&self, path: &str) -> bool {
+        let path = Path::new(path);
+        path.starts_with(&self.root_path)
+            && !path.components().any(|c| c == Component::ParentDir)
+    }
+
+    // moves a file in to the place `key` keeps its description.
+    // the file can come from another board's editor
+    fn adopt(
+        &mut self,
+        path: &str,
+        key: &str
+    ) -> Result<String, KanbenError> {
+        self.move_in(path, key)
+    }
+
+    // the text goes in a temporary file, and whatever's in it
+    // when the editor closes is handed back
+    fn edit_text(
+        &mut self,
+        _name: &str,
+        text: &str
+    ) -> Result<String, KanbenError> {
+        if self.default_editor.is_none() {
+            return Err(KanbenError::NoEditor);
+        }
+
+        let (path, mut file) = temp_file()?;
+        let written = file.write_all(text.as_bytes());
+        drop(file);
+        if let Err(e) = written {
+            let _ = fs::remove_file(&path);
+            return Err(KanbenError::Editor(e.to_string()));
+        }
         let output_path = path.to_str()
-            .expect("unable to get path");
-        self.open_editor(output_path);
-        Ok(output_path.to_string())
+            .ok_or(KanbenError::Editor(format!("bad path {:?}", path)))?;
+
+        let result = self.open_editor(output_path).and_then(|_| {
+            fs::read_to_string(&path)
+                .map_err(|e| KanbenError::Editor(e.to_string()))
+        });
+        let _ = fs::remove_file(&path);
+        result
+    }
+
+    // writes a description without opening the editor, making
+    // a new file for it when the task doesn't have one yet
+    fn save(
+        &mut self,
+        path: Option<&str>,
+        key: &str,
+        text: &str
+    ) -> Result<String, KanbenError> {
+        let path = match path {
+            Some(p) => PathBuf::from(p),
+            None => self.prepare(key)?
+        };
+
+        fs::write(&path, text)
+            .map_err(|e| KanbenError::Editor(e.to_string()))?;
+        to_string(&path)
+    }
+
+    // anything already in the trash under the same key is
+    // replaced, it belonged to a task that's long gone
+    fn trash(&mut self, path: &str, key: &str) -> Result<String, KanbenError> {
+        let dir = self.trash_path().join(key);
+        fs::create_dir_all(&dir)
+            .map_err(|e| KanbenError::Editor(e.to_string()))?;
+        let trashed = dir.join("description.md");
+        fs::rename(path, &trashed)
+            .map_err(|e| KanbenError::Editor(e.to_string()))?;
+        self.tidy(path);
+        to_string(&trashed)
+    }
+
+    fn trash_path_for(&self, key: &str) -> String {
+        self.trash_path().join(key).join("description.md")
+            .to_string_lossy().into_owned()
+    }
+
+    fn stored_files(&self) -> Vec<String> {
+        let trash = self.trash_path();
+        self.files_in(Path::new(&self.root_path), Some(trash.as_path()))
     }
+
+    fn trashed_files(&self) -> Vec<String> {
+        self.files_in(&self.trash_path(), None)
+    }
+
+    // only ever removes files kanben keeps itself
+    fn purge(&mut self, path: &str) -> Result<(), KanbenError> {
+        if !self.owns(path) {
+            return Err(KanbenError::Editor(
+                format!("{} isn't a kanben file", path)
+            ));
+        }
+
+        fs::remove_file(path)
+            .map_err(|e| KanbenError::Editor(e.to_string()))?;
+        self.tidy(path);
+        Ok(())
+    }
+}
+
+// a new file only this user can read. anything already in the
+// temp directory under the same name, a link someone's left
+// there say, is never written through
+fn temp_file() -> Result<(PathBuf, File), KanbenError> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    for attempt in 0..100 {
+        let path = env::temp_dir()
+            .join(format!("kanben-{}-{}.md", process::id(), attempt));
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(KanbenError::Editor(e.to_string()))
+        }
+    }
+    Err(KanbenError::Editor("unable to make a temporary file".to_owned()))
 }
 
 #[cfg(test)]
 mod tests {
-//    use super::*;
+    use super::*;
 
+    fn task(name: &str, id: Option<u64>) -> Task {
+        Task{
+            name: name.to_owned(),
+            id,
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn files_are_kept_by_id() {
+        assert_eq!(file_key(&task("../notes/A Task", Some(12))), "12");
+    }
+
+    #[test]
+    fn tasks_without_an_id_get_a_safe_name() {
+        assert_eq!(file_key(&task("../notes/A Task", None)), "___notes_a_task");
+    }
+
+    #[test]
+    fn a_temporary_file_is_never_one_already_there() {
+        let taken = env::temp_dir()
+            .join(format!("kanben-{}-0.md", process::id()));
+        fs::write(&taken, "someone else's").unwrap();
+
+        let (path, _) = temp_file().unwrap();
+
+        assert_ne!(path, taken);
+        assert_eq!(fs::read_to_string(&taken).unwrap(), "someone else's");
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&taken);
+    }
+
+    #[test]
+    fn paths_that_climb_out_of_the_files_arent_kanbens() {
+        let mut editor = FileEditor::new(None, "/kanben/files".to_owned());
+
+        assert!(editor.owns("/kanben/files/12/description.md"));
+        assert!(!editor.owns("/kanben/files/../../etc/passwd"));
+        assert!(!editor.owns("/notes/12.md"));
+        assert!(editor.purge("/kanben/files/../../etc/passwd").is_err());
+    }
 }
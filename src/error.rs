@@ -0,0 +1,247 @@
+use std::fmt;
+
+// every failure kanben knows how to report. each kind of
+// failure exits with its own code so scripts can tell them apart
+#[derive(Debug, PartialEq, Clone)]
+pub enum KanbenError {
+    ColumnExists(String),
+    ColumnNotFound(String),
+    ColumnNotEmpty(String),
+    TooFewColumns,
+    TaskNotFound(String),
+    TaskExists(String),
+    InvalidTitle,
+    InvalidLimitMode(String),
+    InvalidSettings(String),
+    #[cfg(not(feature = "sqlite"))]
+    SqliteUnavailable,
+    IllegalMove{ from: String, to: String },
+    WipLimitReached{ column: String, limit: usize },
+    NoEditor,
+    Editor(String),
+    UnreadableDescription(String),
+    Store(String),
+    Archive(String),
+    Web(String),
+    BoardInconsistent(usize),
+    BoardExists(String),
+    BoardNotFound(String),
+    BoardNameTaken(String),
+    InvalidBoardName(String),
+    BoardNotEmpty(String),
+    MainBoard,
+    SameBoard(String),
+    NotInColumn{ task: String, column: String },
+    InvalidPlan(String),
+    InvalidDetails(String),
+    InvalidAge(String),
+    InvalidPosition(usize),
+    NotInTrash(String),
+    ChangedSince(String),
+    SchemaTooNew{ found: u32, supported: u32 },
+    NeedsUpgrade(String),
+}
+
+impl KanbenError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            KanbenError::InvalidTitle
+                | KanbenError::InvalidLimitMode(_)
+                | KanbenError::InvalidSettings(_)
+                | KanbenError::InvalidBoardName(_)
+                | KanbenError::InvalidPlan(_)
+                | KanbenError::InvalidDetails(_)
+                | KanbenError::InvalidAge(_)
+                | KanbenError::InvalidPosition(_) => 2,
+            #[cfg(not(feature = "sqlite"))]
+            KanbenError::SqliteUnavailable => 2,
+            KanbenError::TaskNotFound(_)
+                | KanbenError::ColumnNotFound(_)
+                | KanbenError::BoardNotFound(_)
+                | KanbenError::NotInColumn{ .. }
+                | KanbenError::NotInTrash(_) => 3,
+            KanbenError::TaskExists(_)
+                | KanbenError::ColumnExists(_)
+                | KanbenError::BoardExists(_)
+                | KanbenError::BoardNameTaken(_) => 4,
+            KanbenError::ColumnNotEmpty(_)
+                | KanbenError::TooFewColumns
+                | KanbenError::IllegalMove{ .. }
+                | KanbenError::WipLimitReached{ .. }
+                | KanbenError::BoardNotEmpty(_)
+                | KanbenError::MainBoard
+                | KanbenError::SameBoard(_)
+                | KanbenError::ChangedSince(_) => 5,
+            KanbenError::NoEditor
+                | KanbenError::Editor(_)
+                | KanbenError::UnreadableDescription(_) => 6,
+            KanbenError::Store(_) => 7,
+            KanbenError::Archive(_)
+                | KanbenError::Web(_) => 8,
+            KanbenError::BoardInconsistent(_) => 9,
+            KanbenError::SchemaTooNew{ .. }
+                | KanbenError::NeedsUpgrade(_) => 10,
+        }
+    }
+}
+
+impl fmt::Display for KanbenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KanbenError::ColumnExists(c) => write!(
+                f, "A column named '{}' already exists.", c
+            ),
+            KanbenError::ColumnNotFound(c) => write!(
+                f, "No column named '{}' found.", c
+            ),
+            KanbenError::ColumnNotEmpty(c) => write!(
+                f, "Column '{}' still has tasks in it.", c
+            ),
+            KanbenError::TooFewColumns => write!(
+                f, "A board needs at least two columns."
+            ),
+            KanbenError::TaskNotFound(t) => write!(
+                f, "No task named '{}' found.", t
+            ),
+            KanbenError::TaskExists(t) => write!(
+                f, "A task named '{}' already exists.", t
+            ),
+            KanbenError::InvalidTitle => write!(
+                f, "A task needs a name."
+            ),
+            KanbenError::InvalidLimitMode(_) => write!(
+                f, "Limit mode should be 'strict' or 'soft'."
+            ),
+            KanbenError::InvalidSettings(e) => write!(
+                f, "Unable to read settings.yml: {}", e
+            ),
+            #[cfg(not(feature = "sqlite"))]
+            KanbenError::SqliteUnavailable => write!(
+                f,
+                "This kanben was built without SQLite support. \
+                Install it with `--features sqlite` to use it."
+            ),
+            KanbenError::IllegalMove{ from, to } => write!(
+                f,
+                "The workflow doesn't allow moving from '{}' to '{}'. \
+                `start` and `complete` take --force to move it anyway.",
+                from, to
+            ),
+            KanbenError::WipLimitReached{ column, limit } => write!(
+                f,
+                "Column '{}' is at its limit of {} tasks. \
+                `start` and `complete` take --force to move it anyway.",
+                column, limit
+            ),
+            KanbenError::NoEditor => write!(
+                f,
+                "No default editor found. \
+                Set EDITOR environment variable to enable `kanben edit`."
+            ),
+            KanbenError::Editor(e) => write!(
+                f, "Unable to edit the task: {}", e
+            ),
+            KanbenError::UnreadableDescription(t) => write!(
+                f, "Error loading file for '{}'", t
+            ),
+            KanbenError::Store(e) => write!(
+                f, "Unable to save to the board: {}", e
+            ),
+            KanbenError::Archive(e) => write!(
+                f, "Unable to create the backup: {}", e
+            ),
+            KanbenError::Web(e) => write!(
+                f, "Unable to upload the backup: {}", e
+            ),
+            KanbenError::BoardInconsistent(n) => write!(
+                f, "Found {} problem(s) with the board.", n
+            ),
+            KanbenError::BoardExists(dir) => write!(
+                f, "There's already a board at '{}'.", dir
+            ),
+            KanbenError::BoardNotFound(b) => write!(
+                f, "No board named '{}' found.", b
+            ),
+            KanbenError::BoardNameTaken(b) => write!(
+                f, "A board named '{}' already exists.", b
+            ),
+            KanbenError::InvalidBoardName(b) => write!(
+                f,
+                "'{}' can't be used as a board name. \
+                Use letters, numbers, '-' and '_'.",
+                b
+            ),
+            KanbenError::BoardNotEmpty(b) => write!(
+                f,
+                "Board '{}' still has tasks in it. \
+                Use --force to delete it anyway.",
+                b
+            ),
+            KanbenError::MainBoard => write!(
+                f, "The main board can't be deleted."
+            ),
+            KanbenError::SameBoard(b) => write!(
+                f, "The task is already on the '{}' board.", b
+            ),
+            KanbenError::NotInColumn{ task, column } => write!(
+                f, "'{}' isn't in the '{}' column.", task, column
+            ),
+            KanbenError::InvalidPlan(e) => write!(
+                f, "Unable to read the plan: {}", e
+            ),
+            KanbenError::InvalidDetails(e) => write!(
+                f, "Unable to read the task's details: {}", e
+            ),
+            KanbenError::InvalidAge(a) => write!(
+                f, "'{}' isn't an age kanben understands, try one like 30d.", a
+            ),
+            KanbenError::InvalidPosition(p) => write!(
+                f, "There's no position {}, positions start from 1.", p
+            ),
+            KanbenError::NotInTrash(t) => write!(
+                f, "'{}' isn't in the trash.", t
+            ),
+            KanbenError::ChangedSince(c) => write!(
+                f,
+                "The board has changed since \"{}\" in a way kanben \
+                didn't record, so it's been left as it is.",
+                c
+            ),
+            KanbenError::SchemaTooNew{ found, supported } => write!(
+                f,
+                "This board was saved by a newer version of kanben \
+                (schema {}, this version reads up to {}). \
+                Please upgrade kanben.",
+                found, supported
+            ),
+            KanbenError::NeedsUpgrade(b) => write!(
+                f,
+                "The '{}' board was saved by an older version of kanben \
+                and needs upgrading first. Run `kanben --board {}` to \
+                upgrade it.",
+                b, b
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_kind_of_failure_has_its_own_exit_code() {
+        assert_eq!(KanbenError::InvalidTitle.exit_code(), 2);
+        assert_eq!(KanbenError::TaskNotFound("a".to_owned()).exit_code(), 3);
+        assert_eq!(KanbenError::TaskExists("a".to_owned()).exit_code(), 4);
+        assert_eq!(KanbenError::TooFewColumns.exit_code(), 5);
+        assert_eq!(KanbenError::NoEditor.exit_code(), 6);
+        assert_eq!(KanbenError::Store("a".to_owned()).exit_code(), 7);
+        assert_eq!(KanbenError::Web("a".to_owned()).exit_code(), 8);
+        assert_eq!(
+            KanbenError::SchemaTooNew{ found: 3, supported: 2 }.exit_code(),
+            10
+        );
+        assert_eq!(KanbenError::NeedsUpgrade("a".to_owned()).exit_code(), 10);
+    }
+}
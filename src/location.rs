@@ -0,0 +1,94 @@
+use std::path::{Path, PathBuf};
+use crate::settings::SETTINGS_FILE;
+
+pub const BOARD_DIR: &str = ".kanben";
+
+// the board a command works on. like git, the nearest `.kanben`
+// in the current directory or above it wins, and the home board
+// is used when there isn't one. `KANBEN_HOME` moves the home
+// board, and --global skips the search. only folders made by
+// `kanben init` count, which always have a settings file
+pub fn board_dir(
+    cwd: &Path,
+    home: &Path,
+    kanben_home: Option<String>,
+    global: bool
+) -> PathBuf {
+    let default_home = home.join(BOARD_DIR);
+    let home_board = match kanben_home {
+        Some(dir) if !dir.trim().is_empty() => PathBuf::from(dir),
+        _ => default_home.clone()
+    };
+
+    if global {
+        return home_board;
+    }
+
+    // `~/.kanben` is only a project board if it's been moved
+    cwd.ancestors()
+        .map(|dir| dir.join(BOARD_DIR))
+        .filter(|dir| *dir != default_home)
+        .find(|dir| dir.join(SETTINGS_FILE).is_file())
+        .unwrap_or(home_board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::env::temp_dir;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn scratch(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH).unwrap()
+            .as_nanos();
+        let dir = temp_dir().join(format!("kanben-{}-{}", name, nanos));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn it_uses_the_home_board_outside_a_project() {
+        let root = scratch("home");
+        let home = root.join("home");
+        let cwd = root.join("somewhere");
+        fs::create_dir_all(home.join(BOARD_DIR)).unwrap();
+        // not made by `kanben init`
+        fs::create_dir_all(cwd.join(BOARD_DIR)).unwrap();
+
+        assert_eq!(board_dir(&cwd, &home, None, false), home.join(BOARD_DIR));
+    }
+
+    #[test]
+    fn it_finds_the_nearest_project_board() {
+        let root = scratch("project");
+        let home = root.join("home");
+        let project = home.join("project");
+        let cwd = project.join("src").join("deep");
+        fs::create_dir_all(home.join(BOARD_DIR)).unwrap();
+        fs::create_dir_all(project.join(BOARD_DIR)).unwrap();
+        fs::write(project.join(BOARD_DIR).join(SETTINGS_FILE), "").unwrap();
+        fs::create_dir_all(&cwd).unwrap();
+
+        assert_eq!(board_dir(&cwd, &home, None, false), project.join(BOARD_DIR));
+        assert_eq!(board_dir(&cwd, &home, None, true), home.join(BOARD_DIR));
+    }
+
+    #[test]
+    fn kanben_home_moves_the_home_board() {
+        let root = scratch("kanben-home");
+        let home = root.join("home");
+        let elsewhere = root.join("boards").join("main");
+        fs::create_dir_all(home.join(BOARD_DIR)).unwrap();
+
+        let found = board_dir(
+            &home,
+            &home,
+            Some(elsewhere.to_string_lossy().into_owned()),
+            false
+        );
+
+        assert_eq!(found, elsewhere);
+    }
+}
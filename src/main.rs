@@ -1,33 +1,44 @@
-use std::env::var;
+use std::env::{var, current_dir};
+use std::process;
 use dirs::home_dir;
 use clap::{App, Clap};
 use colored::*;
 use reqwest;
 
-use kv::{Store as KvStore, Config, Json};
-
 use flexi_logger::{Logger, opt_format, Duplicate};
 use log::info;
 
 mod commands;
 mod opts;
 mod store;
+mod storage;
 mod editor;
+mod prompt;
 mod file;
 mod board;
 mod web;
 mod archive;
+mod error;
+mod migrate;
+mod settings;
+mod location;
+mod clock;
 
 #[cfg(test)]
 mod test;
 
-use opts::Opts;
-use store::PersistantStore;
+use opts::{Opts, SubCommand, MoveBoardItem};
+use storage::{StoredBoards, files_path, migrate_store};
 use editor::FileEditor;
+use prompt::StdinPrompt;
 use file::FileReader;
 use archive::ZipArchive;
-use board::Board;
+use settings::Settings;
+use location::board_dir;
+use board::BoardAccess;
 use web::{Client, WebClient};
+use commands::{BoardStorage, BoardVisitor};
+use error::KanbenError;
 
 fn main() {
 //    let web_service_path = env!("KANBEN_WEB_SERVICE_PATH");
@@ -54,73 +65,222 @@ fn main() {
     info!("kanben starting up!");
 
 
-    let home_path_bfr = home_dir().unwrap();
-    let home_path = home_path_bfr.to_str().unwrap();
-    let cfg_location = format!("{}{}", home_path, "/.kanben");
-    let cfg = Config::new(&cfg_location);
-    let kv_store = KvStore::new(cfg)
-        .expect("unable to open store");
-    let bucket = kv_store.bucket::<String, Json<opts::Task>>(
-        Some("tasks")
-    ).expect("unable to get bucket");
-    let col_bucket = kv_store.bucket::<String, Json<Vec<String>>>(
-        Some("columns")
-    ).expect("unable to get bucket");
-    let tag_bucket = kv_store.bucket::<String, Json<Vec<String>>>(
-        Some("tags")
-    ).expect("unable to get bucket");
-
-
-    let mut store = PersistantStore::new(&bucket);
-    let mut col_store = PersistantStore::new(&col_bucket);
-    let mut tag_store = PersistantStore::new(&tag_bucket);
-    let mut board = Board::new(
-        &mut store,
-        &mut col_store,
-        &mut tag_store
+    if let Err(e) = run(opts) {
+        eprintln!("{}", e.to_string().red());
+        process::exit(e.exit_code());
+    }
+}
+
+fn run(opts: Opts) -> Result<(), KanbenError> {
+    let home_path_bfr = home_dir()
+        .ok_or(KanbenError::Store("no home directory found".to_owned()))?;
+    let cwd = current_dir().unwrap_or(home_path_bfr.clone());
+
+    if let Some(SubCommand::Init(i)) = &opts.subcmd {
+        let stdout = std::io::stdout();
+        return commands::init(&cwd, i.store, &mut stdout.lock());
+    }
+
+    let board_path = board_dir(
+        &cwd,
+        &home_path_bfr,
+        var("KANBEN_HOME").ok(),
+        opts.global
     );
+    let cfg_location = board_path.to_str()
+        .ok_or(KanbenError::Store("board path isn't valid utf-8".to_owned()))?
+        .to_owned();
+    info!("using the board at {}", &cfg_location);
+    let mut settings = Settings::load(&cfg_location)?;
 
-    let stdout = std::io::stdout();
-    let mut writer = stdout.lock();
+    if let Some(SubCommand::Boards(b)) = &opts.subcmd {
+        let stdout = std::io::stdout();
+        let mut storage = StoredBoards::open(&settings, &cfg_location)?;
+        commands::boards(
+            b.subcmd.clone(),
+            &mut settings,
+            &mut storage,
+            &mut stdout.lock()
+        )?;
+        return settings.save(&cfg_location);
+    }
 
-    let default_editor_result = var("EDITOR");
+    let board_name = settings.select_board(opts.board.as_deref())?;
+    info!("using the '{}' board", &board_name);
 
-    let default_editor = if default_editor_result.is_err() {
-        println!("{}", "No default editor found.".red());
-        println!("{}",
-            "Set EDITOR environment variable to enable `kanben edit`.".red());
-        None
-    } else {
-        Some(default_editor_result.unwrap())
+    if let Some(SubCommand::MigrateStore) = opts.subcmd {
+        return migrate_store(&cfg_location, &settings, &board_name);
+    }
+
+    let storage = StoredBoards::open(&settings, &cfg_location)?
+        .with_actor(board::user());
+
+    match &opts.subcmd {
+        Some(SubCommand::Now(n)) if n.all_boards => {
+            let stdout = std::io::stdout();
+            return commands::now_all(
+                settings.board_names(),
+                &storage.read_only(),
+                &mut stdout.lock(),
+                opts.no_newlines,
+                opts.tag
+            );
+        },
+        Some(SubCommand::Overview) => {
+            let stdout = std::io::stdout();
+            return commands::overview(
+                settings.board_names(),
+                &storage.read_only(),
+                &mut stdout.lock(),
+                opts.no_newlines,
+                opts.tag
+            );
+        },
+        _ => ()
+    }
+
+    if let Some(SubCommand::MoveBoard(m)) = &opts.subcmd {
+        if !settings.has_board(&m.board) {
+            return Err(KanbenError::BoardNotFound(m.board.clone()));
+        }
+        if m.board == board_name {
+            return Err(KanbenError::SameBoard(m.board.clone()));
+        }
+        let mut move_from = MoveFrom{
+            storage: &storage,
+            item: m.clone(),
+            cfg_location: &cfg_location,
+            board_name: &board_name
+        };
+        return storage.with_board(&board_name, &mut move_from);
+    }
+
+    let mut command = RunCommand{
+        opts: Some(opts),
+        files_path: files_path(&cfg_location, &board_name),
+        archive: storage.archive(&board_name)
     };
+    storage.with_board(&board_name, &mut command)
+}
 
-    let root_file_path = format!(
-        "{}{}",
-        home_path,
-        "/.kanben/files"
-    );
+// runs whatever the command line asked for on the chosen board
+struct RunCommand {
+    opts: Option<Opts>,
+    files_path: String,
+    archive: ZipArchive
+}
+
+impl BoardVisitor for RunCommand {
+    fn visit<B: BoardAccess>(
+        &mut self,
+        board: &mut B
+    ) -> Result<(), KanbenError> {
+        match self.opts.take() {
+            Some(opts) => handle(opts, board, &self.files_path, &self.archive),
+            None => Ok(())
+        }
+    }
+}
+
+// `move-board` needs both boards open at once, so the source
+// board opens the target one in turn
+struct MoveFrom<'a> {
+    storage: &'a StoredBoards<'a>,
+    item: MoveBoardItem,
+    cfg_location: &'a str,
+    board_name: &'a str
+}
+
+impl<'a> BoardVisitor for MoveFrom<'a> {
+    fn visit<B: BoardAccess>(
+        &mut self,
+        board: &mut B
+    ) -> Result<(), KanbenError> {
+        let mut move_into = MoveInto{
+            source: board,
+            item: &self.item,
+            cfg_location: self.cfg_location,
+            board_name: self.board_name
+        };
+        self.storage.with_board(&self.item.board, &mut move_into)
+    }
+}
+
+struct MoveInto<'a, S: BoardAccess> {
+    source: &'a mut S,
+    item: &'a MoveBoardItem,
+    cfg_location: &'a str,
+    board_name: &'a str
+}
+
+impl<'a, S: BoardAccess> BoardVisitor for MoveInto<'a, S> {
+    fn visit<B: BoardAccess>(
+        &mut self,
+        board: &mut B
+    ) -> Result<(), KanbenError> {
+        let stdout = std::io::stdout();
+        let mut writer = stdout.lock();
+        let source_editor = FileEditor::new(
+            var("EDITOR").ok(),
+            files_path(self.cfg_location, self.board_name)
+        );
+        let mut target_editor = FileEditor::new(
+            var("EDITOR").ok(),
+            files_path(self.cfg_location, &self.item.board)
+        );
+
+        commands::move_board(
+            self.item.clone(),
+            self.source,
+            board,
+            &source_editor,
+            &mut target_editor,
+            &mut writer
+        )
+    }
+}
+
+fn handle<B: BoardAccess>(
+    opts: Opts,
+    board: &mut B,
+    files_path: &str,
+    archive: &ZipArchive
+) -> Result<(), KanbenError> {
+    let stdout = std::io::stdout();
+    let mut writer = stdout.lock();
+
+    // a missing editor is only reported when something needs it
+    let default_editor = var("EDITOR").ok();
 
     let mut editor = FileEditor::new(
         default_editor,
-        root_file_path
+        files_path.to_owned()
     );
 
+    let moved = commands::migrate_descriptions(board, &mut editor)?;
+    if moved > 0 {
+        eprintln!(
+            "Moved {} description file(s) into '{}', one folder per task.",
+            moved, files_path
+        );
+    }
+
+    let mut prompt = StdinPrompt::new();
     let file_reader = FileReader::new();
     
     let client = reqwest::blocking::Client::new();
     let http_client = Client::new(client);
     let mut web = WebClient::new(http_client);
-    let archive = ZipArchive::new(&cfg_location);
 
     commands::handle(
         opts,
-        &mut board,
+        board,
         &mut writer,
         &mut editor,
+        &mut prompt,
         &file_reader,
         &mut web,
-        &archive
-    );
+        archive
+    )
 }
 
-
@@ -0,0 +1,386 @@
+use serde_json::{Value, json};
+use log::info;
+use crate::store::Store;
+use crate::archive::Archive;
+use crate::error::KanbenError;
+use crate::board::{BoardConfig, CONFIG_KEY, column_label};
+
+// the schema this build reads and writes. bump it and add a
+// step to `Migrator::step` whenever stored records change shape
+pub const SCHEMA_VERSION: u32 = 2;
+pub const VERSION_KEY: &str = "schema_version";
+
+// upgrades the stored board one schema version at a time.
+// tasks are handled as plain json, so a step keeps working
+// however `Task` changes after it was written
+pub struct Migrator<
+    'a,
+    T: Store<Value>,
+    C: Store<Vec<String>>,
+    K: Store<BoardConfig>,
+    M: Store<u32>
+> {
+    tasks: &'a mut T,
+    columns: &'a mut C,
+    config: &'a mut K,
+    meta: &'a mut M,
+}
+
+impl <
+    'a,
+    T: Store<Value>,
+    C: Store<Vec<String>>,
+    K: Store<BoardConfig>,
+    M: Store<u32>
+> Migrator<'a, T, C, K, M> {
+    pub fn new(
+        tasks: &'a mut T,
+        columns: &'a mut C,
+        config: &'a mut K,
+        meta: &'a mut M
+    ) -> Migrator<'a, T, C, K, M> {
+        Migrator{ tasks, columns, config, meta }
+    }
+
+    // boards saved before versioning have no version at all
+    pub fn stored_version(&self) -> u32 {
+        self.meta.get(VERSION_KEY).unwrap_or(0)
+    }
+
+    // whether `migrate` has anything to do. a board saved by a
+    // newer version can't be brought up to date at all
+    pub fn needs_migrating(&self) -> Result<bool, KanbenError> {
+        let version = self.stored_version();
+        if version > SCHEMA_VERSION {
+            return Err(KanbenError::SchemaTooNew{
+                found: version,
+                supported: SCHEMA_VERSION
+            });
+        }
+        Ok(version < SCHEMA_VERSION && !self.is_empty())
+    }
+
+    // brings the board up to date, taking a copy of the data
+    // first. returns where the copy was put, if one was needed
+    pub fn migrate<A: Archive>(
+        &mut self,
+        archive: &A
+    ) -> Result<Option<String>, KanbenError> {
+        let version = self.stored_version();
+        if version > SCHEMA_VERSION {
+            return Err(KanbenError::SchemaTooNew{
+                found: version,
+                supported: SCHEMA_VERSION
+            });
+        }
+
+        if version == SCHEMA_VERSION {
+            return Ok(None);
+        }
+
+        // a new board starts out at the latest schema
+        if self.is_empty() {
+            self.meta.set(VERSION_KEY, SCHEMA_VERSION)?;
+            return Ok(None);
+        }
+
+        let backup = archive.snapshot(
+            &format!("backup-schema-v{}", version)
+        )?;
+        info!("board copied to {} before migrating", &backup);
+
+        // each step is recorded as soon as it lands. steps are
+        // safe to run twice in case the version isn't saved
+        for next in (version + 1)..=SCHEMA_VERSION {
+            info!("migrating board to schema v{}", next);
+            self.atomically(|m| m.step(next))?;
+            self.meta.set(VERSION_KEY, next)?;
+        }
+
+        Ok(Some(backup))
+    }
+
+    fn step(&mut self, version: u32) -> Result<(), KanbenError> {
+        match version {
+            1 => self.lowercase_columns(),
+            2 => self.assign_ids(),
+            _ => Ok(())
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tasks.keys().is_empty()
+            && self.tasks.corrupt_keys().is_empty()
+            && self.columns.keys().is_empty()
+            && self.config.get(CONFIG_KEY).is_none()
+    }
+
+    fn atomically<F>(&mut self, change: F) -> Result<(), KanbenError>
+    where F: FnOnce(&mut Self) -> Result<(), KanbenError> {
+        self.config.begin();
+        self.tasks.begin();
+        self.columns.begin();
+
+        let result = change(self)
+            .and_then(|_| self.config.commit())
+            .and_then(|_| self.tasks.commit())
+            .and_then(|_| self.columns.commit());
+
+        self.config.rollback();
+        self.tasks.rollback();
+        self.columns.rollback();
+        result
+    }
+
+    // v1: columns used to be a fixed enum, stored as `Todo`,
+    // `Doing` and `Done`
+    fn lowercase_columns(&mut self) -> Result<(), KanbenError> {
+        for key in self.tasks.keys() {
+            let mut task = match self.tasks.get(&key) {
+                Some(t) => t,
+                None => continue
+            };
+            let label = match task.get("column").and_then(|c| c.as_str()) {
+                Some(c) => column_label(c),
+                None => continue
+            };
+            if task["column"] != json!(label) {
+                task["column"] = json!(label);
+                self.tasks.set(&key, task)?;
+            }
+        }
+        Ok(())
+    }
+
+    // v2: every task has an id. tasks from before ids existed
+    // are numbered in the order they appear on the board
+    fn assign_ids(&mut self) -> Result<(), KanbenError> {
+        let mut config = match self.config.get(CONFIG_KEY) {
+            Some(c) if !c.columns.is_empty() => c,
+            _ => BoardConfig::default()
+        };
+
+        let mut keys: Vec<String> = config.columns.iter()
+            .flat_map(|c| self.columns.get(c).unwrap_or_default())
+            .collect();
+        for key in self.tasks.keys() {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+
+        let mut count = 0;
+        for key in keys {
+            let mut task = match self.tasks.get(&key) {
+                Some(t) if t.is_object() => t,
+                _ => continue
+            };
+            if task.get("id").unwrap_or(&Value::Null).is_null() {
+                config.last_id += 1;
+                task["id"] = json!(config.last_id);
+                self.tasks.set(&key, task)?;
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            self.config.set(CONFIG_KEY, config)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{StoreMock, ArchiveMock};
+
+    fn old_task(name: &str, column: &str) -> Value {
+        json!({
+            "name": name,
+            "column": column,
+            "description": null,
+            "tags": null
+        })
+    }
+
+    #[test]
+    fn a_new_board_starts_at_the_latest_schema() {
+        let mut tasks = StoreMock::<Value>::new();
+        let mut columns = StoreMock::new();
+        let mut config = StoreMock::new();
+        let mut meta = StoreMock::new();
+        let archive = ArchiveMock::new();
+        let mut migrator = Migrator::new(
+            &mut tasks,
+            &mut columns,
+            &mut config,
+            &mut meta
+        );
+
+        assert_eq!(migrator.migrate(&archive), Ok(None));
+        assert_eq!(migrator.stored_version(), SCHEMA_VERSION);
+        assert!(!archive.snapshot_called());
+    }
+
+    #[test]
+    fn it_upgrades_old_tasks_in_place() {
+        let mut tasks = StoreMock::new();
+        tasks.bulk_insert(vec!(
+            ("task1", old_task("task1", "Doing")),
+            ("task2", old_task("task2", "Todo")),
+        ));
+        let mut columns = StoreMock::new();
+        columns.bulk_insert(vec!(
+            ("todo", vec!("task2".to_owned())),
+            ("doing", vec!("task1".to_owned())),
+        ));
+        let mut config = StoreMock::new();
+        let mut meta = StoreMock::new();
+        let archive = ArchiveMock::new();
+        let mut migrator = Migrator::new(
+            &mut tasks,
+            &mut columns,
+            &mut config,
+            &mut meta
+        );
+
+        let backup = migrator.migrate(&archive).unwrap();
+
+        assert_eq!(backup, Some("/tmp/kanben-backup-schema-v0".to_owned()));
+        assert_eq!(migrator.stored_version(), SCHEMA_VERSION);
+        let task1 = tasks.get("task1").unwrap();
+        let task2 = tasks.get("task2").unwrap();
+        assert_eq!(task1["column"], json!("doing"));
+        assert_eq!(task2["id"], json!(1));
+        assert_eq!(task1["id"], json!(2));
+        assert_eq!(config.get(CONFIG_KEY).unwrap().last_id, 2);
+    }
+
+    #[test]
+    fn it_only_runs_the_steps_still_needed() {
+        let mut task = old_task("task", "todo");
+        task["id"] = json!(7);
+        let mut tasks = StoreMock::new();
+        tasks.bulk_insert(vec!(("task", task.clone())));
+        let mut columns = StoreMock::new();
+        let mut config = StoreMock::new();
+        let mut meta = StoreMock::new();
+        meta.bulk_insert(vec!((VERSION_KEY, 1)));
+        let archive = ArchiveMock::new();
+        let mut migrator = Migrator::new(
+            &mut tasks,
+            &mut columns,
+            &mut config,
+            &mut meta
+        );
+
+        migrator.migrate(&archive).unwrap();
+
+        assert!(archive.snapshot_called_with("backup-schema-v1"));
+        assert_eq!(tasks.get("task").unwrap(), task);
+        assert!(!config.set_called());
+    }
+
+    #[test]
+    fn it_leaves_an_up_to_date_board_alone() {
+        let mut tasks = StoreMock::new();
+        tasks.bulk_insert(vec!(("task", old_task("task", "Todo"))));
+        let mut columns = StoreMock::new();
+        let mut config = StoreMock::new();
+        let mut meta = StoreMock::new();
+        meta.bulk_insert(vec!((VERSION_KEY, SCHEMA_VERSION)));
+        let archive = ArchiveMock::new();
+        let mut migrator = Migrator::new(
+            &mut tasks,
+            &mut columns,
+            &mut config,
+            &mut meta
+        );
+
+        assert_eq!(migrator.migrate(&archive), Ok(None));
+        assert!(!archive.snapshot_called());
+        assert!(!tasks.set_called());
+    }
+
+    #[test]
+    fn it_tells_whether_a_board_needs_migrating_without_changing_it() {
+        let mut tasks = StoreMock::new();
+        tasks.bulk_insert(vec!(("task", old_task("task", "Todo"))));
+        let mut columns = StoreMock::new();
+        let mut config = StoreMock::new();
+        let mut meta = StoreMock::new();
+        let migrator = Migrator::new(
+            &mut tasks,
+            &mut columns,
+            &mut config,
+            &mut meta
+        );
+
+        assert_eq!(migrator.needs_migrating(), Ok(true));
+        assert!(!tasks.set_called());
+        assert!(!meta.set_called());
+    }
+
+    #[test]
+    fn a_new_board_doesnt_need_migrating() {
+        let mut tasks = StoreMock::<Value>::new();
+        let mut columns = StoreMock::new();
+        let mut config = StoreMock::new();
+        let mut meta = StoreMock::new();
+        let migrator = Migrator::new(
+            &mut tasks,
+            &mut columns,
+            &mut config,
+            &mut meta
+        );
+
+        assert_eq!(migrator.needs_migrating(), Ok(false));
+    }
+
+    #[test]
+    fn it_refuses_data_from_a_newer_version() {
+        let mut tasks = StoreMock::<Value>::new();
+        let mut columns = StoreMock::new();
+        let mut config = StoreMock::new();
+        let mut meta = StoreMock::new();
+        meta.bulk_insert(vec!((VERSION_KEY, SCHEMA_VERSION + 1)));
+        let archive = ArchiveMock::new();
+        let mut migrator = Migrator::new(
+            &mut tasks,
+            &mut columns,
+            &mut config,
+            &mut meta
+        );
+
+        assert_eq!(
+            migrator.migrate(&archive),
+            Err(KanbenError::SchemaTooNew{
+                found: SCHEMA_VERSION + 1,
+                supported: SCHEMA_VERSION
+            })
+        );
+        assert!(!archive.snapshot_called());
+    }
+
+    #[test]
+    fn a_failed_step_leaves_the_version_where_it_was() {
+        let mut tasks = StoreMock::new();
+        tasks.bulk_insert(vec!(("task", old_task("task", "Todo"))));
+        tasks.fail_writes();
+        let mut columns = StoreMock::new();
+        let mut config = StoreMock::new();
+        let mut meta = StoreMock::new();
+        let archive = ArchiveMock::new();
+        let mut migrator = Migrator::new(
+            &mut tasks,
+            &mut columns,
+            &mut config,
+            &mut meta
+        );
+
+        assert!(migrator.migrate(&archive).is_err());
+        assert_eq!(migrator.stored_version(), 0);
+        assert!(archive.snapshot_called());
+    }
+}
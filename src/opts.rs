@@ -1,5 +1,6 @@
-use clap::Clap;
-use serde::{Serialize, Deserialize};
+use clap::{Clap, ArgGroup};
+use serde::{Serialize, Deserialize, Deserializer};
+use crate::settings::Backend;
 
 #[derive(Clap)]
 #[clap(version = "1.0", author = "Ben Brunton")]
@@ -11,7 +12,11 @@ pub struct Opts {
     #[clap(short, long, about="filter by tags")]
     pub tag: Option<String>,
     #[clap(short, long, about="verbose logging to stdout")]
-    pub verbose: bool
+    pub verbose: bool,
+    #[clap(short, long, about="use the home board, even inside a project")]
+    pub global: bool,
+    #[clap(short, long, about="which board to use, see `kanben boards`")]
+    pub board: Option<String>
 }
 
 #[derive(Clap)]
@@ -19,29 +24,69 @@ pub enum SubCommand {
     #[clap(about="Adds a new task in todo column")]
     Add(NewItem),
     #[clap(about="Move a task into doing")]
-    Start(Item),
+    Start(MoveItem),
     #[clap(about="Move a task into done")]
-    Complete(Item),
+    Complete(MoveItem),
     #[clap(about="Delete a task")]
     Delete(Item),
     #[clap(about="clear tasks from the done column")]
     ClearDone,
     #[clap(about="Edit the information about a task")]
-    Edit(Item),
+    Edit(EditItem),
     #[clap(about="View any additional information about a task")]
-    View(Item),
+    View(ViewItem),
     #[clap(about="Outputs in-progress tasks")]
-    Now,
+    Now(NowItem),
     #[clap(about="Re-indexes columns")]
     Reindex,
     #[clap(about="moves task to top of priorities")]
     Top(Item),
+    #[clap(about="moves task to bottom of priorities")]
+    Bottom(Item),
+    #[clap(about="moves task up its column")]
+    Up(StepItem),
+    #[clap(about="moves task down its column")]
+    Down(StepItem),
+    #[clap(about="moves task to a new place in its column")]
+    Move(MoveItemWithin),
+    #[clap(about="reorder the board in your editor")]
+    Plan,
     #[clap(about="add a tag to a task or view a tasks tags")]
     Tag(TagItem),
+    #[clap(about="give a task a new title")]
+    Rename(RenameItem),
     #[clap(about="list all tasks")]
     Tasks,
     #[clap(about="backup local board to the cloud")]
-    Backup
+    Backup,
+    #[clap(about="list or manage the board's columns")]
+    Column(ColumnOpts),
+    #[clap(about="view or change the moves allowed between columns")]
+    Workflow(WorkflowOpts),
+    #[clap(about="check the board for inconsistencies")]
+    Fsck(FsckItem),
+    #[clap(about="find description files nothing uses any more")]
+    Gc(GcItem),
+    #[clap(about="list or empty the deleted tasks")]
+    Trash(TrashOpts),
+    #[clap(about="bring back a deleted task")]
+    Restore(Item),
+    #[clap(about="undo the latest changes to the board")]
+    Undo(UndoItem),
+    #[clap(about="redo the latest change undone")]
+    Redo,
+    #[clap(about="what's happened to the tasks on the board")]
+    History(HistoryItem),
+    #[clap(about="copy the board into an SQLite store")]
+    MigrateStore,
+    #[clap(about="start a board for the current directory")]
+    Init(InitItem),
+    #[clap(about="list or manage the named boards")]
+    Boards(BoardsOpts),
+    #[clap(about="move a task onto another board")]
+    MoveBoard(MoveBoardItem),
+    #[clap(about="column counts and in-progress tasks for every board")]
+    Overview
 }
 
 #[derive(Clap, Clone, PartialEq)]
@@ -50,12 +95,175 @@ pub struct Item {
     pub title: String
 }
 
+#[derive(Clap, Clone, PartialEq)]
+pub struct EditItem {
+    #[clap(about="Name of task")]
+    pub title: String,
+    #[clap(long, about="edit the title, column and tags as well")]
+    pub full: bool
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct ViewItem {
+    #[clap(about="Name of task")]
+    pub title: String,
+    #[clap(long, about="show what's happened to the task instead")]
+    pub history: bool
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct MoveItem {
+    #[clap(about="Name of task")]
+    pub title: String,
+    #[clap(short, long, about="move even if the workflow forbids it")]
+    pub force: bool
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct StepItem {
+    #[clap(about="Name of task")]
+    pub title: String,
+    #[clap(default_value="1", about="How many places to move it")]
+    pub places: usize
+}
+
+#[derive(Clap, Clone, PartialEq)]
+#[clap(group = ArgGroup::with_name("to").required(true))]
+pub struct MoveItemWithin {
+    #[clap(about="Name of task")]
+    pub title: String,
+    #[clap(long, group="to", about="put it just above this task")]
+    pub before: Option<String>,
+    #[clap(long, group="to", about="put it just below this task")]
+    pub after: Option<String>,
+    #[clap(long, group="to", about="put it at this position, starting from 1")]
+    pub position: Option<usize>
+}
+
 #[derive(Clap, Clone, PartialEq)]
 pub struct NewItem {
     #[clap(about="Name of task")]
     pub title: String,
     #[clap(short, long, about="add a tag to the new task")]
-    pub tag: Option<String>
+    pub tag: Option<String>,
+    #[clap(
+        long,
+        conflicts_with="open",
+        about="add a number to the title if it's already taken"
+    )]
+    pub suffix: bool,
+    #[clap(long, about="open the existing task if the title is taken")]
+    pub open: bool
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct GcItem {
+    #[clap(long, about="remove them, once you've said yes")]
+    pub purge: bool
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct TrashOpts {
+    #[clap(subcommand)]
+    pub subcmd: Option<TrashCommand>
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub enum TrashCommand {
+    #[clap(about="Lists deleted tasks, most recent first")]
+    List,
+    #[clap(about="Removes deleted tasks for good")]
+    Empty(EmptyTrashItem)
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct EmptyTrashItem {
+    #[clap(long, about="only tasks deleted at least this long ago, like 30d")]
+    pub older_than: Option<String>
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct UndoItem {
+    #[clap(default_value="1", about="How many changes to undo")]
+    pub times: usize,
+    #[clap(long, about="list the recent changes instead")]
+    pub list: bool
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct HistoryItem {
+    #[clap(long, about="only what's happened within this long, like 7d")]
+    pub since: Option<String>
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct FsckItem {
+    #[clap(long, about="fix any problems that can be fixed safely")]
+    pub repair: bool
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct InitItem {
+    #[clap(
+        long,
+        possible_values=&["kv", "markdown", "sqlite"],
+        about="how the board is stored"
+    )]
+    pub store: Option<Backend>
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct NowItem {
+    #[clap(long, about="include every board, grouped by name")]
+    pub all_boards: bool
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct MoveBoardItem {
+    #[clap(about="Name of task")]
+    pub title: String,
+    #[clap(about="Name of board to move it to")]
+    pub board: String,
+    #[clap(long, about="put it at the top of its column")]
+    pub top: bool
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct BoardsOpts {
+    #[clap(subcommand)]
+    pub subcmd: Option<BoardsCommand>
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub enum BoardsCommand {
+    #[clap(about="Adds a new, empty board")]
+    Create(BoardItem),
+    #[clap(about="Deletes a board and its tasks")]
+    Delete(DeleteBoardItem),
+    #[clap(about="Uses a board when --board isn't given")]
+    Default(BoardItem)
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct BoardItem {
+    #[clap(about="Name of board")]
+    pub name: String
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct DeleteBoardItem {
+    #[clap(about="Name of board")]
+    pub name: String,
+    #[clap(short, long, about="delete it even if it has tasks")]
+    pub force: bool
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct RenameItem {
+    #[clap(about="Name of task")]
+    pub title: String,
+    #[clap(about="New name for the task")]
+    pub new_title: String
 }
 
 #[derive(Clap, Clone, PartialEq)]
@@ -68,18 +276,202 @@ pub struct TagItem {
     pub remove: bool
 }
 
+#[derive(Clap, Clone, PartialEq)]
+pub struct ColumnOpts {
+    #[clap(subcommand)]
+    pub subcmd: Option<ColumnCommand>
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub enum ColumnCommand {
+    #[clap(about="Adds a column in front of the last one")]
+    Add(ColumnItem),
+    #[clap(about="Removes an empty column")]
+    Remove(ColumnItem),
+    #[clap(about="Renames a column, keeping its tasks")]
+    Rename(RenameColumnItem),
+    #[clap(about="Moves a column to a new position")]
+    Reorder(ReorderColumnItem),
+    #[clap(about="Sets how many tasks a column can hold, 0 for no limit")]
+    Limit(LimitItem),
+    #[clap(about="Whether limits are 'strict' or 'soft'")]
+    LimitMode(LimitModeItem)
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct ColumnItem {
+    #[clap(about="Name of column")]
+    pub name: String
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct RenameColumnItem {
+    #[clap(about="Current name of column")]
+    pub name: String,
+    #[clap(about="New name of column")]
+    pub new_name: String
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct ReorderColumnItem {
+    #[clap(about="Name of column")]
+    pub name: String,
+    #[clap(about="New position, starting from 1")]
+    pub position: usize
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct LimitItem {
+    #[clap(about="Name of column")]
+    pub name: String,
+    #[clap(about="Most tasks allowed in the column")]
+    pub limit: usize
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct LimitModeItem {
+    #[clap(about="strict refuses moves into a full column, soft warns")]
+    pub mode: String
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct WorkflowOpts {
+    #[clap(subcommand)]
+    pub subcmd: Option<WorkflowCommand>
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub enum WorkflowCommand {
+    #[clap(about="Allows tasks to move from one column to another")]
+    Allow(TransitionItem),
+    #[clap(about="Stops tasks moving from one column to another")]
+    Forbid(TransitionItem),
+    #[clap(about="Only allows moves into the next column")]
+    Linear,
+    #[clap(about="Removes the workflow so any move is allowed")]
+    Clear
+}
+
+#[derive(Clap, Clone, PartialEq)]
+pub struct TransitionItem {
+    #[clap(about="Column tasks move from")]
+    pub from: String,
+    #[clap(about="Column tasks move to")]
+    pub to: String
+}
 
-#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
 pub struct Task {
     pub name: String,
-    pub column: Column,
+    #[serde(deserialize_with = "column_label")]
+    pub column: String,
     pub description: Option<String>,
-    pub tags: Option<Vec<String>>
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub id: Option<u64>,
+    // older records have no times, and load with none set
+    #[serde(default, skip_serializing_if = "Times::is_empty")]
+    pub times: Times
+}
+
+// when things happened to a task, in seconds since the epoch.
+// the board keeps these up to date as the task moves
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct Times {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<u64>,
+    // when it first went into an in-progress column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started: Option<u64>,
+    // when it reached the last column, cleared if it leaves
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed: Option<u64>,
+    // the last time it changed column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moved: Option<u64>
+}
+
+impl Times {
+    pub fn is_empty(&self) -> bool {
+        *self == Times::default()
+    }
+}
+
+impl Task {
+    // how a task is shown in lists, with its id in front
+    pub fn label(&self) -> String {
+        match self.id {
+            Some(id) => format!("{} {}", id, self.name),
+            None => self.name.clone()
+        }
+    }
+}
+
+// columns used to be a fixed enum, serialized as `Todo`,
+// `Doing` and `Done`. labels are lowercase now, so older
+// records are normalised as they are read
+fn column_label<'de, D: Deserializer<'de>>(
+    deserializer: D
+) -> Result<String, D::Error> {
+    let label = String::deserialize(deserializer)?;
+    Ok(label.to_lowercase())
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
-pub enum Column {
-    Todo,
-    Doing,
-    Done
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kv::{Codec, Json, Raw, Value};
+
+    #[test]
+    fn it_reads_columns_stored_by_older_versions() {
+        let raw = Raw::from(&br#"{
+            "name": "task",
+            "column": "Doing",
+            "description": null,
+            "tags": null
+        }"#[..]);
+
+        let task = Json::<Task>::from_raw_value(raw).unwrap().to_inner();
+
+        assert_eq!(task.column, "doing");
+    }
+
+    #[test]
+    fn tasks_without_times_still_load() {
+        let raw = Raw::from(&br#"{
+            "name": "task",
+            "column": "todo",
+            "description": null,
+            "tags": ["x"],
+            "id": 3
+        }"#[..]);
+
+        let task = Json::<Task>::from_raw_value(raw).unwrap().to_inner();
+
+        assert_eq!(task.id, Some(3));
+        assert_eq!(task.tags, Some(vec!("x".to_owned())));
+        assert!(task.times.is_empty());
+    }
+
+    #[test]
+    fn times_are_kept_when_stored() {
+        let task = Task{
+            name: "task".to_owned(),
+            column: "doing".to_owned(),
+            description: None,
+            tags: None,
+            id: Some(1),
+            times: Times{
+                created: Some(10),
+                started: Some(20),
+                completed: None,
+                moved: Some(20)
+            }
+        };
+
+        let raw = Json(task.clone()).to_raw_value().unwrap();
+        let stored = Json::<Task>::from_raw_value(raw).unwrap().to_inner();
+
+        assert_eq!(stored, task);
+    }
 }
@@ -0,0 +1,26 @@
+use std::io::{self, BufRead};
+
+// asks whoever is at the terminal to confirm something. the
+// question itself is written by the command
+pub trait Prompt {
+    fn confirm(&mut self) -> bool;
+}
+
+pub struct StdinPrompt;
+
+impl StdinPrompt {
+    pub fn new() -> StdinPrompt {
+        StdinPrompt
+    }
+}
+
+// anything but a yes, including no answer at all, is a no
+impl Prompt for StdinPrompt {
+    fn confirm(&mut self) -> bool {
+        let mut answer = String::new();
+        if io::stdin().lock().read_line(&mut answer).is_err() {
+            return false;
+        }
+        matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+    }
+}
@@ -0,0 +1,217 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+use crate::error::KanbenError;
+
+pub const SETTINGS_FILE: &str = "settings.yml";
+// the board every folder starts with. it keeps the unnamed
+// buckets and paths kanben used before boards had names
+pub const MAIN_BOARD: &str = "main";
+
+// how kanben itself is set up, as opposed to the board config,
+// which lives inside whichever store is chosen here. read from
+// `settings.yml` in the kanben folder, which is optional
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct Settings {
+    #[serde(default)]
+    pub store: Backend,
+    // where a markdown or sqlite board is kept. relative paths
+    // are taken from the kanben folder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    // boards made with `kanben boards create`, besides main
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub boards: Vec<String>,
+    // used when --board isn't given. main if there isn't one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_board: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Kv,
+    Markdown,
+    Sqlite,
+}
+
+impl Default for Backend {
+    fn default() -> Backend {
+        Backend::Kv
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Backend, String> {
+        match name {
+            "kv" => Ok(Backend::Kv),
+            "markdown" => Ok(Backend::Markdown),
+            "sqlite" => Ok(Backend::Sqlite),
+            _ => Err(format!("unknown store '{}'", name))
+        }
+    }
+}
+
+impl Settings {
+    pub fn load(dir: &str) -> Result<Settings, KanbenError> {
+        let path = Path::new(dir).join(SETTINGS_FILE);
+        if !path.exists() {
+            return Ok(Settings::default());
+        }
+
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| KanbenError::InvalidSettings(e.to_string()))?;
+        Settings::parse(&contents)
+    }
+
+    pub fn save(&self, dir: &str) -> Result<(), KanbenError> {
+        let contents = serde_yaml::to_string(self)
+            .map_err(|e| KanbenError::InvalidSettings(e.to_string()))?;
+        fs::write(
+            Path::new(dir).join(SETTINGS_FILE),
+            contents.trim_start_matches("---\n")
+        ).map_err(|e| KanbenError::InvalidSettings(e.to_string()))
+    }
+
+    pub fn parse(contents: &str) -> Result<Settings, KanbenError> {
+        if contents.trim().is_empty() {
+            return Ok(Settings::default());
+        }
+
+        serde_yaml::from_str(contents)
+            .map_err(|e| KanbenError::InvalidSettings(e.to_string()))
+    }
+
+    pub fn board_names(&self) -> Vec<String> {
+        let mut names = vec!(MAIN_BOARD.to_owned());
+        names.extend(self.boards.iter().cloned());
+        names
+    }
+
+    pub fn has_board(&self, name: &str) -> bool {
+        self.board_names().iter().any(|b| b == name)
+    }
+
+    // --board wins over the default, which wins over main
+    pub fn select_board(
+        &self,
+        requested: Option<&str>
+    ) -> Result<String, KanbenError> {
+        let name = requested
+            .or(self.default_board.as_deref())
+            .unwrap_or(MAIN_BOARD);
+        if !self.has_board(name) {
+            return Err(KanbenError::BoardNotFound(name.to_owned()));
+        }
+        Ok(name.to_owned())
+    }
+
+    pub fn board_path(&self, dir: &str, board: &str) -> String {
+        self.path_for(self.store, dir, board)
+    }
+
+    // `path` only applies to the store that's selected, any
+    // other store is found in its usual place. kv boards share
+    // one store, the others keep each named board to itself
+    pub fn path_for(&self, store: Backend, dir: &str, board: &str) -> String {
+        let default = match store {
+            Backend::Kv => return dir.to_owned(),
+            Backend::Markdown => "board",
+            Backend::Sqlite => "kanben.db"
+        };
+        let path = match &self.path {
+            Some(p) if store == self.store => p.clone(),
+            _ => default.to_owned()
+        };
+        let path = Path::new(dir).join(path);
+        if board == MAIN_BOARD {
+            return path.to_string_lossy().into_owned();
+        }
+
+        let named = match store {
+            Backend::Markdown => path.join("boards").join(board),
+            _ => {
+                let stem = path.file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let file = match path.extension() {
+                    Some(ext) => format!(
+                        "{}.{}.{}", stem, board, ext.to_string_lossy()
+                    ),
+                    None => format!("{}.{}", stem, board)
+                };
+                path.with_file_name(file)
+            }
+        };
+        named.to_string_lossy().into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_defaults_to_the_kv_store() {
+        let settings = Settings::parse("").unwrap();
+        assert_eq!(settings.store, Backend::Kv);
+    }
+
+    #[test]
+    fn it_can_select_the_markdown_store() {
+        let settings = Settings::parse("store: markdown\n").unwrap();
+        assert_eq!(settings.store, Backend::Markdown);
+        assert_eq!(settings.board_path("/home/a/.kanben", MAIN_BOARD), "/home/a/.kanben/board");
+    }
+
+    #[test]
+    fn markdown_boards_can_live_anywhere() {
+        let settings = Settings::parse(
+            "store: markdown\npath: /src/project/board\n"
+        ).unwrap();
+        assert_eq!(settings.board_path("/home/a/.kanben", MAIN_BOARD), "/src/project/board");
+        assert_eq!(
+            settings.path_for(Backend::Sqlite, "/home/a/.kanben", MAIN_BOARD),
+            "/home/a/.kanben/kanben.db"
+        );
+    }
+
+    #[test]
+    fn named_boards_are_kept_apart() {
+        let settings = Settings::parse("store: markdown\n").unwrap();
+        assert_eq!(
+            settings.board_path("/home/a/.kanben", "work"),
+            "/home/a/.kanben/board/boards/work"
+        );
+        assert_eq!(
+            settings.path_for(Backend::Sqlite, "/home/a/.kanben", "work"),
+            "/home/a/.kanben/kanben.work.db"
+        );
+        assert_eq!(
+            settings.path_for(Backend::Kv, "/home/a/.kanben", "work"),
+            "/home/a/.kanben"
+        );
+    }
+
+    #[test]
+    fn it_selects_a_board() {
+        let settings = Settings::parse(
+            "boards:\n  - work\n  - home\ndefault_board: work\n"
+        ).unwrap();
+        assert_eq!(settings.select_board(None).unwrap(), "work");
+        assert_eq!(settings.select_board(Some("main")).unwrap(), "main");
+        assert_eq!(
+            settings.select_board(Some("garden")),
+            Err(KanbenError::BoardNotFound("garden".to_owned()))
+        );
+    }
+
+    #[test]
+    fn it_rejects_an_unknown_store() {
+        let result = Settings::parse("store: paper\n");
+        assert!(matches!(result, Err(KanbenError::InvalidSettings(_))));
+    }
+}
@@ -0,0 +1,464 @@
+use std::fs;
+use std::path::Path;
+use kv::{Store as KvStore, Bucket, Config, Json, Value};
+
+use crate::opts::Task;
+use crate::store::{
+    PersistantStore, MarkdownStore, MarkdownIndex, MarkdownTrash, Index, YamlStore
+};
+#[cfg(feature = "sqlite")]
+use crate::store::sqlite::{self, SqliteStore, SqliteIndex, SqliteRecords};
+use crate::archive::ZipArchive;
+use crate::migrate::Migrator;
+use crate::settings::{Settings, Backend, MAIN_BOARD};
+use crate::board::{
+    Board, BoardAccess, BoardConfig, Trashed, Entry, Event, Pending
+};
+use crate::commands::{BoardStorage, BoardVisitor};
+use crate::error::KanbenError;
+
+// every board in a kanben folder, kept in whichever store
+// settings.yml picks. kv boards all share one store, which can
+// only be opened once, so it's held onto here
+pub struct StoredBoards<'a> {
+    settings: Settings,
+    cfg_location: &'a str,
+    kv_store: Option<KvStore>,
+    read_only: bool,
+    actor: Option<String>
+}
+
+impl<'a> StoredBoards<'a> {
+    pub fn open(
+        settings: &Settings,
+        cfg_location: &'a str
+    ) -> Result<StoredBoards<'a>, KanbenError> {
+        let kv_store = match settings.store {
+            Backend::Kv => Some(open_kv(cfg_location)?),
+            _ => None
+        };
+        Ok(StoredBoards{
+            settings: settings.clone(),
+            cfg_location,
+            kv_store,
+            read_only: false,
+            actor: None
+        })
+    }
+
+    // who the changes made to its boards are put down to
+    pub fn with_actor(
+        mut self,
+        actor: Option<String>
+    ) -> StoredBoards<'a> {
+        self.actor = actor;
+        self
+    }
+
+    // for commands that only look at boards. an older kv board
+    // isn't upgraded on the way, it's an error instead
+    pub fn read_only(mut self) -> StoredBoards<'a> {
+        self.read_only = true;
+        self
+    }
+
+    // markdown boards are backed up on their own, the others
+    // along with the rest of the kanben folder
+    pub fn archive(&self, board: &str) -> ZipArchive {
+        match self.settings.store {
+            Backend::Markdown => ZipArchive::new(
+                &self.settings.board_path(self.cfg_location, board)
+            ),
+            _ => ZipArchive::new(self.cfg_location)
+        }
+    }
+
+    fn kv(&self) -> Result<&KvStore, KanbenError> {
+        self.kv_store.as_ref()
+            .ok_or(KanbenError::Store("the kv store isn't open".to_owned()))
+    }
+
+    fn with_kv_board<V: BoardVisitor>(
+        &self,
+        board: &str,
+        visitor: &mut V
+    ) -> Result<(), KanbenError> {
+        let kv_store = self.kv()?;
+        upgrade_kv(
+            kv_store,
+            board,
+            &ZipArchive::new(self.cfg_location),
+            self.read_only
+        )?;
+
+        let bucket = kv_bucket::<Json<Task>>(kv_store, board, "tasks")?;
+        let col_bucket = kv_bucket::<Json<Vec<String>>>(
+            kv_store, board, "columns"
+        )?;
+        let tag_bucket = kv_bucket::<Json<Vec<String>>>(
+            kv_store, board, "tags"
+        )?;
+        let config_bucket = kv_bucket::<Json<BoardConfig>>(
+            kv_store, board, "board"
+        )?;
+        let trash_bucket = kv_bucket::<Json<Trashed>>(
+            kv_store, board, "trash"
+        )?;
+        let journal_bucket = kv_bucket::<Json<Entry>>(
+            kv_store, board, "journal"
+        )?;
+        let history_bucket = kv_bucket::<Json<Event>>(
+            kv_store, board, "history"
+        )?;
+        let pending_bucket = kv_bucket::<Json<Pending>>(
+            kv_store, board, "pending"
+        )?;
+
+        let mut store = PersistantStore::new(&bucket);
+        let mut col_store = PersistantStore::new(&col_bucket);
+        let mut tag_store = PersistantStore::new(&tag_bucket);
+        let mut config_store = PersistantStore::new(&config_bucket);
+        let mut trash_store = PersistantStore::new(&trash_bucket);
+        let mut journal_store = PersistantStore::new(&journal_bucket);
+        let mut history_store = PersistantStore::new(&history_bucket);
+        let mut pending_store = PersistantStore::new(&pending_bucket);
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        ).with_trash(&mut trash_store)
+            .with_journal(&mut journal_store)
+            .with_history(&mut history_store)
+            .with_pending(&mut pending_store)
+            .with_actor(self.actor.clone());
+
+        board.recover()?;
+        visitor.visit(&mut board)
+    }
+
+    // a board kept as markdown files, see `store::MarkdownStore`
+    fn with_markdown_board<V: BoardVisitor>(
+        &self,
+        board: &str,
+        visitor: &mut V
+    ) -> Result<(), KanbenError> {
+        let board_path = self.settings.board_path(self.cfg_location, board);
+        let tasks_path = format!("{}/tasks", board_path);
+        let mut store = MarkdownStore::new(&tasks_path);
+        let mut col_store = MarkdownIndex::new(&board_path, Index::Columns);
+        let mut tag_store = MarkdownIndex::new(&board_path, Index::Tags);
+        let mut config_store = YamlStore::new(
+            &format!("{}/board.yml", board_path)
+        );
+        let mut trash_store = MarkdownTrash::new(&board_path);
+        let mut journal_store = YamlStore::new(
+            &format!("{}/journal.yml", board_path)
+        );
+        let mut history_store = YamlStore::new(
+            &format!("{}/history.yml", board_path)
+        );
+        let mut pending_store = YamlStore::new(
+            &format!("{}/pending.yml", board_path)
+        );
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        ).with_trash(&mut trash_store)
+            .with_journal(&mut journal_store)
+            .with_history(&mut history_store)
+            .with_pending(&mut pending_store)
+            .with_actor(self.actor.clone());
+
+        board.recover()?;
+        visitor.visit(&mut board)
+    }
+
+    // a board kept in an sqlite database, see `store::sqlite`
+    #[cfg(feature = "sqlite")]
+    fn with_sqlite_board<V: BoardVisitor>(
+        &self,
+        board: &str,
+        visitor: &mut V
+    ) -> Result<(), KanbenError> {
+        let db = sqlite::open(
+            &self.settings.board_path(self.cfg_location, board)
+        )?;
+        let mut store = SqliteStore::new(&db);
+        let mut col_store = SqliteIndex::new(&db, Index::Columns);
+        let mut tag_store = SqliteIndex::new(&db, Index::Tags);
+        let mut config_store = SqliteRecords::new(&db);
+        let mut trash_store = SqliteRecords::in_table(&db, "trash");
+        let mut journal_store = SqliteRecords::in_table(&db, "journal");
+        let mut history_store = SqliteRecords::in_table(&db, "history");
+        let mut board = Board::new(
+            &mut store,
+            &mut col_store,
+            &mut tag_store,
+            &mut config_store
+        ).with_trash(&mut trash_store)
+            .with_journal(&mut journal_store)
+            .with_history(&mut history_store)
+            .with_transaction(&db)
+            .with_actor(self.actor.clone());
+
+        // a read that failed is reported even if nothing was
+        // written after it
+        visitor.visit(&mut board)?;
+        db.check()
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    fn with_sqlite_board<V: BoardVisitor>(
+        &self,
+        _board: &str,
+        _visitor: &mut V
+    ) -> Result<(), KanbenError> {
+        Err(KanbenError::SqliteUnavailable)
+    }
+}
+
+impl<'a> BoardStorage for StoredBoards<'a> {
+    fn task_count(&self, board: &str) -> Result<usize, KanbenError> {
+        let mut count = CountTasks(0);
+        self.with_board(board, &mut count)?;
+        Ok(count.0)
+    }
+
+    fn drop_board(&mut self, board: &str) -> Result<(), KanbenError> {
+        match self.settings.store {
+            Backend::Kv => {
+                let kv_store = self.kv()?;
+                for bucket in &[
+                    "tasks", "columns", "tags", "board", "meta", "trash",
+                    "journal", "history", "pending"
+                ] {
+                    kv_store.drop_bucket(bucket_name(board, bucket))
+                        .map_err(store_error)?;
+                }
+            },
+            _ => remove_path(
+                &self.settings.board_path(self.cfg_location, board)
+            )?
+        }
+        remove_path(&format!("{}/boards/{}", self.cfg_location, board))
+    }
+
+    fn with_board<V: BoardVisitor>(
+        &self,
+        board: &str,
+        visitor: &mut V
+    ) -> Result<(), KanbenError> {
+        match self.settings.store {
+            Backend::Kv => self.with_kv_board(board, visitor),
+            Backend::Markdown => self.with_markdown_board(board, visitor),
+            Backend::Sqlite => self.with_sqlite_board(board, visitor)
+        }
+    }
+}
+
+struct CountTasks(usize);
+
+impl BoardVisitor for CountTasks {
+    fn visit<B: BoardAccess>(
+        &mut self,
+        board: &mut B
+    ) -> Result<(), KanbenError> {
+        self.0 = board.get_all_tasks().len();
+        Ok(())
+    }
+}
+
+// named boards keep their buckets apart with a prefix. main
+// keeps the plain names older versions used
+fn bucket_name(board: &str, bucket: &str) -> String {
+    if board == MAIN_BOARD {
+        bucket.to_owned()
+    } else {
+        format!("{}:{}", board, bucket)
+    }
+}
+
+fn kv_bucket<'a, V: Value>(
+    kv_store: &KvStore,
+    board: &str,
+    bucket: &str
+) -> Result<Bucket<'a, String, V>, KanbenError> {
+    kv_store.bucket::<String, V>(Some(&bucket_name(board, bucket)))
+        .map_err(store_error)
+}
+
+// anything kept outside the store, like descriptions
+pub fn files_path(cfg_location: &str, board: &str) -> String {
+    if board == MAIN_BOARD {
+        format!("{}/files", cfg_location)
+    } else {
+        format!("{}/boards/{}/files", cfg_location, board)
+    }
+}
+
+fn open_kv(cfg_location: &str) -> Result<KvStore, KanbenError> {
+    let cfg = Config::new(cfg_location);
+    KvStore::new(cfg).map_err(store_error)
+}
+
+// older boards are upgraded before anything reads them, unless
+// they're only being read - a board is upgraded when it's opened
+// to be worked on
+fn upgrade_kv(
+    kv_store: &KvStore,
+    board: &str,
+    archive: &ZipArchive,
+    read_only: bool
+) -> Result<(), KanbenError> {
+    let raw_bucket = kv_bucket::<Json<serde_json::Value>>(
+        kv_store, board, "tasks"
+    )?;
+    let col_bucket = kv_bucket::<Json<Vec<String>>>(
+        kv_store, board, "columns"
+    )?;
+    let config_bucket = kv_bucket::<Json<BoardConfig>>(
+        kv_store, board, "board"
+    )?;
+    let meta_bucket = kv_bucket::<Json<u32>>(kv_store, board, "meta")?;
+
+    let mut raw_store = PersistantStore::new(&raw_bucket);
+    let mut col_store = PersistantStore::new(&col_bucket);
+    let mut config_store = PersistantStore::new(&config_bucket);
+    let mut meta_store = PersistantStore::new(&meta_bucket);
+    let mut migrator = Migrator::new(
+        &mut raw_store,
+        &mut col_store,
+        &mut config_store,
+        &mut meta_store
+    );
+    if read_only {
+        return match migrator.needs_migrating()? {
+            true => Err(KanbenError::NeedsUpgrade(board.to_owned())),
+            false => Ok(())
+        };
+    }
+    if let Some(backup) = migrator.migrate(archive)? {
+        eprintln!(
+            "Upgraded the board. The old data was copied to '{}'.",
+            backup
+        );
+    }
+    Ok(())
+}
+
+// copies the selected kv board into sqlite, wherever
+// settings.yml would look for it
+#[cfg(feature = "sqlite")]
+pub fn migrate_store(
+    cfg_location: &str,
+    settings: &Settings,
+    board: &str
+) -> Result<(), KanbenError> {
+    let kv_store = open_kv(cfg_location)?;
+    upgrade_kv(&kv_store, board, &ZipArchive::new(cfg_location), false)?;
+
+    let bucket = kv_bucket::<Json<Task>>(
+        &kv_store, board, "tasks"
+    )?;
+    let col_bucket = kv_bucket::<Json<Vec<String>>>(
+        &kv_store, board, "columns"
+    )?;
+    let tag_bucket = kv_bucket::<Json<Vec<String>>>(
+        &kv_store, board, "tags"
+    )?;
+    let config_bucket = kv_bucket::<Json<BoardConfig>>(
+        &kv_store, board, "board"
+    )?;
+    let trash_bucket = kv_bucket::<Json<Trashed>>(
+        &kv_store, board, "trash"
+    )?;
+    let journal_bucket = kv_bucket::<Json<Entry>>(
+        &kv_store, board, "journal"
+    )?;
+    let history_bucket = kv_bucket::<Json<Event>>(
+        &kv_store, board, "history"
+    )?;
+    let pending_bucket = kv_bucket::<Json<Pending>>(
+        &kv_store, board, "pending"
+    )?;
+    let mut store = PersistantStore::new(&bucket);
+    let mut col_store = PersistantStore::new(&col_bucket);
+    let mut tag_store = PersistantStore::new(&tag_bucket);
+    let mut config_store = PersistantStore::new(&config_bucket);
+    let mut trash_store = PersistantStore::new(&trash_bucket);
+    let mut journal_store = PersistantStore::new(&journal_bucket);
+    let mut history_store = PersistantStore::new(&history_bucket);
+    let mut pending_store = PersistantStore::new(&pending_bucket);
+
+    // a change cut short is finished before anything's copied
+    Board::new(
+        &mut store,
+        &mut col_store,
+        &mut tag_store,
+        &mut config_store
+    ).with_trash(&mut trash_store)
+        .with_journal(&mut journal_store)
+        .with_history(&mut history_store)
+        .with_pending(&mut pending_store)
+        .recover()?;
+
+    let db = sqlite::open(
+        &settings.path_for(Backend::Sqlite, cfg_location, board)
+    )?;
+    let mut new_store = SqliteStore::new(&db);
+    let mut new_col_store = SqliteIndex::new(&db, Index::Columns);
+    let mut new_tag_store = SqliteIndex::new(&db, Index::Tags);
+    let mut new_config_store = SqliteRecords::new(&db);
+    let mut new_trash_store = SqliteRecords::in_table(&db, "trash");
+    let mut new_journal_store = SqliteRecords::in_table(&db, "journal");
+    let mut new_history_store = SqliteRecords::in_table(&db, "history");
+
+    let stdout = std::io::stdout();
+    let mut writer = stdout.lock();
+
+    crate::commands::migrate_store(
+        (&store, &col_store, &tag_store, &config_store),
+        (
+            &mut new_store,
+            &mut new_col_store,
+            &mut new_tag_store,
+            &mut new_config_store
+        ),
+        (&trash_store, &journal_store, &history_store),
+        (
+            &mut new_trash_store,
+            &mut new_journal_store,
+            &mut new_history_store
+        ),
+        &mut writer
+    )?;
+    db.check()
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub fn migrate_store(
+    _cfg_location: &str,
+    _settings: &Settings,
+    _board: &str
+) -> Result<(), KanbenError> {
+    Err(KanbenError::SqliteUnavailable)
+}
+
+fn remove_path(path: &str) -> Result<(), KanbenError> {
+    let path = Path::new(path);
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else if path.exists() {
+        fs::remove_file(path)
+    } else {
+        Ok(())
+    };
+    result.map_err(|e| KanbenError::Store(e.to_string()))
+}
+
+fn store_error(e: kv::Error) -> KanbenError {
+    KanbenError::Store(e.to_string())
+}
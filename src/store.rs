@@ -1,46 +1,147 @@
-use kv::{Bucket, Json, Codec};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use kv::{Batch, Bucket, Json, Codec};
+use crate::error::KanbenError;
 
+mod markdown;
+mod yaml;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub use markdown::{MarkdownStore, MarkdownIndex, MarkdownTrash, Index, body};
+pub use yaml::YamlStore;
+
+// writes made between `begin` and `commit` are held back and
+// written to the bucket together as a single batch, so either
+// all of them land or none do. `rollback` throws them away.
 pub trait Store <T>{
     fn get_all(&self) -> Vec<T>;
+    fn keys(&self) -> Vec<String>;
+    // records that are stored but can't be read back, which
+    // get_all and keys leave out
+    fn corrupt_keys(&self) -> Vec<String>;
     fn get(&self, key: &str) -> Option<T>;
+    // the record as it was last committed, leaving out anything
+    // written to it since `begin`
+    fn stored(&self, key: &str) -> Option<T>;
+    // the records for several keys, in the order asked for.
+    // missing keys are skipped
+    fn get_many(&self, keys: &[String]) -> Vec<T> {
+        keys.iter().filter_map(|key| self.get(key)).collect()
+    }
     fn set(
         &mut self,
         key: &str,
         value: T
-    );
-    fn rm(&mut self, key: &str);
+    ) -> Result<(), KanbenError>;
+    fn rm(&mut self, key: &str) -> Result<(), KanbenError>;
+    fn begin(&mut self);
+    // the writes held back since `begin`, with None for a key
+    // waiting to be removed
+    fn staged(&self) -> Vec<(String, Option<T>)>;
+    fn commit(&mut self) -> Result<(), KanbenError>;
+    fn rollback(&mut self);
+}
+
+// one transaction around every store of a board, for stores kept
+// somewhere that can commit them all at once. each store still
+// holds its own writes back until it's committed
+pub trait Transaction {
+    fn begin(&self) -> Result<(), KanbenError>;
+    fn commit(&self) -> Result<(), KanbenError>;
+    // does nothing once the transaction's committed
+    fn rollback(&self);
 }
 
 pub struct PersistantStore <'a, T: serde::Serialize + serde::de::DeserializeOwned> {
-    bucket: &'a Bucket<'a, String, Json<T>>
+    bucket: &'a Bucket<'a, String, Json<T>>,
+    // None marks a key waiting to be removed
+    pending: Option<BTreeMap<String, Option<T>>>
 }
 
-impl <'a, T: serde::Serialize + serde::de::DeserializeOwned> PersistantStore <'a, T> {
+impl <'a, T: serde::Serialize + serde::de::DeserializeOwned + Clone> PersistantStore <'a, T> {
     pub fn new(bucket: &'a Bucket<String, Json<T>>) -> PersistantStore<'a, T> {
 
-        PersistantStore{ bucket }
+        PersistantStore{ bucket, pending: None }
+    }
+
+    fn entries(&self) -> BTreeMap<String, T> {
+        let mut items: BTreeMap<String, T> = self.bucket.iter()
+            .filter_map(|item| { 
+                let item = item.ok()?;
+                let key = item.key::<String>().ok()?;
+                let value = item.value::<Json<T>>().ok()?;
+                Some((key, value.to_inner()))
+            }).collect();
+
+        if let Some(pending) = &self.pending {
+            for (key, value) in pending.iter() {
+                match value {
+                    Some(v) => items.insert(key.clone(), v.clone()),
+                    None => items.remove(key)
+                };
+            }
+        }
+
+        items
     }
 }
 
+fn store_error(e: kv::Error) -> KanbenError {
+    KanbenError::Store(e.to_string())
+}
+
+// file based stores write to a temporary file first, so a file
+// is either replaced whole or not at all
+fn write_file(path: &Path, contents: &str) -> Result<(), KanbenError> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents)
+        .and_then(|_| fs::rename(&tmp, path))
+        .map_err(|e| KanbenError::Store(e.to_string()))
+}
+
 impl <'a, 
-    T: serde::Serialize + serde::de::DeserializeOwned
+    T: serde::Serialize + serde::de::DeserializeOwned + Clone
 > Store<T> for PersistantStore<'a, T> {
     fn get_all(&self) -> Vec<T> {
-        self.bucket.iter().filter_map(|item| { 
-            let task = item.expect("unable to get item");
-            let json_value_result = task.value::<Json<T>>();
-            match json_value_result {
-                Ok(v) => Some(v.to_inner()),
-                Err(_) => None
-            }
-        }).collect()
+        self.entries().into_iter().map(|(_, v)| v).collect()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.entries().into_iter().map(|(k, _)| k).collect()
     }
 
-    fn set(&mut self, key: &str, value: T) {
-        let _ = self.bucket.set(String::from(key), Json(value));
+    fn corrupt_keys(&self) -> Vec<String> {
+        self.bucket.iter()
+            .filter_map(|item| item.ok())
+            .filter(|item| item.value::<Json<T>>().is_err())
+            .filter_map(|item| item.key::<String>().ok())
+            .filter(|key| {
+                !self.pending.as_ref().map_or(false, |p| p.contains_key(key))
+            })
+            .collect()
+    }
+
+    fn set(&mut self, key: &str, value: T) -> Result<(), KanbenError> {
+        if let Some(pending) = &mut self.pending {
+            pending.insert(key.to_owned(), Some(value));
+            return Ok(());
+        }
+
+        self.bucket.set(String::from(key), Json(value))
+            .map_err(store_error)
     }
 
     fn get(&self, key: &str) -> Option<T> {
+        if let Some(value) = self.pending.as_ref()
+            .and_then(|p| p.get(key)) {
+            return value.clone();
+        }
+
+        self.stored(key)
+    }
+
+    fn stored(&self, key: &str) -> Option<T> {
         let get_result = self.bucket.get(key.to_owned());
 
         let item_result = match get_result {
@@ -57,9 +158,103 @@ impl <'a,
         }
     }
 
-    fn rm(&mut self, key: &str) {
-        let _ = self.bucket.remove(String::from(key));
+    fn rm(&mut self, key: &str) -> Result<(), KanbenError> {
+        if let Some(pending) = &mut self.pending {
+            pending.insert(key.to_owned(), None);
+            return Ok(());
+        }
+
+        self.bucket.remove(String::from(key))
+            .map_err(store_error)
+    }
+
+    fn begin(&mut self) {
+        if self.pending.is_none() {
+            self.pending = Some(BTreeMap::new());
+        }
+    }
+
+    fn staged(&self) -> Vec<(String, Option<T>)> {
+        self.pending.clone().map_or(vec!(), |p| p.into_iter().collect())
+    }
+
+    fn commit(&mut self) -> Result<(), KanbenError> {
+        let pending = match self.pending.take() {
+            Some(p) => p,
+            None => return Ok(())
+        };
+
+        let mut batch = Batch::new();
+        for (key, value) in pending {
+            match value {
+                Some(v) => batch.set(key, &Json(v)),
+                None => batch.remove(key)
+            }.map_err(store_error)?;
+        }
+
+        self.bucket.batch(batch).map_err(store_error)
+    }
+
+    fn rollback(&mut self) {
+        self.pending = None;
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kv::{Config, Store as KvStore};
+
+    fn temp_store(name: &str) -> KvStore {
+        let path = format!("/tmp/kanben-store-test-{}", name);
+        KvStore::new(Config::new(path).temporary(true)).unwrap()
+    }
 
+    #[test]
+    fn writes_are_held_back_until_commit() {
+        let kv_store = temp_store("commit");
+        let bucket = kv_store.bucket::<String, Json<String>>(None).unwrap();
+        let mut store = PersistantStore::new(&bucket);
+
+        store.begin();
+        store.set("a", "one".to_owned()).unwrap();
+        store.set("b", "two".to_owned()).unwrap();
+
+        assert_eq!(store.get("a"), Some("one".to_owned()));
+        assert_eq!(bucket.len(), 0);
+
+        store.commit().unwrap();
+
+        assert_eq!(bucket.len(), 2);
+        assert_eq!(store.get_all(), vec!("one", "two"));
+    }
+
+    #[test]
+    fn rollback_discards_pending_writes() {
+        let kv_store = temp_store("rollback");
+        let bucket = kv_store.bucket::<String, Json<String>>(None).unwrap();
+        let mut store = PersistantStore::new(&bucket);
+        store.set("a", "one".to_owned()).unwrap();
+
+        store.begin();
+        store.set("b", "two".to_owned()).unwrap();
+        store.rm("a").unwrap();
+        assert_eq!(store.get_all(), vec!("two"));
+        store.rollback();
+
+        assert_eq!(store.get_all(), vec!("one"));
+    }
+
+    #[test]
+    fn it_reports_records_that_cant_be_read() {
+        let kv_store = temp_store("corrupt");
+        let bucket = kv_store.bucket::<String, Json<String>>(None).unwrap();
+        let raw = kv_store.bucket::<String, String>(None).unwrap();
+        raw.set("bad", "not json").unwrap();
+        let mut store = PersistantStore::new(&bucket);
+        store.set("good", "fine".to_owned()).unwrap();
+
+        assert_eq!(store.keys(), vec!("good"));
+        assert_eq!(store.corrupt_keys(), vec!("bad"));
+    }
+}
@@ -0,0 +1,790 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use crate::opts::{Task, Times};
+use crate::board::Trashed;
+use crate::error::KanbenError;
+use super::{Store, YamlStore, write_file};
+
+// a board kept as plain text, one markdown file per task. the
+// task's details sit in yaml front matter and the description
+// is the body, so a board can be committed and diffed like any
+// other file:
+//
+//     ---
+//     name: write the docs
+//     id: 4
+//     column: doing
+//     tags:
+//       - docs
+//     times:
+//       created: 1710000000
+//     ---
+//     what the task is about
+//
+// the column and tag lists are kept separately - see
+// `MarkdownIndex`
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+struct FrontMatter {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<u64>,
+    column: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tags: Option<Vec<String>>,
+    // position in the column, lowest first. only read from boards
+    // saved before columns.yml, see `MarkdownIndex`
+    #[serde(default, skip_serializing)]
+    order: Option<usize>,
+    #[serde(default, skip_serializing_if = "Times::is_empty")]
+    times: Times,
+}
+
+struct TaskFile {
+    path: PathBuf,
+    front: FrontMatter,
+    body: String,
+}
+
+impl TaskFile {
+    fn task(&self) -> Task {
+        Task{
+            name: self.front.name.clone(),
+            column: self.front.column.clone(),
+            description: Some(self.path.to_string_lossy().into_owned()),
+            tags: self.front.tags.clone(),
+            id: self.front.id,
+            times: self.front.times.clone()
+        }
+    }
+
+    fn save(&self) -> Result<(), KanbenError> {
+        let front = serde_yaml::to_string(&self.front)
+            .map_err(|e| KanbenError::Store(e.to_string()))?;
+        let front = front.trim_start_matches("---\n");
+        write_file(
+            &self.path,
+            &format!("---\n{}\n---\n{}", front.trim_end(), self.body)
+        )
+    }
+}
+
+// splits a file into its front matter and body. text without
+// front matter is all body
+fn split(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix("---\n")?;
+    if let Some(front) = rest.strip_prefix("---") {
+        return Some(("", front.strip_prefix("\n").unwrap_or(front)));
+    }
+    let end = rest.find("\n---\n").map(|i| (i, i + 5))
+        .or_else(|| {
+            if rest.ends_with("\n---") {
+                Some((rest.len() - 4, rest.len()))
+            } else {
+                None
+            }
+        })?;
+    Some((&rest[..end.0], &rest[end.1..]))
+}
+
+// the description part of a task file. anything that isn't a
+// task file comes back as it is
+pub fn body(text: &str) -> &str {
+    match split(text) {
+        Some((front, body)) if serde_yaml::from_str::<FrontMatter>(
+            front
+        ).is_ok() => body,
+        _ => text
+    }
+}
+
+fn read(path: &Path) -> Option<TaskFile> {
+    let text = fs::read_to_string(path).ok()?;
+    let (front, body) = split(&text)?;
+    let front = serde_yaml::from_str(front).ok()?;
+    Some(TaskFile{
+        path: path.to_owned(),
+        front,
+        body: body.to_owned()
+    })
+}
+
+fn task_paths(dir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().map_or(false, |x| x == "md"))
+            .collect(),
+        Err(_) => vec!()
+    };
+    paths.sort();
+    paths
+}
+
+fn task_files(dir: &Path) -> Vec<TaskFile> {
+    task_paths(dir).iter().filter_map(|p| read(p)).collect()
+}
+
+// lowercase words joined by dashes, so a title makes a readable
+// filename
+fn slug(key: &str) -> String {
+    let slug = key.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-");
+    if slug.is_empty() { "task".to_owned() } else { slug }
+}
+
+// most tasks live at their slug, but titles that slug the same
+// get a number on the end, so there's a fallback search
+fn find(dir: &Path, key: &str) -> Option<TaskFile> {
+    let path = dir.join(format!("{}.md", slug(key)));
+    match read(&path) {
+        Some(file) if file.front.name == key => Some(file),
+        _ => task_files(dir).into_iter().find(|f| f.front.name == key)
+    }
+}
+
+fn new_path(dir: &Path, key: &str) -> PathBuf {
+    let slug = slug(key);
+    let mut path = dir.join(format!("{}.md", slug));
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = dir.join(format!("{}-{}.md", slug, n));
+    }
+    path
+}
+
+pub struct MarkdownStore {
+    dir: PathBuf,
+    // None marks a key waiting to be removed
+    pending: Option<BTreeMap<String, Option<Task>>>
+}
+
+impl MarkdownStore {
+    pub fn new(dir: &str) -> MarkdownStore {
+        MarkdownStore{ dir: PathBuf::from(dir), pending: None }
+    }
+
+    fn entries(&self) -> BTreeMap<String, Task> {
+        let mut items: BTreeMap<String, Task> = task_files(&self.dir)
+            .iter()
+            .map(|f| (f.front.name.clone(), f.task()))
+            .collect();
+
+        if let Some(pending) = &self.pending {
+            for (key, value) in pending.iter() {
+                match value {
+                    Some(v) => items.insert(key.clone(), v.clone()),
+                    None => items.remove(key)
+                };
+            }
+        }
+
+        items
+    }
+
+    fn write(&self, key: &str, task: Task) -> Result<(), KanbenError> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| KanbenError::Store(e.to_string()))?;
+
+        let existing = find(&self.dir, key);
+        let path = match &existing {
+            Some(file) => file.path.clone(),
+            None => new_path(&self.dir, key)
+        };
+
+        // a description kept anywhere else (another task file
+        // when renaming, or a file from the kv store) is copied
+        // in as the body
+        let imported = task.description.as_ref()
+            .filter(|d| Path::new(d) != path)
+            .and_then(|d| fs::read_to_string(d).ok())
+            .map(|text| body(&text).to_owned());
+        let body = imported
+            .or_else(|| existing.as_ref().map(|f| f.body.clone()))
+            .unwrap_or_default();
+
+        let file = TaskFile{
+            path,
+            front: FrontMatter{
+                name: key.to_owned(),
+                id: task.id,
+                column: task.column,
+                tags: task.tags,
+                order: None,
+                times: task.times
+            },
+            body
+        };
+        file.save()
+    }
+
+    fn delete(&self, key: &str) -> Result<(), KanbenError> {
+        match find(&self.dir, key) {
+            Some(file) => fs::remove_file(&file.path)
+                .map_err(|e| KanbenError::Store(e.to_string())),
+            None => Ok(())
+        }
+    }
+}
+
+impl Store<Task> for MarkdownStore {
+    fn get_all(&self) -> Vec<Task> {
+        self.entries().into_iter().map(|(_, v)| v).collect()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.entries().into_iter().map(|(k, _)| k).collect()
+    }
+
+    fn corrupt_keys(&self) -> Vec<String> {
+        task_paths(&self.dir).iter()
+            .filter(|p| read(p).is_none())
+            .filter_map(|p| p.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn get(&self, key: &str) -> Option<Task> {
+        if let Some(pending) = &self.pending {
+            if let Some(value) = pending.get(key) {
+                return value.clone();
+            }
+        }
+
+        self.stored(key)
+    }
+
+    fn stored(&self, key: &str) -> Option<Task> {
+        find(&self.dir, key).map(|f| f.task())
+    }
+
+    fn set(&mut self, key: &str, value: Task) -> Result<(), KanbenError> {
+        match &mut self.pending {
+            Some(pending) => {
+                pending.insert(key.to_owned(), Some(value));
+                Ok(())
+            },
+            None => self.write(key, value)
+        }
+    }
+
+    fn rm(&mut self, key: &str) -> Result<(), KanbenError> {
+        match &mut self.pending {
+            Some(pending) => {
+                pending.insert(key.to_owned(), None);
+                Ok(())
+            },
+            None => self.delete(key)
+        }
+    }
+
+    fn begin(&mut self) {
+        self.pending = Some(BTreeMap::new());
+    }
+
+    fn staged(&self) -> Vec<(String, Option<Task>)> {
+        self.pending.clone().map_or(vec!(), |p| p.into_iter().collect())
+    }
+
+    // files are written one at a time, so unlike the kv store
+    // a failure part way through can leave some of them written,
+    // until the board finishes the change from its pending
+    // record. new files are written before old ones go, so a
+    // rename can still copy the description across
+    fn commit(&mut self) -> Result<(), KanbenError> {
+        let pending = match self.pending.take() {
+            Some(p) => p,
+            None => return Ok(())
+        };
+
+        for (key, value) in pending.iter() {
+            if let Some(task) = value {
+                self.write(key, task.clone())?;
+            }
+        }
+        for (key, value) in pending.iter() {
+            if value.is_none() {
+                self.delete(key)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn rollback(&mut self) {
+        self.pending = None;
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Index {
+    Columns,
+    Tags,
+}
+
+// the column or tag lists, kept in columns.yml and tags.yml
+// beside the task files, so reading one doesn't mean reading
+// every task. `kanben fsck --repair` brings them back in line
+// with the files after they've been edited by hand
+pub struct MarkdownIndex {
+    records: YamlStore<Vec<String>>,
+    path: PathBuf,
+    tasks_dir: PathBuf,
+    index: Index,
+    // the lists worked out from the task files are waiting to be
+    // committed, see `seed`
+    seeded: bool
+}
+
+impl MarkdownIndex {
+    pub fn new(board_dir: &str, index: Index) -> MarkdownIndex {
+        let board_dir = Path::new(board_dir);
+        let path = board_dir.join(match index {
+            Index::Columns => "columns.yml",
+            Index::Tags => "tags.yml"
+        });
+        MarkdownIndex{
+            records: YamlStore::new(&path.to_string_lossy()),
+            path,
+            tasks_dir: board_dir.join("tasks"),
+            index,
+            seeded: false
+        }
+    }
+
+    fn indexed(&self) -> bool {
+        self.seeded || self.path.exists()
+    }
+
+    // boards from before the index files were kept have their
+    // lists worked out from the task files, columns in the
+    // `order` their front matter gave them
+    fn file_lists(&self) -> BTreeMap<String, Vec<String>> {
+        let mut files = task_files(&self.tasks_dir);
+        files.sort_by_key(|f| f.front.order.unwrap_or(usize::MAX));
+
+        let mut lists: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for file in files {
+            let labels = match self.index {
+                Index::Columns => vec!(file.front.column.clone()),
+                Index::Tags => file.front.tags.clone().unwrap_or_default()
+            };
+            for label in labels {
+                lists.entry(label).or_default().push(file.front.name.clone());
+            }
+        }
+        lists
+    }
+
+    // the first write to an older board's index saves every list
+    // along with it
+    fn seed(&mut self) -> Result<(), KanbenError> {
+        if self.indexed() {
+            return Ok(());
+        }
+        for (label, list) in self.file_lists() {
+            self.records.set(&label, list)?;
+        }
+        self.seeded = true;
+        Ok(())
+    }
+}
+
+impl Store<Vec<String>> for MarkdownIndex {
+    fn get_all(&self) -> Vec<Vec<String>> {
+        if self.indexed() {
+            self.records.get_all()
+        } else {
+            self.file_lists().into_iter().map(|(_, v)| v).collect()
+        }
+    }
+
+    fn keys(&self) -> Vec<String> {
+        if self.indexed() {
+            self.records.keys()
+        } else {
+            self.file_lists().into_iter().map(|(k, _)| k).collect()
+        }
+    }
+
+    fn corrupt_keys(&self) -> Vec<String> {
+        self.records.corrupt_keys()
+    }
+
+    fn get(&self, key: &str) -> Option<Vec<String>> {
+        if self.indexed() {
+            self.records.get(key)
+        } else {
+            self.file_lists().remove(key)
+        }
+    }
+
+    fn stored(&self, key: &str) -> Option<Vec<String>> {
+        if self.path.exists() {
+            self.records.stored(key)
+        } else {
+            self.file_lists().remove(key)
+        }
+    }
+
+    fn set(
+        &mut self,
+        key: &str,
+        value: Vec<String>
+    ) -> Result<(), KanbenError> {
+        self.seed()?;
+        self.records.set(key, value)
+    }
+
+    fn rm(&mut self, key: &str) -> Result<(), KanbenError> {
+        self.seed()?;
+        self.records.rm(key)
+    }
+
+    fn begin(&mut self) {
+        self.records.begin();
+    }
+
+    fn staged(&self) -> Vec<(String, Option<Vec<String>>)> {
+        self.records.staged()
+    }
+
+    fn commit(&mut self) -> Result<(), KanbenError> {
+        self.seeded = false;
+        self.records.commit()
+    }
+
+    fn rollback(&mut self) {
+        self.seeded = false;
+        self.records.rollback();
+    }
+}
+
+// the trash of a markdown board, listed in trash.yml. a task's
+// description is the body of its file, which goes when the task
+// does, so the body is copied into trash/ first and restoring
+// the task copies it back in
+pub struct MarkdownTrash {
+    records: YamlStore<Trashed>,
+    dir: PathBuf,
+    in_transaction: bool,
+    // copies to remove once the records are committed
+    removed: Vec<String>
+}
+
+impl MarkdownTrash {
+    pub fn new(board_dir: &str) -> MarkdownTrash {
+        let board_dir = Path::new(board_dir);
+        MarkdownTrash{
+            records: YamlStore::new(
+                &board_dir.join("trash.yml").to_string_lossy()
+            ),
+            dir: board_dir.join("trash"),
+            in_transaction: false,
+            removed: vec!()
+        }
+    }
+
+    // a copy left by a change that was rolled back is simply
+    // written over next time
+    fn keep_description(
+        &self,
+        key: &str,
+        trashed: &mut Trashed
+    ) -> Result<(), KanbenError> {
+        let text = match trashed.task.description.as_ref()
+            .and_then(|d| fs::read_to_string(d).ok()) {
+            Some(t) => t,
+            None => return Ok(())
+        };
+
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| KanbenError::Store(e.to_string()))?;
+        let path = self.dir.join(format!("{}.md", slug(key)));
+        write_file(&path, body(&text))?;
+        trashed.task.description = Some(path.to_string_lossy().into_owned());
+        Ok(())
+    }
+
+    fn purge(&mut self) {
+        for path in self.removed.drain(..) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+impl Store<Trashed> for MarkdownTrash {
+    fn get_all(&self) -> Vec<Trashed> {
+        self.records.get_all()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.records.keys()
+    }
+
+    fn corrupt_keys(&self) -> Vec<String> {
+        self.records.corrupt_keys()
+    }
+
+    fn get(&self, key: &str) -> Option<Trashed> {
+        self.records.get(key)
+    }
+
+    fn stored(&self, key: &str) -> Option<Trashed> {
+        self.records.stored(key)
+    }
+
+    fn set(&mut self, key: &str, value: Trashed) -> Result<(), KanbenError> {
+        let mut value = value;
+        self.keep_description(key, &mut value)?;
+        self.records.set(key, value)
+    }
+
+    fn rm(&mut self, key: &str) -> Result<(), KanbenError> {
+        let copy = self.records.get(key)
+            .and_then(|t| t.task.description)
+            .filter(|d| Path::new(d).starts_with(&self.dir));
+        self.records.rm(key)?;
+        self.removed.extend(copy);
+        if !self.in_transaction {
+            self.purge();
+        }
+        Ok(())
+    }
+
+    fn begin(&mut self) {
+        self.in_transaction = true;
+        self.records.begin();
+    }
+
+    fn staged(&self) -> Vec<(String, Option<Trashed>)> {
+        self.records.staged()
+    }
+
+    fn commit(&mut self) -> Result<(), KanbenError> {
+        self.in_transaction = false;
+        self.records.commit()?;
+        self.purge();
+        Ok(())
+    }
+
+    fn rollback(&mut self) {
+        self.in_transaction = false;
+        self.removed.clear();
+        self.records.rollback();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn board_dir(name: &str) -> String {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH).unwrap()
+            .as_nanos();
+        temp_dir().join(format!("kanben-md-{}-{}", name, nanos))
+            .to_string_lossy().into_owned()
+    }
+
+    fn task(name: &str, column: &str) -> Task {
+        Task{
+            name: name.to_owned(),
+            column: column.to_owned(),
+            description: None,
+            tags: None,
+            id: Some(1),
+            times: Times::default()
+        }
+    }
+
+    #[test]
+    fn each_task_is_a_markdown_file() {
+        let dir = board_dir("file");
+        let mut store = MarkdownStore::new(&dir);
+        let mut t = task("Write the docs", "doing");
+        t.tags = Some(vec!("docs".to_owned()));
+
+        store.set("Write the docs", t).unwrap();
+
+        let text = fs::read_to_string(
+            Path::new(&dir).join("write-the-docs.md")
+        ).unwrap();
+        assert_eq!(
+            text,
+            "---\nname: Write the docs\nid: 1\ncolumn: doing\n\
+            tags:\n  - docs\n---\n"
+        );
+        let stored = store.get("Write the docs").unwrap();
+        assert_eq!(stored.column, "doing");
+        assert_eq!(stored.tags, Some(vec!("docs".to_owned())));
+    }
+
+    #[test]
+    fn a_tasks_times_are_kept_in_its_front_matter() {
+        let dir = board_dir("times");
+        let mut store = MarkdownStore::new(&dir);
+        let mut t = task("task", "doing");
+        t.times = Times{
+            created: Some(100),
+            started: Some(200),
+            ..Times::default()
+        };
+
+        store.set("task", t.clone()).unwrap();
+
+        let text = fs::read_to_string(Path::new(&dir).join("task.md"))
+            .unwrap();
+        assert!(text.contains("times:\n  created: 100\n  started: 200\n"));
+        assert_eq!(store.get("task").unwrap().times, t.times);
+    }
+
+    #[test]
+    fn the_description_is_the_body() {
+        let dir = board_dir("body");
+        let mut store = MarkdownStore::new(&dir);
+        store.set("task", task("task", "todo")).unwrap();
+        let path = store.get("task").unwrap().description.unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        fs::write(&path, format!("{}some notes\n", text)).unwrap();
+
+        let mut moved = store.get("task").unwrap();
+        moved.column = "done".to_owned();
+        store.set("task", moved).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(body(&text), "some notes\n");
+        assert!(text.contains("column: done"));
+    }
+
+    #[test]
+    fn a_deleted_tasks_description_is_kept_in_the_trash() {
+        let dir = board_dir("trash");
+        let tasks_dir = format!("{}/tasks", dir);
+        let mut store = MarkdownStore::new(&tasks_dir);
+        let mut trash = MarkdownTrash::new(&dir);
+        store.set("task", task("task", "todo")).unwrap();
+        let path = store.get("task").unwrap().description.unwrap();
+        fs::write(&path, "---\nname: task\ncolumn: todo\n---\nnotes").unwrap();
+
+        let trashed = Trashed{
+            task: store.get("task").unwrap(),
+            position: 0,
+            deleted: 0
+        };
+        trash.set("1", trashed).unwrap();
+        store.rm("task").unwrap();
+        store.set("task", trash.get("1").unwrap().task).unwrap();
+        let copy = trash.get("1").unwrap().task.description.unwrap();
+        trash.rm("1").unwrap();
+
+        let path = store.get("task").unwrap().description.unwrap();
+        assert_eq!(body(&fs::read_to_string(&path).unwrap()), "notes");
+        assert!(!Path::new(&copy).exists());
+        assert!(trash.keys().is_empty());
+    }
+
+    #[test]
+    fn renaming_carries_the_description_across() {
+        let dir = board_dir("rename");
+        let mut store = MarkdownStore::new(&dir);
+        store.set("old", task("old", "todo")).unwrap();
+        let path = store.get("old").unwrap().description.unwrap();
+        fs::write(&path, "---\nname: old\ncolumn: todo\n---\nnotes").unwrap();
+
+        store.begin();
+        let mut renamed = store.get("old").unwrap();
+        renamed.name = "new".to_owned();
+        store.set("new", renamed).unwrap();
+        store.rm("old").unwrap();
+        store.commit().unwrap();
+
+        assert_eq!(store.keys(), vec!("new"));
+        let path = store.get("new").unwrap().description.unwrap();
+        assert_eq!(body(&fs::read_to_string(&path).unwrap()), "notes");
+    }
+
+    #[test]
+    fn titles_with_the_same_slug_get_their_own_files() {
+        let dir = board_dir("slug");
+        let mut store = MarkdownStore::new(&dir);
+        store.set("a task", task("a task", "todo")).unwrap();
+        store.set("A task!", task("A task!", "todo")).unwrap();
+
+        assert_eq!(store.keys(), vec!("A task!", "a task"));
+        assert!(Path::new(&dir).join("a-task-2.md").exists());
+    }
+
+    #[test]
+    fn columns_and_tags_are_kept_in_their_own_files() {
+        let dir = board_dir("columns");
+        let mut store = MarkdownStore::new(&format!("{}/tasks", dir));
+        let mut columns = MarkdownIndex::new(&dir, Index::Columns);
+        let mut tags = MarkdownIndex::new(&dir, Index::Tags);
+        store.set("a", task("a", "todo")).unwrap();
+        store.set("b", task("b", "todo")).unwrap();
+
+        columns.begin();
+        columns.set("todo", vec!("b".to_owned(), "a".to_owned())).unwrap();
+        columns.set("done", vec!()).unwrap();
+        columns.commit().unwrap();
+        tags.set("x", vec!("b".to_owned())).unwrap();
+
+        assert_eq!(columns.get("todo").unwrap(), vec!("b", "a"));
+        assert_eq!(columns.keys(), vec!("done", "todo"));
+        assert_eq!(tags.get("x").unwrap(), vec!("b"));
+        let text = fs::read_to_string(Path::new(&dir).join("columns.yml"))
+            .unwrap();
+        assert_eq!(text, "done: []\ntodo:\n  - b\n  - a\n");
+        let task_file = fs::read_to_string(
+            Path::new(&dir).join("tasks/a.md")
+        ).unwrap();
+        assert!(!task_file.contains("order"));
+    }
+
+    #[test]
+    fn older_boards_have_their_lists_read_from_the_task_files() {
+        let dir = board_dir("older");
+        let tasks_dir = Path::new(&dir).join("tasks");
+        fs::create_dir_all(&tasks_dir).unwrap();
+        fs::write(
+            tasks_dir.join("a.md"),
+            "---\nname: a\ncolumn: todo\ntags:\n  - x\norder: 1\n---\n"
+        ).unwrap();
+        fs::write(
+            tasks_dir.join("b.md"),
+            "---\nname: b\ncolumn: todo\norder: 0\n---\n"
+        ).unwrap();
+        fs::write(
+            tasks_dir.join("c.md"),
+            "---\nname: c\ncolumn: done\norder: 0\n---\n"
+        ).unwrap();
+        let mut columns = MarkdownIndex::new(&dir, Index::Columns);
+        let tags = MarkdownIndex::new(&dir, Index::Tags);
+
+        assert_eq!(columns.get("todo").unwrap(), vec!("b", "a"));
+        assert_eq!(tags.get("x").unwrap(), vec!("a"));
+        assert!(!Path::new(&dir).join("columns.yml").exists());
+
+        columns.begin();
+        columns.set("todo", vec!("a".to_owned(), "b".to_owned())).unwrap();
+        columns.commit().unwrap();
+
+        let columns = MarkdownIndex::new(&dir, Index::Columns);
+        assert_eq!(columns.get("todo").unwrap(), vec!("a", "b"));
+        assert_eq!(columns.get("done").unwrap(), vec!("c"));
+    }
+
+    #[test]
+    fn only_task_files_have_their_front_matter_removed() {
+        assert_eq!(body("---\nname: a\ncolumn: todo\n---\ntext"), "text");
+        assert_eq!(body("---\nnot a task\n---\ntext"), "---\nnot a task\n---\ntext");
+        assert_eq!(body("just text"), "just text");
+    }
+}
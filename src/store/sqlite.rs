@@ -0,0 +1,914 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use rusqlite::{Connection, Row, ToSql, params, NO_PARAMS};
+use crate::opts::{Task, Times};
+use crate::error::KanbenError;
+use super::{Store, Index, Transaction};
+
+// a board kept in a single sqlite database. tasks, column
+// positions and tags each have their own indexed table, so
+// listing a column or the tasks with a tag is one query
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS tasks (
+        key TEXT PRIMARY KEY,
+        id INTEGER,
+        name TEXT NOT NULL,
+        column_label TEXT NOT NULL,
+        description TEXT,
+        tags TEXT,
+        created INTEGER,
+        started INTEGER,
+        completed INTEGER,
+        moved INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS tasks_id ON tasks (id);
+    CREATE TABLE IF NOT EXISTS columns (
+        label TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        key TEXT NOT NULL,
+        PRIMARY KEY (label, position)
+    );
+    CREATE INDEX IF NOT EXISTS columns_key ON columns (key);
+    CREATE TABLE IF NOT EXISTS tags (
+        label TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        key TEXT NOT NULL,
+        PRIMARY KEY (label, position)
+    );
+    CREATE INDEX IF NOT EXISTS tags_key ON tags (key);
+    CREATE TABLE IF NOT EXISTS records (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS trash (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS journal (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS history (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+";
+
+const TASK_COLUMNS: &str = "key, id, name, column_label, description, \
+    tags, created, started, completed, moved";
+
+// columns added to the tasks table since it was first made. older
+// databases have them added when they're opened, left empty
+const ADDED_COLUMNS: [&str; 4] = ["created", "started", "completed", "moved"];
+
+// sqlite refuses statements with too many parameters
+const MAX_PARAMS: usize = 500;
+
+fn sql_error(e: rusqlite::Error) -> KanbenError {
+    KanbenError::Store(e.to_string())
+}
+
+// the database a board's stores share. the Store trait can't
+// return a read that failed, so the first one is kept here
+// instead. nothing more is written once there's been one, and
+// it's reported when the board is done with
+pub struct Database {
+    conn: Connection,
+    failed: RefCell<Option<KanbenError>>
+}
+
+pub fn open(path: &str) -> Result<Database, KanbenError> {
+    let conn = Connection::open(path).map_err(sql_error)?;
+    conn.execute_batch(SCHEMA).map_err(sql_error)?;
+    add_columns(&conn).map_err(sql_error)?;
+    Ok(Database::new(conn))
+}
+
+fn add_columns(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let existing = rows(
+        conn, "PRAGMA table_info(tasks)", NO_PARAMS, |row| row.get(1)
+    )?;
+    for column in ADDED_COLUMNS.iter().filter(|c| {
+        !existing.iter().any(|e: &String| e == *c)
+    }) {
+        conn.execute(
+            &format!("ALTER TABLE tasks ADD COLUMN {} INTEGER", column),
+            NO_PARAMS
+        )?;
+    }
+    Ok(())
+}
+
+// every row a query returns, or the first thing that went wrong
+fn rows<T, P, F>(
+    conn: &Connection,
+    sql: &str,
+    params: P,
+    row: F
+) -> Result<Vec<T>, rusqlite::Error>
+where
+    P: IntoIterator,
+    P::Item: ToSql,
+    F: FnMut(&Row) -> Result<T, rusqlite::Error>
+{
+    let mut stmt = conn.prepare(sql)?;
+    let found = stmt.query_map(params, row)?.collect();
+    found
+}
+
+impl Database {
+    fn new(conn: Connection) -> Database {
+        Database{ conn, failed: RefCell::new(None) }
+    }
+
+    // what a read found, or nothing if it failed
+    fn read<T: Default>(&self, result: Result<T, rusqlite::Error>) -> T {
+        result.unwrap_or_else(|e| {
+            self.failed.borrow_mut().get_or_insert(sql_error(e));
+            T::default()
+        })
+    }
+
+    // the first read that failed, if any has
+    pub fn check(&self) -> Result<(), KanbenError> {
+        match self.failed.borrow().as_ref() {
+            Some(e) => Err(e.clone()),
+            None => Ok(())
+        }
+    }
+
+    // writes go inside the board's transaction when there is
+    // one, and get one of their own when there isn't
+    fn write<F>(&self, change: F) -> Result<(), KanbenError>
+    where F: FnOnce(&Connection) -> Result<(), rusqlite::Error> {
+        self.check()?;
+        if !self.conn.is_autocommit() {
+            return change(&self.conn).map_err(sql_error);
+        }
+        let tx = self.conn.unchecked_transaction().map_err(sql_error)?;
+        change(&tx).map_err(sql_error)?;
+        tx.commit().map_err(sql_error)
+    }
+}
+
+impl Transaction for Database {
+    // the write lock is taken straight away, so nothing else can
+    // change the board between a change reading it and writing it
+    fn begin(&self) -> Result<(), KanbenError> {
+        self.check()?;
+        self.conn.execute_batch("BEGIN IMMEDIATE").map_err(sql_error)
+    }
+
+    fn commit(&self) -> Result<(), KanbenError> {
+        self.check()?;
+        self.conn.execute_batch("COMMIT").map_err(sql_error)
+    }
+
+    fn rollback(&self) {
+        if !self.conn.is_autocommit() {
+            let _ = self.conn.execute_batch("ROLLBACK");
+        }
+    }
+}
+
+// a row whose tags can't be read is skipped, and reported by
+// `corrupt_keys`
+fn task_from_row(row: &Row) -> Result<Option<(String, Task)>, rusqlite::Error> {
+    let tags: Option<String> = row.get(5)?;
+    let tags = match tags {
+        Some(t) => match serde_json::from_str(&t) {
+            Ok(tags) => Some(tags),
+            Err(_) => return Ok(None)
+        },
+        None => None
+    };
+    let id: Option<i64> = row.get(1)?;
+    let time = |i: usize| -> Result<Option<u64>, rusqlite::Error> {
+        Ok(row.get::<_, Option<i64>>(i)?.map(|t| t as u64))
+    };
+    Ok(Some((row.get(0)?, Task{
+        name: row.get(2)?,
+        column: row.get(3)?,
+        description: row.get(4)?,
+        tags,
+        id: id.map(|i| i as u64),
+        times: Times{
+            created: time(6)?,
+            started: time(7)?,
+            completed: time(8)?,
+            moved: time(9)?
+        }
+    })))
+}
+
+fn seconds(at: Option<u64>) -> Option<i64> {
+    at.map(|t| t as i64)
+}
+
+fn write_task(
+    conn: &Connection,
+    key: &str,
+    task: &Task
+) -> Result<(), rusqlite::Error> {
+    let tags = task.tags.as_ref()
+        .map(|t| serde_json::to_string(t).unwrap_or_default());
+    conn.execute(
+        &format!(
+            "INSERT OR REPLACE INTO tasks ({})
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            TASK_COLUMNS
+        ),
+        params![
+            key,
+            task.id.map(|i| i as i64),
+            task.name,
+            task.column,
+            task.description,
+            tags,
+            seconds(task.times.created),
+            seconds(task.times.started),
+            seconds(task.times.completed),
+            seconds(task.times.moved)
+        ]
+    ).map(|_| ())
+}
+
+pub struct SqliteStore<'a> {
+    db: &'a Database,
+    // None marks a key waiting to be removed
+    pending: Option<BTreeMap<String, Option<Task>>>
+}
+
+impl <'a> SqliteStore<'a> {
+    pub fn new(db: &'a Database) -> SqliteStore<'a> {
+        SqliteStore{ db, pending: None }
+    }
+
+    fn query(&self, sql: &str, keys: &[String]) -> Vec<(String, Task)> {
+        let found = self.db.read(rows(&self.db.conn, sql, keys, task_from_row));
+        found.into_iter().flatten().collect()
+    }
+
+    fn entries(&self) -> BTreeMap<String, Task> {
+        let mut items: BTreeMap<String, Task> = self.query(
+            &format!("SELECT {} FROM tasks", TASK_COLUMNS),
+            &[]
+        ).into_iter().collect();
+
+        if let Some(pending) = &self.pending {
+            for (key, value) in pending.iter() {
+                match value {
+                    Some(v) => items.insert(key.clone(), v.clone()),
+                    None => items.remove(key)
+                };
+            }
+        }
+
+        items
+    }
+}
+
+fn remove_task(conn: &Connection, key: &str) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM tasks WHERE key = ?1", params![key])
+        .map(|_| ())
+}
+
+impl <'a> Store<Task> for SqliteStore<'a> {
+    fn get_all(&self) -> Vec<Task> {
+        self.entries().into_iter().map(|(_, v)| v).collect()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.entries().into_iter().map(|(k, _)| k).collect()
+    }
+
+    fn corrupt_keys(&self) -> Vec<String> {
+        let tagged: Vec<(String, String)> = self.db.read(rows(
+            &self.db.conn,
+            "SELECT key, tags FROM tasks WHERE tags IS NOT NULL",
+            NO_PARAMS,
+            |row| Ok((row.get(0)?, row.get(1)?))
+        ));
+        tagged.into_iter()
+            .filter(|(_, tags)| {
+                serde_json::from_str::<Vec<String>>(tags).is_err()
+            })
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    fn get(&self, key: &str) -> Option<Task> {
+        self.get_many(&[key.to_owned()]).pop()
+    }
+
+    fn stored(&self, key: &str) -> Option<Task> {
+        self.query(
+            &format!("SELECT {} FROM tasks WHERE key = ?1", TASK_COLUMNS),
+            &[key.to_owned()]
+        ).pop().map(|(_, task)| task)
+    }
+
+    fn get_many(&self, keys: &[String]) -> Vec<Task> {
+        let nothing_pending = BTreeMap::new();
+        let pending = self.pending.as_ref().unwrap_or(&nothing_pending);
+        let stored: Vec<String> = keys.iter()
+            .filter(|k| !pending.contains_key(*k))
+            .cloned()
+            .collect();
+
+        let mut found: BTreeMap<String, Task> = BTreeMap::new();
+        for chunk in stored.chunks(MAX_PARAMS) {
+            let placeholders = vec!("?"; chunk.len()).join(", ");
+            let sql = format!(
+                "SELECT {} FROM tasks WHERE key IN ({})",
+                TASK_COLUMNS,
+                placeholders
+            );
+            found.extend(self.query(&sql, chunk));
+        }
+
+        keys.iter().filter_map(|key| match pending.get(key) {
+            Some(value) => value.clone(),
+            None => found.get(key).cloned()
+        }).collect()
+    }
+
+    fn set(&mut self, key: &str, value: Task) -> Result<(), KanbenError> {
+        match &mut self.pending {
+            Some(pending) => {
+                pending.insert(key.to_owned(), Some(value));
+                Ok(())
+            },
+            None => self.db.write(|conn| write_task(conn, key, &value))
+        }
+    }
+
+    fn rm(&mut self, key: &str) -> Result<(), KanbenError> {
+        match &mut self.pending {
+            Some(pending) => {
+                pending.insert(key.to_owned(), None);
+                Ok(())
+            },
+            None => self.db.write(|conn| remove_task(conn, key))
+        }
+    }
+
+    fn begin(&mut self) {
+        self.pending = Some(BTreeMap::new());
+    }
+
+    fn staged(&self) -> Vec<(String, Option<Task>)> {
+        self.pending.clone().map_or(vec!(), |p| p.into_iter().collect())
+    }
+
+    fn commit(&mut self) -> Result<(), KanbenError> {
+        let pending = match self.pending.take() {
+            Some(p) => p,
+            None => return Ok(())
+        };
+
+        self.db.write(|conn| {
+            for (key, value) in pending.iter() {
+                match value {
+                    Some(task) => write_task(conn, key, task)?,
+                    None => remove_task(conn, key)?
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn rollback(&mut self) {
+        self.pending = None;
+    }
+}
+
+// the column or tag lists, one row per task with its position
+pub struct SqliteIndex<'a> {
+    db: &'a Database,
+    table: &'static str,
+    pending: Option<BTreeMap<String, Option<Vec<String>>>>
+}
+
+impl <'a> SqliteIndex<'a> {
+    pub fn new(db: &'a Database, index: Index) -> SqliteIndex<'a> {
+        let table = match index {
+            Index::Columns => "columns",
+            Index::Tags => "tags"
+        };
+        SqliteIndex{ db, table, pending: None }
+    }
+
+    fn positions(
+        &self,
+        conn: &Connection,
+        label: &str
+    ) -> Result<Vec<String>, rusqlite::Error> {
+        rows(
+            conn,
+            &format!(
+                "SELECT key FROM {} WHERE label = ?1 ORDER BY position",
+                self.table
+            ),
+            params![label],
+            |row| row.get(0)
+        )
+    }
+
+    fn labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self.db.read(rows(
+            &self.db.conn,
+            &format!(
+                "SELECT DISTINCT label FROM {} ORDER BY label",
+                self.table
+            ),
+            NO_PARAMS,
+            |row| row.get(0)
+        ));
+
+        if let Some(pending) = &self.pending {
+            for (label, value) in pending.iter() {
+                labels.retain(|l| l != label);
+                if value.is_some() {
+                    labels.push(label.clone());
+                }
+            }
+            labels.sort();
+        }
+
+        labels
+    }
+
+    // only the positions that now hold a different task are
+    // written, and any past the end of the list removed, so
+    // adding a task to the bottom of a long column is one row
+    fn write(
+        &self,
+        conn: &Connection,
+        label: &str,
+        keys: Option<&Vec<String>>
+    ) -> Result<(), rusqlite::Error> {
+        let keys = keys.map_or(&[][..], |k| &k[..]);
+        let old = self.positions(conn, label)?;
+        let sql = format!(
+            "INSERT OR REPLACE INTO {} (label, position, key) \
+            VALUES (?1, ?2, ?3)",
+            self.table
+        );
+        for (i, key) in keys.iter().enumerate() {
+            if old.get(i) != Some(key) {
+                conn.execute(&sql, params![label, i as i64, key])?;
+            }
+        }
+        if old.len() > keys.len() {
+            conn.execute(
+                &format!(
+                    "DELETE FROM {} WHERE label = ?1 AND position >= ?2",
+                    self.table
+                ),
+                params![label, keys.len() as i64]
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl <'a> Store<Vec<String>> for SqliteIndex<'a> {
+    fn get_all(&self) -> Vec<Vec<String>> {
+        self.labels().iter().filter_map(|l| self.get(l)).collect()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.labels()
+    }
+
+    fn corrupt_keys(&self) -> Vec<String> {
+        vec!()
+    }
+
+    fn get(&self, key: &str) -> Option<Vec<String>> {
+        if let Some(pending) = &self.pending {
+            if let Some(value) = pending.get(key) {
+                return value.clone();
+            }
+        }
+
+        self.stored(key)
+    }
+
+    fn stored(&self, key: &str) -> Option<Vec<String>> {
+        let list = self.db.read(self.positions(&self.db.conn, key));
+        if list.is_empty() { None } else { Some(list) }
+    }
+
+    fn set(
+        &mut self,
+        key: &str,
+        value: Vec<String>
+    ) -> Result<(), KanbenError> {
+        match &mut self.pending {
+            Some(pending) => {
+                pending.insert(key.to_owned(), Some(value));
+                Ok(())
+            },
+            None => self.db.write(|conn| self.write(conn, key, Some(&value)))
+        }
+    }
+
+    fn rm(&mut self, key: &str) -> Result<(), KanbenError> {
+        match &mut self.pending {
+            Some(pending) => {
+                pending.insert(key.to_owned(), None);
+                Ok(())
+            },
+            None => self.db.write(|conn| self.write(conn, key, None))
+        }
+    }
+
+    fn begin(&mut self) {
+        self.pending = Some(BTreeMap::new());
+    }
+
+    fn staged(&self) -> Vec<(String, Option<Vec<String>>)> {
+        self.pending.clone().map_or(vec!(), |p| p.into_iter().collect())
+    }
+
+    fn commit(&mut self) -> Result<(), KanbenError> {
+        let pending = match self.pending.take() {
+            Some(p) => p,
+            None => return Ok(())
+        };
+
+        self.db.write(|conn| {
+            for (label, value) in pending.iter() {
+                self.write(conn, label, value.as_ref())?;
+            }
+            Ok(())
+        })
+    }
+
+    fn rollback(&mut self) {
+        self.pending = None;
+    }
+}
+
+// anything else, such as the board config, stored as json.
+// the trash, the journal and the task history have tables of
+// their own, laid out the same way
+pub struct SqliteRecords<'a, T> {
+    db: &'a Database,
+    table: &'static str,
+    pending: Option<BTreeMap<String, Option<T>>>,
+    value: PhantomData<T>
+}
+
+impl <'a, T: serde::Serialize + serde::de::DeserializeOwned + Clone> SqliteRecords<'a, T> {
+    pub fn new(db: &'a Database) -> SqliteRecords<'a, T> {
+        Self::in_table(db, "records")
+    }
+
+    pub fn in_table(
+        db: &'a Database,
+        table: &'static str
+    ) -> SqliteRecords<'a, T> {
+        SqliteRecords{ db, table, pending: None, value: PhantomData }
+    }
+
+    fn rows(&self) -> Vec<(String, String)> {
+        self.db.read(rows(
+            &self.db.conn,
+            &format!("SELECT key, value FROM {} ORDER BY key", self.table),
+            NO_PARAMS,
+            |row| Ok((row.get(0)?, row.get(1)?))
+        ))
+    }
+    fn entries(&self) -> BTreeMap<String, T> {
+        let mut items: BTreeMap<String, T> = self.rows().into_iter()
+            .filter_map(|(key, value)| {
+                Some((key, serde_json::from_str(&value).ok()?))
+            }).collect();
+
+        if let Some(pending) = &self.pending {
+            for (key, value) in pending.iter() {
+                match value {
+                    Some(v) => items.insert(key.clone(), v.clone()),
+                    None => items.remove(key)
+                };
+            }
+        }
+
+        items
+    }
+
+    fn write(
+        conn: &Connection,
+        table: &str,
+        key: &str,
+        value: Option<&T>
+    ) -> Result<(), rusqlite::Error> {
+        match value {
+            Some(v) => conn.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO {} (key, value) VALUES (?1, ?2)",
+                    table
+                ),
+                params![key, serde_json::to_string(v).unwrap_or_default()]
+            ),
+            None => conn.execute(
+                &format!("DELETE FROM {} WHERE key = ?1", table),
+                params![key]
+            )
+        }.map(|_| ())
+    }
+}
+
+impl <'a, T: serde::Serialize + serde::de::DeserializeOwned + Clone> Store<T> for SqliteRecords<'a, T> {
+    fn get_all(&self) -> Vec<T> {
+        self.entries().into_iter().map(|(_, v)| v).collect()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.entries().into_iter().map(|(k, _)| k).collect()
+    }
+
+    fn corrupt_keys(&self) -> Vec<String> {
+        self.rows().into_iter()
+            .filter(|(_, value)| serde_json::from_str::<T>(value).is_err())
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    fn get(&self, key: &str) -> Option<T> {
+        self.entries().remove(key)
+    }
+
+    fn stored(&self, key: &str) -> Option<T> {
+        let values: Vec<String> = self.db.read(rows(
+            &self.db.conn,
+            &format!("SELECT value FROM {} WHERE key = ?1", self.table),
+            params![key],
+            |row| row.get(0)
+        ));
+        values.first().and_then(|v| serde_json::from_str(v).ok())
+    }
+
+    fn set(&mut self, key: &str, value: T) -> Result<(), KanbenError> {
+        match &mut self.pending {
+            Some(pending) => {
+                pending.insert(key.to_owned(), Some(value));
+                Ok(())
+            },
+            None => self.db.write(|conn| {
+                Self::write(conn, self.table, key, Some(&value))
+            })
+        }
+    }
+
+    fn rm(&mut self, key: &str) -> Result<(), KanbenError> {
+        match &mut self.pending {
+            Some(pending) => {
+                pending.insert(key.to_owned(), None);
+                Ok(())
+            },
+            None => self.db.write(|conn| {
+                Self::write(conn, self.table, key, None)
+            })
+        }
+    }
+
+    fn begin(&mut self) {
+        self.pending = Some(BTreeMap::new());
+    }
+
+    fn staged(&self) -> Vec<(String, Option<T>)> {
+        self.pending.clone().map_or(vec!(), |p| p.into_iter().collect())
+    }
+
+    fn commit(&mut self) -> Result<(), KanbenError> {
+        let pending = match self.pending.take() {
+            Some(p) => p,
+            None => return Ok(())
+        };
+
+        let table = self.table;
+        self.db.write(|conn| {
+            for (key, value) in pending.iter() {
+                Self::write(conn, table, key, value.as_ref())?;
+            }
+            Ok(())
+        })
+    }
+
+    fn rollback(&mut self) {
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory() -> Database {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        Database::new(conn)
+    }
+
+    fn task(name: &str, column: &str) -> Task {
+        Task{
+            name: name.to_owned(),
+            column: column.to_owned(),
+            description: None,
+            tags: Some(vec!("a".to_owned())),
+            id: Some(1),
+            times: Times::default()
+        }
+    }
+
+    #[test]
+    fn it_stores_tasks_in_their_own_table() {
+        let db = memory();
+        let mut store = SqliteStore::new(&db);
+
+        store.set("one", task("one", "todo")).unwrap();
+        store.set("two", task("two", "done")).unwrap();
+        store.rm("two").unwrap();
+
+        assert_eq!(store.get("one"), Some(task("one", "todo")));
+        assert_eq!(store.keys(), vec!("one"));
+    }
+
+    #[test]
+    fn a_tasks_times_are_stored() {
+        let db = memory();
+        let mut store = SqliteStore::new(&db);
+        let mut t = task("one", "done");
+        t.times = Times{
+            created: Some(100),
+            started: None,
+            completed: Some(300),
+            moved: Some(300)
+        };
+
+        store.set("one", t.clone()).unwrap();
+
+        assert_eq!(store.get("one"), Some(t));
+    }
+
+    #[test]
+    fn older_databases_get_the_time_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("
+            CREATE TABLE tasks (
+                key TEXT PRIMARY KEY,
+                id INTEGER,
+                name TEXT NOT NULL,
+                column_label TEXT NOT NULL,
+                description TEXT,
+                tags TEXT
+            );
+            INSERT INTO tasks VALUES ('one', 1, 'one', 'todo', NULL, '[\"a\"]');
+        ").unwrap();
+
+        add_columns(&conn).unwrap();
+        add_columns(&conn).unwrap();
+
+        let db = Database::new(conn);
+        let store = SqliteStore::new(&db);
+        assert_eq!(store.get("one"), Some(task("one", "todo")));
+    }
+
+    #[test]
+    fn it_fetches_several_tasks_in_the_order_asked() {
+        let db = memory();
+        let mut store = SqliteStore::new(&db);
+        store.set("one", task("one", "todo")).unwrap();
+        store.set("two", task("two", "todo")).unwrap();
+
+        let keys = vec!("two".to_owned(), "gone".to_owned(), "one".to_owned());
+        let names: Vec<String> = store.get_many(&keys).into_iter()
+            .map(|t| t.name)
+            .collect();
+
+        assert_eq!(names, vec!("two", "one"));
+    }
+
+    #[test]
+    fn writes_are_held_back_until_commit() {
+        let db = memory();
+        let mut store = SqliteStore::new(&db);
+        let mut columns = SqliteIndex::new(&db, Index::Columns);
+
+        store.begin();
+        columns.begin();
+        store.set("one", task("one", "todo")).unwrap();
+        columns.set("todo", vec!("one".to_owned())).unwrap();
+        assert_eq!(store.get("one"), Some(task("one", "todo")));
+        store.rollback();
+        columns.rollback();
+
+        assert_eq!(store.get("one"), None);
+        assert_eq!(columns.get("todo"), None);
+    }
+
+    #[test]
+    fn columns_keep_their_order() {
+        let db = memory();
+        let mut columns = SqliteIndex::new(&db, Index::Columns);
+        let tags = SqliteIndex::new(&db, Index::Tags);
+
+        columns.set("todo", vec!("b".to_owned(), "a".to_owned())).unwrap();
+        columns.set("done", vec!("c".to_owned())).unwrap();
+        columns.set("todo", vec!("a".to_owned(), "b".to_owned())).unwrap();
+
+        assert_eq!(columns.get("todo").unwrap(), vec!("a", "b"));
+        assert_eq!(columns.keys(), vec!("done", "todo"));
+        assert_eq!(tags.keys(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn it_stores_other_records_as_json() {
+        let db = memory();
+        let mut records = SqliteRecords::<Vec<u32>>::new(&db);
+
+        records.set("numbers", vec!(1, 2)).unwrap();
+        db.conn.execute(
+            "INSERT INTO records (key, value) VALUES ('bad', 'x')",
+            NO_PARAMS
+        ).unwrap();
+
+        assert_eq!(records.get("numbers"), Some(vec!(1, 2)));
+        assert_eq!(records.corrupt_keys(), vec!("bad"));
+    }
+
+    #[test]
+    fn records_in_another_table_are_kept_apart() {
+        let db = memory();
+        let mut records = SqliteRecords::<u32>::new(&db);
+        let mut trash = SqliteRecords::<u32>::in_table(&db, "trash");
+
+        records.set("a", 1).unwrap();
+        trash.set("b", 2).unwrap();
+
+        assert_eq!(records.keys(), vec!("a"));
+        assert_eq!(trash.keys(), vec!("b"));
+    }
+
+    #[test]
+    fn a_read_that_fails_is_reported_and_stops_any_writes() {
+        let db = memory();
+        let mut store = SqliteStore::new(&db);
+        db.conn.execute_batch("DROP TABLE tasks").unwrap();
+
+        assert_eq!(store.get("one"), None);
+
+        assert!(db.check().is_err());
+        assert!(store.set("one", task("one", "todo")).is_err());
+    }
+
+    #[test]
+    fn only_the_positions_that_change_are_written() {
+        let db = memory();
+        let mut columns = SqliteIndex::new(&db, Index::Columns);
+        let changes = || -> i64 {
+            db.conn.query_row(
+                "SELECT total_changes()", NO_PARAMS, |r| r.get(0)
+            ).unwrap()
+        };
+        let keys = |ks: &[&str]| ks.iter().map(|k| k.to_string()).collect();
+
+        columns.set("todo", keys(&["a", "b", "c"])).unwrap();
+        let before = changes();
+        columns.set("todo", keys(&["a", "b", "c", "d"])).unwrap();
+        assert_eq!(changes() - before, 1);
+
+        let before = changes();
+        columns.set("todo", keys(&["a", "b"])).unwrap();
+        assert_eq!(changes() - before, 2);
+        assert_eq!(columns.get("todo").unwrap(), vec!("a", "b"));
+    }
+
+    #[test]
+    fn a_transaction_keeps_every_store_or_none() {
+        let db = memory();
+        let mut store = SqliteStore::new(&db);
+        let mut columns = SqliteIndex::new(&db, Index::Columns);
+
+        db.begin().unwrap();
+        store.set("one", task("one", "todo")).unwrap();
+        columns.set("todo", vec!("one".to_owned())).unwrap();
+        db.rollback();
+
+        assert_eq!(store.get("one"), None);
+        assert_eq!(columns.get("todo"), None);
+
+        db.begin().unwrap();
+        store.set("one", task("one", "todo")).unwrap();
+        columns.set("todo", vec!("one".to_owned())).unwrap();
+        db.commit().unwrap();
+
+        assert_eq!(store.get("one"), Some(task("one", "todo")));
+        assert_eq!(columns.get("todo"), Some(vec!("one".to_owned())));
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use crate::error::KanbenError;
+use super::{Store, write_file};
+
+// a handful of records kept together in one yaml file, keyed
+// by name. used for the board config of a markdown board
+pub struct YamlStore<T> {
+    path: PathBuf,
+    // None marks a key waiting to be removed
+    pending: Option<BTreeMap<String, Option<T>>>
+}
+
+impl <T: serde::Serialize + serde::de::DeserializeOwned + Clone> YamlStore<T> {
+    pub fn new(path: &str) -> YamlStore<T> {
+        YamlStore{ path: PathBuf::from(path), pending: None }
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, T>, String> {
+        match fs::read_to_string(&self.path) {
+            Ok(text) if text.trim().is_empty() => Ok(BTreeMap::new()),
+            Ok(text) => serde_yaml::from_str(&text).map_err(|e| e.to_string()),
+            Err(_) => Ok(BTreeMap::new())
+        }
+    }
+
+    fn entries(&self) -> BTreeMap<String, T> {
+        let mut items = self.read().unwrap_or_default();
+
+        if let Some(pending) = &self.pending {
+            for (key, value) in pending.iter() {
+                match value {
+                    Some(v) => items.insert(key.clone(), v.clone()),
+                    None => items.remove(key)
+                };
+            }
+        }
+
+        items
+    }
+
+    fn save(&self, items: &BTreeMap<String, T>) -> Result<(), KanbenError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| KanbenError::Store(e.to_string()))?;
+        }
+        let text = serde_yaml::to_string(items)
+            .map_err(|e| KanbenError::Store(e.to_string()))?;
+        write_file(&self.path, text.trim_start_matches("---\n"))
+    }
+
+    // an unreadable file is left alone rather than overwritten
+    fn update<F>(&self, change: F) -> Result<(), KanbenError>
+    where F: FnOnce(&mut BTreeMap<String, T>) {
+        let mut items = self.read().map_err(KanbenError::Store)?;
+        change(&mut items);
+        self.save(&items)
+    }
+}
+
+impl <T: serde::Serialize + serde::de::DeserializeOwned + Clone> Store<T> for YamlStore<T> {
+    fn get_all(&self) -> Vec<T> {
+        self.entries().into_iter().map(|(_, v)| v).collect()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.entries().into_iter().map(|(k, _)| k).collect()
+    }
+
+    fn corrupt_keys(&self) -> Vec<String> {
+        match self.read() {
+            Ok(_) => vec!(),
+            Err(_) => self.path.file_name()
+                .map(|n| vec!(n.to_string_lossy().into_owned()))
+                .unwrap_or_default()
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<T> {
+        self.entries().remove(key)
+    }
+
+    fn stored(&self, key: &str) -> Option<T> {
+        self.read().unwrap_or_default().remove(key)
+    }
+
+    fn set(&mut self, key: &str, value: T) -> Result<(), KanbenError> {
+        match &mut self.pending {
+            Some(pending) => {
+                pending.insert(key.to_owned(), Some(value));
+                Ok(())
+            },
+            None => self.update(|items| {
+                items.insert(key.to_owned(), value);
+            })
+        }
+    }
+
+    fn rm(&mut self, key: &str) -> Result<(), KanbenError> {
+        match &mut self.pending {
+            Some(pending) => {
+                pending.insert(key.to_owned(), None);
+                Ok(())
+            },
+            None => self.update(|items| {
+                items.remove(key);
+            })
+        }
+    }
+
+    fn begin(&mut self) {
+        self.pending = Some(BTreeMap::new());
+    }
+
+    fn staged(&self) -> Vec<(String, Option<T>)> {
+        self.pending.clone().map_or(vec!(), |p| p.into_iter().collect())
+    }
+
+    fn commit(&mut self) -> Result<(), KanbenError> {
+        let pending = match self.pending.take() {
+            Some(p) if !p.is_empty() => p,
+            _ => return Ok(())
+        };
+
+        self.update(|items| {
+            for (key, value) in pending.into_iter() {
+                match value {
+                    Some(v) => items.insert(key, v),
+                    None => items.remove(&key)
+                };
+            }
+        })
+    }
+
+    fn rollback(&mut self) {
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+
+    #[test]
+    fn it_keeps_records_in_one_file() {
+        let path = temp_dir().join("kanben-yaml-store-test.yml");
+        let _ = fs::remove_file(&path);
+        let mut store = YamlStore::<Vec<String>>::new(
+            &path.to_string_lossy()
+        );
+
+        store.begin();
+        store.set("todo", vec!("a".to_owned())).unwrap();
+        assert!(!path.exists());
+        store.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "todo:\n  - a\n");
+        assert_eq!(store.get("todo").unwrap(), vec!("a"));
+    }
+}
@@ -4,6 +4,8 @@ mod file;
 mod board;
 mod web;
 mod archive;
+mod storage;
+mod prompt;
 
 pub use store::StoreMock; 
 pub use editor::EditorMock;
@@ -11,3 +13,5 @@ pub use file::ReaderMock;
 pub use board::BoardMock;
 pub use web::WebMock;
 pub use archive::ArchiveMock;
+pub use storage::{StorageMock, Stores};
+pub use prompt::PromptMock;
@@ -1,17 +1,34 @@
 use std::fs::File;
+use std::cell::RefCell;
 use crate::archive::Archive;
+use crate::error::KanbenError;
 
-pub struct ArchiveMock;
+pub struct ArchiveMock {
+    snapshots: RefCell<Vec<String>>
+}
 
 impl ArchiveMock {
     pub fn new() -> ArchiveMock {
-        ArchiveMock
+        ArchiveMock{ snapshots: RefCell::new(vec!()) }
+    }
+
+    pub fn snapshot_called_with(&self, name: &str) -> bool {
+        self.snapshots.borrow().iter().any(|s| s == name)
+    }
+
+    pub fn snapshot_called(&self) -> bool {
+        !self.snapshots.borrow().is_empty()
     }
 }
 
 impl Archive for ArchiveMock {
-    fn write(&self) -> File {
-        File::create("/tmp/archivemocktest").unwrap()
+    fn write(&self) -> Result<File, KanbenError> {
+        File::create("/tmp/archivemocktest")
+            .map_err(|e| KanbenError::Archive(e.to_string()))
     }
-}
 
+    fn snapshot(&self, name: &str) -> Result<String, KanbenError> {
+        self.snapshots.borrow_mut().push(name.to_owned());
+        Ok(format!("/tmp/kanben-{}", name))
+    }
+}
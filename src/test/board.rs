@@ -1,5 +1,9 @@
-use crate::board::BoardAccess;
-use crate::opts::{Task, Column};
+use crate::board::{
+    BoardAccess, BoardConfig, Position, Problem, Trashed, Entry, Event,
+    OverLimit
+};
+use crate::error::KanbenError;
+use crate::opts::Task;
 use std::collections::HashMap;
 
 pub struct BoardMock {
@@ -7,7 +11,19 @@ pub struct BoardMock {
     set_tasks: HashMap<String, Task>,
     create_task: Option<String>,
     update_task: Option<(String, Task)>,
-    remove_tasks: Vec<String>
+    remove_tasks: Vec<String>,
+    delete_tasks: Vec<String>,
+    trashed: Vec<Trashed>,
+    restore_calls: Vec<String>,
+    emptied: Vec<String>,
+    journal: Vec<Entry>,
+    history: Vec<Event>,
+    described_calls: Vec<String>,
+    set_description_calls: Vec<(String, String)>,
+    rename_calls: Vec<(String, String)>,
+    insert_calls: Vec<(Task, bool)>,
+    move_calls: Vec<(String, Position)>,
+    over_limit: Vec<OverLimit>
 }
 
 impl BoardMock {
@@ -19,6 +35,18 @@ impl BoardMock {
             create_task: None,
             update_task: None,
             remove_tasks: vec!(),
+            delete_tasks: vec!(),
+            trashed: vec!(),
+            restore_calls: vec!(),
+            emptied: vec!(),
+            journal: vec!(),
+            history: vec!(),
+            described_calls: vec!(),
+            set_description_calls: vec!(),
+            rename_calls: vec!(),
+            insert_calls: vec!(),
+            move_calls: vec!(),
+            over_limit: vec!(),
          }
     }
 
@@ -48,6 +76,54 @@ impl BoardMock {
         self.remove_tasks.iter().any(|k| k == key)
     }
 
+    pub fn delete_called_with(&self, key: &str) -> bool {
+        self.delete_tasks.iter().any(|k| k == key)
+    }
+
+    pub fn set_trashed(&mut self, trashed: Vec<Trashed>) {
+        self.trashed = trashed;
+    }
+
+    pub fn restore_called_with(&self, key: &str) -> bool {
+        self.restore_calls.iter().any(|k| k == key)
+    }
+
+    pub fn emptied(&self) -> Vec<String> {
+        self.emptied.clone()
+    }
+
+    pub fn set_journal(&mut self, journal: Vec<Entry>) {
+        self.journal = journal;
+    }
+
+    pub fn set_history(&mut self, history: Vec<Event>) {
+        self.history = history;
+    }
+
+    pub fn set_over_limit(&mut self, over_limit: Vec<OverLimit>) {
+        self.over_limit = over_limit;
+    }
+
+    pub fn described_called_with(&self, key: &str) -> bool {
+        self.described_calls.iter().any(|k| k == key)
+    }
+
+    pub fn set_description_called_with(&self, key: &str, path: &str) -> bool {
+        self.set_description_calls.iter().any(|(k, p)| k == key && p == path)
+    }
+
+    pub fn rename_called_with(&self, key: &str, new_key: &str) -> bool {
+        self.rename_calls.iter().any(|(k, n)| k == key && n == new_key)
+    }
+
+    pub fn move_called_with(&self, key: &str, position: &Position) -> bool {
+        self.move_calls.iter().any(|(k, p)| k == key && p == position)
+    }
+
+    pub fn insert_called_with(&self, task: &Task, top: bool) -> bool {
+        self.insert_calls.iter().any(|(t, p)| t == task && *p == top)
+    }
+
 }
 
 impl BoardAccess for BoardMock {
@@ -59,8 +135,14 @@ impl BoardAccess for BoardMock {
         vec!()
     }
 
-    fn create_task(&mut self, key: &str, _: Option<String>){
+    fn create_task(
+        &mut self, key: &str, _: Option<String>
+    ) -> Result<(), KanbenError> {
+        if self.get(key).is_some() {
+            return Err(KanbenError::TaskExists(key.to_string()));
+        }
         self.create_task = Some(key.to_string());
+        Ok(())
     }
 
     fn get(&self, key: &str) -> Option<Task>{
@@ -70,19 +152,179 @@ impl BoardAccess for BoardMock {
         }
     }
 
-    fn update(&mut self, key: &str, task: Task){
+    fn find_by_id(&self, id: u64) -> Option<Task> {
+        self.set_tasks.values()
+            .chain(self.tasks.iter())
+            .find(|t| t.id == Some(id))
+            .cloned()
+    }
+
+    fn update(&mut self, key: &str, task: Task) -> Result<(), KanbenError> {
         self.update_task = Some((key.to_string(), task));
+        Ok(())
     }
 
-    fn remove(&mut self, key: &str){
+    fn force_update(
+        &mut self, key: &str, task: Task
+    ) -> Result<(), KanbenError> {
+        self.update(key, task)
+    }
+
+    fn set_description(
+        &mut self, key: &str, path: &str
+    ) -> Result<(), KanbenError> {
+        self.set_description_calls.push((key.to_owned(), path.to_owned()));
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> Result<(), KanbenError> {
         self.remove_tasks.push(key.to_string());
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &str) -> Result<(), KanbenError> {
+        self.delete_tasks.push(key.to_string());
+        Ok(())
+    }
+
+    fn trashed(&self) -> Vec<Trashed> {
+        self.trashed.clone()
+    }
+
+    fn restore(&mut self, key: &str) -> Result<Task, KanbenError> {
+        self.restore_calls.push(key.to_string());
+        self.trashed.iter()
+            .find(|t| t.key() == key)
+            .map(|t| t.task.clone())
+            .ok_or(KanbenError::NotInTrash(key.to_string()))
+    }
+
+    fn empty_trash(&mut self, keys: &[String]) -> Result<(), KanbenError> {
+        self.emptied.extend(keys.iter().cloned());
+        Ok(())
+    }
+
+    fn journal(&self) -> Vec<Entry> {
+        self.journal.clone()
+    }
+
+    // marks the entry as undone or redone, without changing
+    // anything else
+    fn undo(&mut self) -> Result<Option<Entry>, KanbenError> {
+        let entry = self.journal.iter_mut().rev().find(|e| !e.undone);
+        Ok(entry.map(|e| {
+            e.undone = true;
+            e.clone()
+        }))
+    }
+
+    fn redo(&mut self) -> Result<Option<Entry>, KanbenError> {
+        let entry = self.journal.iter_mut().find(|e| e.undone);
+        Ok(entry.map(|e| {
+            e.undone = false;
+            e.clone()
+        }))
+    }
+
+    fn history(&self, key: &str) -> Vec<Event> {
+        self.history.iter().filter(|e| e.task == key).cloned().collect()
+    }
+
+    fn all_history(&self) -> Vec<Event> {
+        self.history.clone()
+    }
+
+    fn described(&mut self, key: &str) -> Result<(), KanbenError> {
+        self.described_calls.push(key.to_string());
+        Ok(())
+    }
+
+    fn take_over_limit(&mut self) -> Vec<OverLimit> {
+        std::mem::take(&mut self.over_limit)
+    }
+
+    fn together<R, F>(
+        &mut self,
+        _name: &str,
+        change: F
+    ) -> Result<R, KanbenError>
+    where F: FnOnce(&mut Self) -> Result<R, KanbenError> {
+        change(self)
+    }
+
+    fn insert_task(
+        &mut self, task: Task, top: bool
+    ) -> Result<String, KanbenError> {
+        if self.get(&task.name).is_some() {
+            return Err(KanbenError::TaskExists(task.name));
+        }
+        let column = task.column.clone();
+        self.set_tasks.insert(task.name.clone(), task.clone());
+        self.insert_calls.push((task, top));
+        Ok(column)
+    }
+
+    fn rename(
+        &mut self, key: &str, new_key: &str
+    ) -> Result<(), KanbenError> {
+        self.rename_calls.push((key.to_string(), new_key.to_string()));
+        if let Some(mut task) = self.set_tasks.remove(key) {
+            task.name = new_key.to_string();
+            self.set_tasks.insert(new_key.to_string(), task);
+        }
+        Ok(())
+    }
+
+    fn reindex_columns(&mut self) -> Result<usize, KanbenError> {
+        unimplemented!()
+    }
+
+    fn check(&self) -> Vec<Problem> {
+        unimplemented!()
+    }
+
+    fn repair(&mut self) -> Result<usize, KanbenError> {
+        unimplemented!()
+    }
+
+    fn top_priority(&mut self, _key: &str) -> Result<(), KanbenError> {
+        unimplemented!()
+    }
+
+    fn move_within(
+        &mut self, key: &str, position: Position
+    ) -> Result<(), KanbenError> {
+        self.move_calls.push((key.to_string(), position));
+        Ok(())
+    }
+
+    fn get_config(&self) -> BoardConfig {
+        BoardConfig::default()
+    }
+
+    fn save_config(
+        &mut self, _config: BoardConfig
+    ) -> Result<(), KanbenError> {
+        unimplemented!()
+    }
+
+    fn add_column(&mut self, _name: &str) -> Result<(), KanbenError> {
+        unimplemented!()
+    }
+
+    fn remove_column(&mut self, _name: &str) -> Result<(), KanbenError> {
+        unimplemented!()
     }
 
-    fn reindex_columns(&mut self) -> Result<usize, ()> {
+    fn rename_column(
+        &mut self, _name: &str, _new_name: &str
+    ) -> Result<(), KanbenError> {
         unimplemented!()
     }
 
-    fn top_priority(&mut self, _key: &str) {
+    fn reorder_column(
+        &mut self, _name: &str, _position: usize
+    ) -> Result<(), KanbenError> {
         unimplemented!()
     }
 }
@@ -95,8 +337,8 @@ mod tests {
     fn it_can_set_all_tasks_to_be_returned() {
         let mut board_mock = BoardMock::new();
         let tasks = vec!(
-            get_task("task1", Column::Doing),
-            get_task("task2", Column::Todo),
+            get_task("task1", "doing"),
+            get_task("task2", "todo"),
         );
 
         board_mock.set_tasks(tasks.clone());
@@ -107,7 +349,7 @@ mod tests {
     #[test]
     fn it_can_set_for_get() {
         let mut board_mock = BoardMock::new();
-        let task = get_task("task1", Column::Doing);
+        let task = get_task("task1", "doing");
         board_mock.set("task1", task.clone());
 
         assert_eq!(board_mock.get("task1").unwrap(), task.clone());
@@ -123,7 +365,7 @@ mod tests {
     fn it_can_report_on_created_tasks() {
         let mut board_mock = BoardMock::new();
 
-        board_mock.create_task("task1", None);
+        board_mock.create_task("task1", None).unwrap();
 
         assert!(
             board_mock.create_task_called_with("task1")
@@ -142,8 +384,8 @@ mod tests {
     fn it_can_report_on_updated_tasks() {
         let mut board_mock = BoardMock::new();
 
-        let task = get_task("task1", Column::Doing);
-        board_mock.update("task1", task.clone());
+        let task = get_task("task1", "doing");
+        board_mock.update("task1", task.clone()).unwrap();
 
         assert!(
             board_mock.update_called_with("task1", &task)
@@ -153,7 +395,7 @@ mod tests {
     #[test]
     fn it_returns_false_when_update_check_doesnt_match() {
         let board_mock = BoardMock::new();
-        let task = get_task("task1", Column::Doing);
+        let task = get_task("task1", "doing");
 
         assert!(
             !board_mock.update_called_with("task1", &task)
@@ -163,7 +405,7 @@ mod tests {
     #[test]
     fn it_can_report_on_removed_tasks() {
         let mut board_mock = BoardMock::new();
-        board_mock.remove("task1");
+        board_mock.remove("task1").unwrap();
         assert!(
             board_mock.remove_called_with("task1")
         );
@@ -180,21 +422,20 @@ mod tests {
     #[test]
     fn it_returns_true_for_all_remove_checks() {
         let mut board_mock = BoardMock::new();
-        board_mock.remove("task1");
-        board_mock.remove("task2");
-        board_mock.remove("task3");
+        board_mock.remove("task1").unwrap();
+        board_mock.remove("task2").unwrap();
+        board_mock.remove("task3").unwrap();
         assert!(board_mock.remove_called_with("task1"));
         assert!(board_mock.remove_called_with("task2"));
         assert!(board_mock.remove_called_with("task3"));
 
     }
 
-    fn get_task(key: &str, column: Column) -> Task {
+    fn get_task(key: &str, column: &str) -> Task {
         Task{
             name: key.to_owned(),
-            column,
-            description: None,
-            tags: None
+            column: column.to_owned(),
+            ..Task::default()
         }
     }
 }
@@ -1,9 +1,22 @@
 use crate::editor::Editor;
+use crate::error::KanbenError;
 
 pub struct EditorMock{
     last_open_call: Option<String>,
     last_create_call: Option<String>,
-    create_response: Result<String, ()>,
+    create_response: Result<String, KanbenError>,
+    owned: Vec<String>,
+    last_adopt_call: Option<(String, String)>,
+    adopt_response: Result<String, KanbenError>,
+    edit_text_calls: Vec<(String, String)>,
+    edit_text_responses: Vec<Result<String, KanbenError>>,
+    last_save_call: Option<(Option<String>, String, String)>,
+    save_response: Result<String, KanbenError>,
+    trash_calls: Vec<(String, String)>,
+    trash_response: Result<String, KanbenError>,
+    stored: Vec<String>,
+    trashed: Vec<String>,
+    purge_calls: Vec<String>,
 }
 
 impl EditorMock {
@@ -11,7 +24,19 @@ impl EditorMock {
         EditorMock{
             last_open_call: None,
             last_create_call: None,
-            create_response: Ok("filepath".to_string())
+            create_response: Ok("filepath".to_string()),
+            owned: vec!(),
+            last_adopt_call: None,
+            adopt_response: Ok("adopted-filepath".to_string()),
+            edit_text_calls: vec!(),
+            edit_text_responses: vec!(),
+            last_save_call: None,
+            save_response: Ok("saved-filepath".to_string()),
+            trash_calls: vec!(),
+            trash_response: Ok("trashed-filepath".to_string()),
+            stored: vec!(),
+            trashed: vec!(),
+            purge_calls: vec!()
         }
     }
 
@@ -33,20 +58,156 @@ impl EditorMock {
         }
     }
 
-    pub fn return_from_create(&mut self, path: Result<String, ()>) {
-        self.create_response = path.clone();
+    pub fn return_from_create(&mut self, path: Result<String, KanbenError>) {
+        self.create_response = path;
+    }
+
+    pub fn set_owned(&mut self, path: &str) {
+        self.owned.push(path.to_string());
+    }
+
+    pub fn adopt_called_with(&self, path: &str, key: &str) -> bool {
+        match &self.last_adopt_call {
+            Some((p, k)) => p == path && k == key,
+            _  => false
+        }
+    }
+
+    pub fn return_from_adopt(&mut self, path: Result<String, KanbenError>) {
+        self.adopt_response = path;
+    }
+
+    // each call takes the next response in turn. once they run
+    // out the text is handed back unchanged
+    pub fn return_from_edit_text(
+        &mut self,
+        text: Result<String, KanbenError>
+    ) {
+        self.edit_text_responses.push(text);
+    }
+
+    pub fn edit_text_called_with(&self, name: &str, text: &str) -> bool {
+        match self.edit_text_calls.last() {
+            Some((n, t)) => n == name && t == text,
+            _  => false
+        }
+    }
+
+    pub fn edit_text_call_count(&self) -> usize {
+        self.edit_text_calls.len()
+    }
+
+    pub fn save_called_with(
+        &self,
+        path: Option<&str>,
+        key: &str,
+        text: &str
+    ) -> bool {
+        match &self.last_save_call {
+            Some((p, k, t)) => p.as_deref() == path && k == key && t == text,
+            _  => false
+        }
+    }
+
+    pub fn save_called(&self) -> bool {
+        self.last_save_call.is_some()
+    }
+
+    pub fn trash_called_with(&self, path: &str, key: &str) -> bool {
+        self.trash_calls.iter().any(|(p, k)| p == path && k == key)
+    }
+
+    pub fn trash_called(&self) -> bool {
+        !self.trash_calls.is_empty()
+    }
+
+    pub fn return_from_trash(&mut self, path: Result<String, KanbenError>) {
+        self.trash_response = path;
+    }
+
+    pub fn set_stored(&mut self, files: Vec<&str>) {
+        self.stored = files.iter().map(|f| f.to_string()).collect();
+    }
+
+    pub fn set_trashed(&mut self, files: Vec<&str>) {
+        self.trashed = files.iter().map(|f| f.to_string()).collect();
+    }
+
+    pub fn purged(&self) -> Vec<String> {
+        self.purge_calls.clone()
     }
 }
 
 impl Editor for EditorMock {
-    fn open(&mut self, path: &str) {
+    fn open(&mut self, path: &str) -> Result<(), KanbenError> {
         self.last_open_call = Some(path.to_string());
+        Ok(())
     }
 
-    fn create(&mut self, key: &str) -> Result<String, ()> {
+    fn create(&mut self, key: &str) -> Result<String, KanbenError> {
         self.last_create_call = Some(key.to_string());
         self.create_response.clone()
     }
+
+    // files the mock makes are kept in a folder per key
+    fn path_for(&self, key: &str) -> String {
+        format!("files/{}/description.md", key)
+    }
+
+    fn owns(&self, path: &str) -> bool {
+        self.owned.iter().any(|p| p == path)
+    }
+
+    fn adopt(
+        &mut self, path: &str, key: &str
+    ) -> Result<String, KanbenError> {
+        self.last_adopt_call = Some((path.to_string(), key.to_string()));
+        self.adopt_response.clone()
+    }
+
+    fn edit_text(
+        &mut self, name: &str, text: &str
+    ) -> Result<String, KanbenError> {
+        self.edit_text_calls.push((name.to_string(), text.to_string()));
+        if self.edit_text_responses.is_empty() {
+            Ok(text.to_string())
+        } else {
+            self.edit_text_responses.remove(0)
+        }
+    }
+
+    fn save(
+        &mut self, path: Option<&str>, key: &str, text: &str
+    ) -> Result<String, KanbenError> {
+        self.last_save_call = Some((
+            path.map(|p| p.to_string()), key.to_string(), text.to_string()
+        ));
+        self.save_response.clone()
+    }
+
+    fn trash(
+        &mut self, path: &str, key: &str
+    ) -> Result<String, KanbenError> {
+        self.trash_calls.push((path.to_string(), key.to_string()));
+        self.trash_response.clone()
+    }
+
+    fn trash_path_for(&self, key: &str) -> String {
+        format!("files/.trash/{}/description.md", key)
+    }
+
+    fn stored_files(&self) -> Vec<String> {
+        self.stored.clone()
+    }
+
+    fn trashed_files(&self) -> Vec<String> {
+        self.trashed.clone()
+    }
+
+    fn purge(&mut self, path: &str) -> Result<(), KanbenError> {
+        self.purge_calls.push(path.to_string());
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -57,7 +218,7 @@ mod tests {
     fn it_can_match_on_open_parameters() {
         let mut editor = EditorMock::new();
 
-        editor.open("abc");
+        editor.open("abc").unwrap();
 
         assert!(editor.open_called_with("abc"));
     }
@@ -66,7 +227,7 @@ mod tests {
     fn it_can_report_when_open_is_called() {
         let mut editor = EditorMock::new();
 
-        editor.open("abc");
+        editor.open("abc").unwrap();
 
         assert!(editor.open_called());
     }
@@ -0,0 +1,27 @@
+use crate::prompt::Prompt;
+
+pub struct PromptMock {
+    answer: bool,
+    asked: bool
+}
+
+impl PromptMock {
+    pub fn new() -> PromptMock {
+        PromptMock{ answer: false, asked: false }
+    }
+
+    pub fn answer_yes(&mut self) {
+        self.answer = true;
+    }
+
+    pub fn was_asked(&self) -> bool {
+        self.asked
+    }
+}
+
+impl Prompt for PromptMock {
+    fn confirm(&mut self) -> bool {
+        self.asked = true;
+        self.answer
+    }
+}
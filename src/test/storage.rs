@@ -0,0 +1,100 @@
+use crate::board::{Board, BoardConfig, Trashed, Entry, Event, Pending};
+use crate::commands::{BoardStorage, BoardVisitor};
+use crate::error::KanbenError;
+use crate::opts::Task;
+use crate::test::StoreMock;
+use crate::store::Store;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+pub type MockBoard<'a> = Board<
+    'a, StoreMock<Task>, StoreMock<Vec<String>>, StoreMock<BoardConfig>
+>;
+
+// a real board over mock stores, for commands that need the
+// board to keep track of what they've changed
+pub struct Stores {
+    pub tasks: StoreMock<Task>,
+    pub columns: StoreMock<Vec<String>>,
+    pub tags: StoreMock<Vec<String>>,
+    pub config: StoreMock<BoardConfig>,
+    pub trash: StoreMock<Trashed>,
+    pub journal: StoreMock<Entry>,
+    pub history: StoreMock<Event>,
+    pub pending: StoreMock<Pending>
+}
+
+impl Stores {
+    pub fn new() -> Stores {
+        Stores{
+            tasks: StoreMock::new(),
+            columns: StoreMock::new(),
+            tags: StoreMock::new(),
+            config: StoreMock::new(),
+            trash: StoreMock::new(),
+            journal: StoreMock::new(),
+            history: StoreMock::new(),
+            pending: StoreMock::new()
+        }
+    }
+
+    pub fn board(&mut self) -> MockBoard<'_> {
+        Board::new(
+            &mut self.tasks,
+            &mut self.columns,
+            &mut self.tags,
+            &mut self.config
+        ).with_trash(&mut self.trash)
+            .with_journal(&mut self.journal)
+            .with_history(&mut self.history)
+            .with_pending(&mut self.pending)
+    }
+}
+
+// each named board is a real board over mock stores, made the
+// first time it's asked for
+pub struct StorageMock {
+    boards: RefCell<HashMap<String, Stores>>,
+    dropped: Vec<String>
+}
+
+impl StorageMock {
+    pub fn new() -> StorageMock {
+        StorageMock{
+            boards: RefCell::new(HashMap::new()),
+            dropped: vec!()
+        }
+    }
+
+    pub fn setup<F: FnOnce(&mut MockBoard)>(&mut self, name: &str, f: F) {
+        let mut boards = self.boards.borrow_mut();
+        let stores = boards.entry(name.to_owned()).or_insert_with(Stores::new);
+        f(&mut stores.board());
+    }
+
+    pub fn dropped(&self) -> Vec<String> {
+        self.dropped.clone()
+    }
+}
+
+impl BoardStorage for StorageMock {
+    fn task_count(&self, board: &str) -> Result<usize, KanbenError> {
+        Ok(self.boards.borrow().get(board)
+            .map(|s| s.tasks.keys().len())
+            .unwrap_or(0))
+    }
+
+    fn drop_board(&mut self, board: &str) -> Result<(), KanbenError> {
+        self.boards.borrow_mut().remove(board);
+        self.dropped.push(board.to_owned());
+        Ok(())
+    }
+
+    fn with_board<V: BoardVisitor>(
+        &self, board: &str, visitor: &mut V
+    ) -> Result<(), KanbenError> {
+        let mut boards = self.boards.borrow_mut();
+        let stores = boards.entry(board.to_owned()).or_insert_with(Stores::new);
+        visitor.visit(&mut stores.board())
+    }
+}
@@ -1,11 +1,17 @@
 use crate::opts::*;
 use crate::store::Store;
-use std::collections::HashMap;
+use crate::error::KanbenError;
+use std::collections::{BTreeMap, HashMap};
 
 pub struct StoreMock<T> {
     is_set_called: bool,
     rm_calls: Vec<String>,
     temp_store: HashMap<String, T>,
+    fail_writes: bool,
+    corrupt_keys: Vec<String>,
+    snapshot: Option<(HashMap<String, T>, Vec<String>, bool)>,
+    staged: BTreeMap<String, Option<T>>,
+    fail_commit: bool,
 }
 
 impl <T: std::cmp::PartialEq + std::clone::Clone> StoreMock<T> {
@@ -14,6 +20,11 @@ impl <T: std::cmp::PartialEq + std::clone::Clone> StoreMock<T> {
             is_set_called: false,
             rm_calls: vec!(),
             temp_store: HashMap::new(),
+            fail_writes: false,
+            corrupt_keys: vec!(),
+            snapshot: None,
+            staged: BTreeMap::new(),
+            fail_commit: false,
         }
     }
 
@@ -33,6 +44,20 @@ impl <T: std::cmp::PartialEq + std::clone::Clone> StoreMock<T> {
         self.rm_calls.iter().any(|k| k == key)
     }
 
+    pub fn fail_writes(&mut self) {
+        self.fail_writes = true;
+    }
+
+    // writes go through, but committing them fails once and
+    // they're rolled back
+    pub fn fail_next_commit(&mut self) {
+        self.fail_commit = true;
+    }
+
+    pub fn set_corrupt_keys(&mut self, keys: Vec<&str>) {
+        self.corrupt_keys = keys.iter().map(|k| k.to_string()).collect();
+    }
+
     pub fn bulk_insert(
         &mut self,
         items: Vec<(&str, T)>
@@ -50,9 +75,28 @@ impl <T: std::clone::Clone> Store<T> for StoreMock <T> {
             .collect::<Vec<T>>()
     }
 
-    fn set(&mut self, key: &str, value: T) {
+    fn set(&mut self, key: &str, value: T) -> Result<(), KanbenError> {
+        if self.fail_writes {
+            return Err(KanbenError::Store("write failed".to_owned()));
+        }
         self.is_set_called = true;
+        if self.snapshot.is_some() {
+            self.staged.insert(key.to_string(), Some(value.clone()));
+        }
         self.temp_store.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let mut keys = self.temp_store.keys()
+            .cloned()
+            .collect::<Vec<String>>();
+        keys.sort();
+        keys
+    }
+
+    fn corrupt_keys(&self) -> Vec<String> {
+        self.corrupt_keys.clone()
     }
 
     fn get(&self, key: &str) -> Option<T> {
@@ -62,8 +106,57 @@ impl <T: std::clone::Clone> Store<T> for StoreMock <T> {
         }
     }
 
-    fn rm(&mut self, key: &str) {
+    fn stored(&self, key: &str) -> Option<T> {
+        match &self.snapshot {
+            Some((store, _, _)) => store.get(key).cloned(),
+            None => self.get(key)
+        }
+    }
+
+    fn rm(&mut self, key: &str) -> Result<(), KanbenError> {
+        if self.fail_writes {
+            return Err(KanbenError::Store("write failed".to_owned()));
+        }
         self.rm_calls.push(String::from(key));
+        if self.snapshot.is_some() {
+            self.staged.insert(key.to_string(), None);
+        }
+        self.temp_store.remove(key);
+        Ok(())
+    }
+
+    // a snapshot taken at the start is restored on rollback
+    fn begin(&mut self) {
+        if self.snapshot.is_none() {
+            self.snapshot = Some((
+                self.temp_store.clone(),
+                self.rm_calls.clone(),
+                self.is_set_called
+            ));
+        }
+    }
+
+    fn staged(&self) -> Vec<(String, Option<T>)> {
+        self.staged.clone().into_iter().collect()
+    }
+
+    fn commit(&mut self) -> Result<(), KanbenError> {
+        if self.fail_commit {
+            self.fail_commit = false;
+            return Err(KanbenError::Store("commit failed".to_owned()));
+        }
+        self.snapshot = None;
+        self.staged.clear();
+        Ok(())
+    }
+
+    fn rollback(&mut self) {
+        self.staged.clear();
+        if let Some((store, rm_calls, is_set_called)) = self.snapshot.take() {
+            self.temp_store = store;
+            self.rm_calls = rm_calls;
+            self.is_set_called = is_set_called;
+        }
     }
 }
 
@@ -75,8 +168,8 @@ mod tests {
     fn it_records_what_is_set() {
         let mut store = StoreMock::new();
         let name = String::from("test");
-        let task = get_task(&name, Column::Doing);
-        store.set(&name, task.clone());
+        let task = get_task(&name, "doing");
+        store.set(&name, task.clone()).unwrap();
 
         assert!(store.set_called_with(&name, &task));
     }
@@ -85,10 +178,10 @@ mod tests {
     fn it_returns_false_if_set_not_called_with_passed_values() {
         let mut store = StoreMock::new();
         let name = "test".to_owned();
-        let passed_task = get_task("fake test", Column::Doing);
-        let checked_task = get_task(&name, Column::Doing);
+        let passed_task = get_task("fake test", "doing");
+        let checked_task = get_task(&name, "doing");
 
-        store.set(&name, passed_task.clone());
+        store.set(&name, passed_task.clone()).unwrap();
         assert!(!store.set_called_with(&name, &checked_task));
     }
 
@@ -104,8 +197,8 @@ mod tests {
     fn it_returns_true_when_set_was_called() {
         let mut store = StoreMock::new();
         let name = String::from("test");
-        let task = get_task("fake test", Column::Doing);
-        store.set(&name, task.clone());
+        let task = get_task("fake test", "doing");
+        store.set(&name, task.clone()).unwrap();
 
         assert!(store.set_called());
     }
@@ -114,7 +207,7 @@ mod tests {
     fn it_can_bulk_add_tasks() {
         let mut store = StoreMock::new();
         let name = String::from("test");
-        let task = get_task("fake test", Column::Doing);
+        let task = get_task("fake test", "doing");
         store.bulk_insert(vec!((&name, task.clone())));
 
         assert_eq!(
@@ -126,24 +219,47 @@ mod tests {
     #[test]
     fn it_can_report_on_calls_to_rm() {
         let mut store: StoreMock<()> = StoreMock::new();
-        store.rm("test");
+        store.rm("test").unwrap();
         assert!(store.rm_called_with("test"));
     }
 
     #[test]
     fn it_can_report_multiple_calls_to_rm() {
         let mut store: StoreMock<()> = StoreMock::new();
-        store.rm("test");
-        store.rm("test2");
+        store.rm("test").unwrap();
+        store.rm("test2").unwrap();
         assert!(store.rm_called_with("test"));
         assert!(store.rm_called_with("test2"));
     }
 
+    #[test]
+    fn it_can_roll_back_writes() {
+        let mut store = StoreMock::new();
+        store.set("a", get_task("a", "doing")).unwrap();
+
+        store.begin();
+        store.set("b", get_task("b", "doing")).unwrap();
+        store.rm("a").unwrap();
+        store.rollback();
+
+        assert_eq!(store.get_all(), vec!(get_task("a", "doing")));
+        assert!(!store.rm_called_with("a"));
+    }
+
+    #[test]
+    fn it_can_fail_writes() {
+        let mut store = StoreMock::new();
+        store.fail_writes();
+
+        assert!(store.set("test", get_task("test", "doing")).is_err());
+        assert!(!store.set_called());
+    }
+
     #[test]
     fn it_can_set_the_response_of_get() {
         let mut store = StoreMock::new();
-        let task = get_task("test", Column::Doing);
-        store.set("test", task.clone());
+        let task = get_task("test", "doing");
+        store.set("test", task.clone()).unwrap();
 
         let returned_task: Task = store.get("test").unwrap();
 
@@ -157,7 +273,7 @@ mod tests {
     fn it_can_bulk_add_for_get_by_key() {
         let mut store = StoreMock::new();
         let key = String::from("test");
-        let task = get_task("fake test", Column::Doing);
+        let task = get_task("fake test", "doing");
         store.bulk_insert(vec!((&key, task.clone())));
 
         assert_eq!(
@@ -166,12 +282,11 @@ mod tests {
         );
     }
 
-    fn get_task(key: &str, column: Column) -> Task {
+    fn get_task(key: &str, column: &str) -> Task {
         Task {
             name: key.to_owned(),
-            column,
-            description: None,
-            tags: None
+            column: column.to_owned(),
+            ..Task::default()
         }
     }
 }
@@ -1,25 +1,37 @@
 use crate::web::Web;
+use crate::error::KanbenError;
 use std::fs::File;
 
 pub struct WebMock{
-    is_send_to_backup_called: bool
+    is_send_to_backup_called: bool,
+    fail_backup: bool
 }
 
 impl WebMock {
     pub fn new() -> WebMock {
         WebMock{
-            is_send_to_backup_called: false
+            is_send_to_backup_called: false,
+            fail_backup: false
         }
     }
 
+    pub fn fail_backup(&mut self) {
+        self.fail_backup = true;
+    }
+
     pub fn send_backup_called(&self) -> bool {
         self.is_send_to_backup_called
     }
 }
 
 impl Web for WebMock {
-    fn send_backup(&mut self, _: &str, _: File) -> Result<(), ()> {
+    fn send_backup(
+        &mut self, _: &str, _: File
+    ) -> Result<(), KanbenError> {
         self.is_send_to_backup_called = true;
+        if self.fail_backup {
+            return Err(KanbenError::Web("upload failed".to_owned()));
+        }
         Ok(())
     }
 }
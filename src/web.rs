@@ -1,5 +1,6 @@
 use std::fs::File;
 use log::{info, error};
+use crate::error::KanbenError;
 
 mod client;
 pub use client::Client;
@@ -10,7 +11,7 @@ pub use client::Client;
 pub trait Web {
     fn send_backup(
         &mut self, filename: &str, file: File
-    ) -> Result<(), ()>;
+    ) -> Result<(), KanbenError>;
 }
 
 pub struct WebClient {
@@ -30,23 +31,23 @@ impl Web for WebClient {
         &mut self,
         filename: &str,
         file: File
-    ) -> Result<(), ()>{
+    ) -> Result<(), KanbenError>{
         info!("beginning backup: [{}]", filename);
-        let res_result = self.client.begin_backup(filename);
-        if res_result.is_err() {
-            // todo what happens in failure?
-            return Err(());
-        }
+        let response = self.client.begin_backup(filename)
+            .map_err(|e| {
+                error!("unable to begin backup: {}", e);
+                KanbenError::Web(e.to_string())
+            })?;
 
-        let response = res_result.unwrap();
         info!("msg from signed url: {}", response.message);
         let r = self.client.put_backup(&response.url, file);
         if r.is_ok() {
             info!("file upload successful");
             Ok(())
         } else {
-            error!("response from file put: {}", r.err().unwrap());
-            Err(())
+            let e = r.err().unwrap();
+            error!("response from file put: {}", e);
+            Err(KanbenError::Web(e.to_string()))
         }
     }
 }